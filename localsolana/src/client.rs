@@ -0,0 +1,111 @@
+// Account lists for building instructions off-chain, in the order and with the
+// writability the processor reads them. Compiled in with the `client` feature.
+use solana_program::{
+    instruction::AccountMeta,
+    pubkey::Pubkey,
+    system_program::ID as SYSTEM_PROGRAM_ID,
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::ID as TOKEN_PROGRAM_ID;
+use crate::state::{IndexBucket, MovieAggregate};
+
+// `[author, title]`, the address of a review
+pub fn review_pda(program_id: &Pubkey, author: &Pubkey, title: &str) -> Pubkey {
+    Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], program_id).0
+}
+
+// AddMovieReview and AddMovieReviewV2
+pub fn add_review_accounts(program_id: &Pubkey, initializer: &Pubkey, title: &str) -> Vec<AccountMeta> {
+    let review_pda = review_pda(program_id, initializer, title);
+    let (counter_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), b"comment"], program_id);
+    let (mint, _bump_seed) = Pubkey::find_program_address(&[b"token_mint"], program_id);
+    let (mint_auth, _bump_seed) = Pubkey::find_program_address(&[b"token_auth"], program_id);
+    let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], program_id);
+    let (profile_pda, _bump_seed) = Pubkey::find_program_address(&[initializer.as_ref(), b"profile"], program_id);
+    let title_hash = MovieAggregate::title_hash(title);
+    let (aggregate_pda, _bump_seed) = Pubkey::find_program_address(&[title_hash.as_ref(), b"aggregate"], program_id);
+    let (index_pda, _bump_seed) = Pubkey::find_program_address(&[b"index", &[IndexBucket::key(title)]], program_id);
+    let (global_seq_pda, _bump_seed) = Pubkey::find_program_address(&[b"global_seq"], program_id);
+
+    vec![
+        AccountMeta::new(*initializer, true),
+        AccountMeta::new(review_pda, false),
+        AccountMeta::new(counter_pda, false),
+        AccountMeta::new(mint, false),
+        AccountMeta::new_readonly(mint_auth, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        AccountMeta::new(config_pda, false),
+        AccountMeta::new(profile_pda, false),
+        AccountMeta::new(aggregate_pda, false),
+        AccountMeta::new(index_pda, false),
+        AccountMeta::new(global_seq_pda, false),
+    ]
+}
+
+// UpdateMovieReview, UpdateRating and UpdateDescription; the last never reads the
+// aggregate or the system program. The config is writable so callers can append the
+// helpful-votes bonus accounts
+pub fn update_review_accounts(program_id: &Pubkey, initializer: &Pubkey, title: &str) -> Vec<AccountMeta> {
+    let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], program_id);
+    let title_hash = MovieAggregate::title_hash(title);
+    let (aggregate_pda, _bump_seed) = Pubkey::find_program_address(&[title_hash.as_ref(), b"aggregate"], program_id);
+
+    vec![
+        AccountMeta::new(*initializer, true),
+        AccountMeta::new(review_pda(program_id, initializer, title), false),
+        AccountMeta::new(config_pda, false),
+        AccountMeta::new(aggregate_pda, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ]
+}
+
+// ImportReview of `author`'s review, paid for by `admin`
+pub fn import_review_accounts(program_id: &Pubkey, admin: &Pubkey, author: &Pubkey, title: &str) -> Vec<AccountMeta> {
+    let review_pda = review_pda(program_id, author, title);
+    let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], program_id);
+    let (counter_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), b"comment"], program_id);
+    let title_hash = MovieAggregate::title_hash(title);
+    let (aggregate_pda, _bump_seed) = Pubkey::find_program_address(&[title_hash.as_ref(), b"aggregate"], program_id);
+    let (index_pda, _bump_seed) = Pubkey::find_program_address(&[b"index", &[IndexBucket::key(title)]], program_id);
+    let (global_seq_pda, _bump_seed) = Pubkey::find_program_address(&[b"global_seq"], program_id);
+
+    vec![
+        AccountMeta::new(*admin, true),
+        AccountMeta::new_readonly(config_pda, false),
+        AccountMeta::new(review_pda, false),
+        AccountMeta::new(counter_pda, false),
+        AccountMeta::new(aggregate_pda, false),
+        AccountMeta::new(index_pda, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new(global_seq_pda, false),
+    ]
+}
+
+// AddComment without `create_ata`, posting at `index`, the review counter's current
+// value; with it, append the associated token program
+pub fn add_comment_accounts(program_id: &Pubkey, commenter: &Pubkey, review_pda: &Pubkey, index: u64) -> Vec<AccountMeta> {
+    let (counter_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), b"comment"], program_id);
+    let (comment_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), index.to_be_bytes().as_ref()], program_id);
+    let (mint, _bump_seed) = Pubkey::find_program_address(&[b"comment_token_mint"], program_id);
+    let (mint_auth, _bump_seed) = Pubkey::find_program_address(&[b"comment_token_auth"], program_id);
+    let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], program_id);
+    let (marker_pda, _bump_seed) = Pubkey::find_program_address(
+        &[review_pda.as_ref(), commenter.as_ref(), b"commented"],
+        program_id
+    );
+
+    vec![
+        AccountMeta::new(*commenter, true),
+        AccountMeta::new_readonly(*review_pda, false),
+        AccountMeta::new(counter_pda, false),
+        AccountMeta::new(comment_pda, false),
+        AccountMeta::new(mint, false),
+        AccountMeta::new_readonly(mint_auth, false),
+        AccountMeta::new(get_associated_token_address(commenter, &mint), false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        AccountMeta::new(config_pda, false),
+        AccountMeta::new(marker_pda, false),
+    ]
+}
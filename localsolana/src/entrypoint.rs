@@ -1,25 +1,28 @@
 use solana_program::{
     account_info::AccountInfo, entrypoint::ProgramResult,
+    program_error::PrintProgramError,
     pubkey::Pubkey,
-    msg,
     entrypoint
 };
-use crate::processor;
+use crate::{error::ReviewError, processor};
 
 entrypoint!(process_instruction);
 
-fn process_instruction(
+pub(crate) fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    msg!(
+    verbose_msg!(
         "process_instruction: {}: {} accounts, data={:?}",
         program_id,
         accounts.len(),
         instruction_data
     );
-    processor::process_instruction(program_id, accounts, instruction_data)?;
+    if let Err(error) = processor::process_instruction(program_id, accounts, instruction_data) {
+        error.print::<ReviewError>();
+        return Err(error)
+    }
 
     Ok(())
 }
@@ -1,7 +1,8 @@
-use solana_program::{program_error::ProgramError};
+use num_derive::FromPrimitive;
+use solana_program::{decode_error::DecodeError, msg, program_error::{PrintProgramError, ProgramError}};
 use thiserror::Error;
 
-#[derive(Debug, Error)]
+#[derive(Debug, Error, FromPrimitive)]
 pub enum ReviewError{
     #[error("Account not initialized yet")]
     UninitializedAccount,
@@ -12,11 +13,119 @@ pub enum ReviewError{
     #[error("Input data exceeds max length")]
     InvalidDataLength,
     
-    #[error("Rating greater than 5 or less than 1")]
+    #[error("Rating is zero or above the configured maximum")]
     InvalidRating,
     
     #[error("Accounts do not match")]
     IncorrectAccountError,
+
+    #[error("Description exceeds max length")]
+    DescriptionTooLong,
+
+    #[error("Comment account already exists at this index")]
+    CommentSlotOccupied,
+
+    #[error("Genre is not in the allowed list")]
+    InvalidGenre,
+
+    #[error("Author does not hold enough tokens to slash")]
+    InsufficientTokensToSlash,
+
+    #[error("Account layout version is not supported")]
+    UnsupportedVersion,
+
+    #[error("Title, description and comment cannot be empty")]
+    EmptyField,
+
+    #[error("Minting would exceed the reward token supply cap")]
+    SupplyCapExceeded,
+
+    #[error("Too many comments in one batch")]
+    BatchTooLarge,
+
+    #[error("User associated token account has not been created")]
+    AssociatedTokenAccountMissing,
+
+    #[error("Tip is too small to split between author and treasury")]
+    InvalidTipAmount,
+
+    #[error("Comment counter has not been created")]
+    CounterNotInitialized,
+
+    #[error("Account data could not be serialized")]
+    SerializationFailed,
+
+    #[error("Reaction kind is not one of like, love, laugh or angry")]
+    InvalidReactionKind,
+
+    #[error("Reactor has already reacted to this comment")]
+    AlreadyReacted,
+
+    #[error("Author has reached the maximum number of reviews")]
+    ReviewLimitReached,
+
+    #[error("Only the admin can initialize the config or a mint")]
+    UnauthorizedInitializer,
+
+    #[error("Mint decimals must be at most 9")]
+    InvalidDecimals,
+
+    #[error("Review does not exist")]
+    ReviewDoesNotExist,
+
+    #[error("Watch status must be reviewed, want-to-watch or watching")]
+    InvalidStatus,
+
+    #[error("Author has already reviewed this title")]
+    ReviewAlreadyExists,
+
+    #[error("Timestamp must be between the Unix epoch and now")]
+    InvalidTimestamp,
+
+    #[error("Account is not of the expected type")]
+    WrongDiscriminator,
+
+    #[error("Review already reported by this account")]
+    AlreadyReported,
+
+    #[error("Review has reached the maximum number of comments")]
+    CommentLimitReached,
+
+    #[error("Arithmetic overflow")]
+    ArithmeticOverflow,
+
+    #[error("Arithmetic underflow")]
+    ArithmeticUnderflow,
+
+    #[error("String used as a PDA seed exceeds 32 bytes")]
+    SeedTooLong,
+
+    #[error("No admin transfer is pending")]
+    NoPendingAdmin,
+
+    #[error("Comment index is not below the review's comment count")]
+    CommentIndexOutOfRange,
+
+    #[error("Initializer cannot cover the review fee")]
+    InsufficientFunds,
+
+    #[error("Account must not be a signer")]
+    UnexpectedSigner,
+
+    #[error("Review has expired and can no longer be changed")]
+    ReviewExpired,
+
+    #[error("Review authors cannot comment on their own reviews")]
+    SelfCommentNotAllowed,
+
+    #[error("Description is shorter than the configured minimum")]
+    DescriptionTooShort,
+
+    #[error("Voter has already upvoted this review")]
+    AlreadyUpvoted,
+
+    #[error("Review authors cannot upvote their own reviews")]
+    SelfUpvoteNotAllowed,
 }
 
 impl From<ReviewError> for ProgramError {
@@ -24,3 +133,16 @@ impl From<ReviewError> for ProgramError {
         ProgramError::Custom(e as u32)
     }
 }
+
+impl<T> DecodeError<T> for ReviewError {
+    fn type_of() -> &'static str {
+        "ReviewError"
+    }
+}
+
+// Names the variant alongside its message, since clients only get the error code
+impl PrintProgramError for ReviewError {
+    fn print<E>(&self) {
+        msg!("Error: {:?}: {}", self, self);
+    }
+}
@@ -1,11 +1,15 @@
 use borsh::{BorshDeserialize};
-use solana_program::{program_error::ProgramError};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
 
 pub enum MovieInstruction {
   AddMovieReview {
     title: String,
     rating: u8,
-    description: String
+    description: String,
+    // Follows the old create_ata byte; None stores `title` as the display title
+    display_title: Option<String>,
+    // Follows `display_title`; None adds a regular review
+    status: Option<u8>
   },
   UpdateMovieReview {
     title: String,
@@ -13,9 +17,163 @@ pub enum MovieInstruction {
     description: String
   },
   AddComment {
-      comment: String
+      comment: String,
+      create_ata: bool
+  },
+  InitializeMint {
+    // Makes the `[b"token_auth"]` PDA the freeze authority so FreezeAccount works
+    freezable: bool,
+    // For both reward mints; None uses ProgramConfig::DEFAULT_DECIMALS
+    decimals: Option<u8>
+  },
+  InitializeCommentMint,
+  TransferReview {
+    title: String,
+    new_owner: Pubkey
+  },
+  // Separate tag so V1 clients keep working; writes the "review_v2" layout
+  AddMovieReviewV2 {
+    title: String,
+    rating: u8,
+    description: String,
+    genre: String
+  },
+  CheckReviewExists {
+    author: Pubkey,
+    title: String
+  },
+  InitializeConfig {
+    slash_amount: u64,
+    // None keeps the original 1-5 scale
+    max_rating: Option<u8>,
+    // None uses ProgramConfig::DEFAULT_TIP_FEE_BPS
+    tip_fee_bps: Option<u16>,
+    // None uses AuthorProfile::MAX_REVIEWS_PER_AUTHOR
+    max_reviews_per_author: Option<u64>,
+    // None uses ProgramConfig::DEFAULT_REPORT_THRESHOLD
+    report_threshold: Option<u64>,
+    // None uses MovieComment::MAX_COMMENTS_PER_REVIEW
+    max_comments_per_review: Option<u64>,
+    // Lamports per new review; None charges no fee
+    review_fee: Option<u64>,
+    // None allows review authors to comment on their own reviews
+    allow_self_comment: Option<bool>,
+    // In bytes; None sets no minimum
+    min_description_len: Option<u64>
+  },
+  SlashReview {
+    author: Pubkey,
+    title: String
+  },
+  EnsureCounter {
+    author: Pubkey,
+    title: String
+  },
+  UpdateComment {
+    comment: String
+  },
+  SetSpoilerFlag {
+    title: String,
+    has_spoilers: bool
   },
-  InitializeMint
+  BatchAddComments {
+    comments: Vec<String>
+  },
+  FreezeAccount {
+    owner: Pubkey
+  },
+  MigrateReview {
+    title: String
+  },
+  TipReviewer {
+    amount: u64
+  },
+  SetGenre {
+    title: String,
+    genre: String
+  },
+  // Takes the author like CheckReviewExists, since the review PDA is seeded by it
+  LogComment {
+    author: Pubkey,
+    title: String,
+    index: u64
+  },
+  // 0 like, 1 love, 2 laugh, 3 angry
+  ReactToComment {
+    title: String,
+    index: u64,
+    reaction_kind: u8
+  },
+  LogConfig,
+  UpdateDescription {
+    title: String,
+    description: String
+  },
+  UpdateRating {
+    title: String,
+    rating: u8
+  },
+  CreateTokenMetadata {
+    name: String,
+    symbol: String,
+    uri: String
+  },
+  LogTreasuryBalance,
+  SetWatchStatus {
+    title: String,
+    status: u8
+  },
+  // Admin only; `created_at` is the review's original Unix time
+  ImportReview {
+    author: Pubkey,
+    title: String,
+    rating: u8,
+    description: String,
+    created_at: i64
+  },
+  GetVersion,
+  ReportReview {
+    author: Pubkey,
+    title: String
+  },
+  // InitializeConfig, InitializeMint then InitializeCommentMint in one instruction;
+  // other config options take their defaults
+  InitializeProgram {
+    slash_amount: u64,
+    freezable: bool,
+    // None uses ProgramConfig::DEFAULT_DECIMALS
+    decimals: Option<u8>
+  },
+  // Admin only; `new_admin` takes over once it signs AcceptAdmin
+  TransferAdmin {
+    new_admin: Pubkey
+  },
+  AcceptAdmin,
+  // Admin only; sets ProgramConfig::verbose_logs
+  SetVerboseLogs {
+    enabled: bool
+  },
+  // None unpins
+  PinComment {
+    title: String,
+    index: Option<u64>
+  },
+  ClaimRewards,
+  // Admin only; 0 clears the expiry
+  SetReviewExpiry {
+    author: Pubkey,
+    title: String,
+    expires_at: i64
+  },
+  ExportReview {
+    author: Pubkey,
+    title: String
+  },
+  // Once per voter; authors can't upvote their own reviews
+  UpvoteReview {
+    author: Pubkey,
+    title: String
+  }
 }
 
 #[derive(BorshDeserialize)]
@@ -25,24 +183,220 @@ struct MovieReviewPayload {
   description: String
 }
 
+#[derive(BorshDeserialize)]
+struct MovieReviewV2Payload {
+  title: String,
+  rating: u8,
+  description: String,
+  genre: String
+}
+
 #[derive(BorshDeserialize)]
 struct CommentPayload {
     comment: String
 }
 
+// For instructions that address someone else's review by its PDA seeds
+#[derive(BorshDeserialize)]
+struct ReviewAddressPayload {
+    author: Pubkey,
+    title: String
+}
+
+#[derive(BorshDeserialize)]
+struct InitializeConfigPayload {
+    slash_amount: u64,
+    max_rating: Option<u8>,
+    tip_fee_bps: Option<u16>,
+    max_reviews_per_author: Option<u64>,
+    report_threshold: Option<u64>,
+    max_comments_per_review: Option<u64>,
+    review_fee: Option<u64>,
+    allow_self_comment: Option<bool>,
+    min_description_len: Option<u64>
+}
+
+#[derive(BorshDeserialize)]
+struct InitializeProgramPayload {
+    slash_amount: u64,
+    freezable: bool,
+    decimals: Option<u8>
+}
+
+#[derive(BorshDeserialize)]
+struct TransferAdminPayload {
+    new_admin: Pubkey
+}
+
+#[derive(BorshDeserialize)]
+struct VerboseLogsPayload {
+    enabled: bool
+}
+
+#[derive(BorshDeserialize)]
+struct PinCommentPayload {
+    title: String,
+    index: Option<u64>
+}
+
+#[derive(BorshDeserialize)]
+struct ReviewExpiryPayload {
+    author: Pubkey,
+    title: String,
+    expires_at: i64
+}
+
+#[derive(BorshDeserialize)]
+struct TipPayload {
+    amount: u64
+}
+
+#[derive(BorshDeserialize)]
+struct InitializeMintPayload {
+    freezable: bool
+}
+
+#[derive(BorshDeserialize)]
+struct FreezeAccountPayload {
+    owner: Pubkey
+}
+
+#[derive(BorshDeserialize)]
+struct MigrateReviewPayload {
+    title: String
+}
+
+#[derive(BorshDeserialize)]
+struct BatchCommentsPayload {
+    comments: Vec<String>
+}
+
+#[derive(BorshDeserialize)]
+struct SpoilerFlagPayload {
+    title: String,
+    has_spoilers: bool
+}
+
+#[derive(BorshDeserialize)]
+struct ImportReviewPayload {
+    author: Pubkey,
+    title: String,
+    rating: u8,
+    description: String,
+    created_at: i64
+}
+
+#[derive(BorshDeserialize)]
+struct WatchStatusPayload {
+    title: String,
+    status: u8
+}
+
+#[derive(BorshDeserialize)]
+struct SetGenrePayload {
+    title: String,
+    genre: String
+}
+
+#[derive(BorshDeserialize)]
+struct LogCommentPayload {
+    author: Pubkey,
+    title: String,
+    index: u64
+}
+
+#[derive(BorshDeserialize)]
+struct ReactToCommentPayload {
+    title: String,
+    index: u64,
+    reaction_kind: u8
+}
+
+#[derive(BorshDeserialize)]
+struct UpdateDescriptionPayload {
+    title: String,
+    description: String
+}
+
+#[derive(BorshDeserialize)]
+struct UpdateRatingPayload {
+    title: String,
+    rating: u8
+}
+
+#[derive(BorshDeserialize)]
+struct TokenMetadataPayload {
+    name: String,
+    symbol: String,
+    uri: String
+}
+
+#[derive(BorshDeserialize)]
+struct TransferReviewPayload {
+    title: String,
+    new_owner: Pubkey
+}
+
 impl MovieInstruction {
+    // Leading byte of the instruction data. Tags are part of the wire format: append
+    // new ones, never renumber
+    pub const TAG_ADD_REVIEW: u8 = 0;
+    pub const TAG_UPDATE_REVIEW: u8 = 1;
+    pub const TAG_ADD_COMMENT: u8 = 2;
+    pub const TAG_INITIALIZE_MINT: u8 = 3;
+    pub const TAG_INITIALIZE_COMMENT_MINT: u8 = 4;
+    pub const TAG_TRANSFER_REVIEW: u8 = 5;
+    pub const TAG_ADD_REVIEW_V2: u8 = 6;
+    pub const TAG_CHECK_REVIEW_EXISTS: u8 = 7;
+    pub const TAG_INITIALIZE_CONFIG: u8 = 8;
+    pub const TAG_SLASH_REVIEW: u8 = 9;
+    pub const TAG_ENSURE_COUNTER: u8 = 10;
+    pub const TAG_UPDATE_COMMENT: u8 = 11;
+    pub const TAG_SET_SPOILER_FLAG: u8 = 12;
+    pub const TAG_BATCH_ADD_COMMENTS: u8 = 13;
+    pub const TAG_FREEZE_ACCOUNT: u8 = 14;
+    pub const TAG_MIGRATE_REVIEW: u8 = 15;
+    pub const TAG_TIP_REVIEWER: u8 = 16;
+    pub const TAG_SET_GENRE: u8 = 17;
+    pub const TAG_LOG_COMMENT: u8 = 18;
+    pub const TAG_REACT_TO_COMMENT: u8 = 19;
+    pub const TAG_LOG_CONFIG: u8 = 20;
+    pub const TAG_UPDATE_DESCRIPTION: u8 = 21;
+    pub const TAG_UPDATE_RATING: u8 = 22;
+    pub const TAG_CREATE_TOKEN_METADATA: u8 = 23;
+    pub const TAG_LOG_TREASURY_BALANCE: u8 = 24;
+    pub const TAG_SET_WATCH_STATUS: u8 = 25;
+    pub const TAG_IMPORT_REVIEW: u8 = 26;
+    pub const TAG_GET_VERSION: u8 = 27;
+    pub const TAG_REPORT_REVIEW: u8 = 28;
+    pub const TAG_INITIALIZE_PROGRAM: u8 = 29;
+    pub const TAG_TRANSFER_ADMIN: u8 = 30;
+    pub const TAG_ACCEPT_ADMIN: u8 = 31;
+    pub const TAG_SET_VERBOSE_LOGS: u8 = 32;
+    pub const TAG_PIN_COMMENT: u8 = 33;
+    pub const TAG_CLAIM_REWARDS: u8 = 34;
+    pub const TAG_SET_REVIEW_EXPIRY: u8 = 35;
+    pub const TAG_EXPORT_REVIEW: u8 = 36;
+    pub const TAG_UPVOTE_REVIEW: u8 = 37;
+
   pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
-        let (&variant, rest) = input.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+        let (&variant, mut rest) = input.split_first().ok_or(ProgramError::InvalidInstructionData)?;
         Ok(match variant {
-            0 => {
-                let payload = MovieReviewPayload::try_from_slice(rest).unwrap();
+            Self::TAG_ADD_REVIEW => {
+                // Reviews no longer create ATAs, but the flag byte is still read so
+                // display_title and status keep their offsets
+                let (payload, _create_ata) = unpack_with_create_ata::<MovieReviewPayload>(&mut rest);
+                let display_title = if rest.is_empty() { None } else { Option::<String>::deserialize(&mut rest).unwrap() };
                 Self::AddMovieReview {
                     title: payload.title,
                     rating: payload.rating,
-                    description: payload.description
+                    description: payload.description,
+                    display_title,
+                    // A plain byte, like the create_ata flag
+                    status: rest.first().copied()
                 }
             }
-            1 => {
+            Self::TAG_UPDATE_REVIEW => {
                 let payload = MovieReviewPayload::try_from_slice(rest).unwrap();
                 Self::UpdateMovieReview {
                     title: payload.title,
@@ -50,15 +404,326 @@ impl MovieInstruction {
                     description: payload.description
                 }
             }
-            2 => {
+            Self::TAG_ADD_COMMENT => {
                 // Comment payload uses its own deserializer because of the different data type
-                let payload = CommentPayload::try_from_slice(rest).unwrap();
+                let (payload, create_ata) = unpack_with_create_ata::<CommentPayload>(&mut rest);
                 Self::AddComment {
+                    comment: payload.comment,
+                    create_ata
+                }
+            }
+            // Clients from before the freeze option send the bare tag
+            Self::TAG_INITIALIZE_MINT if rest.is_empty() => Self::InitializeMint { freezable: false, decimals: None },
+            Self::TAG_INITIALIZE_MINT => {
+                let payload = InitializeMintPayload::deserialize(&mut rest).unwrap();
+                Self::InitializeMint {
+                    freezable: payload.freezable,
+                    // A plain byte after the flag; clients from before it don't send one
+                    decimals: rest.first().copied()
+                }
+            }
+            Self::TAG_INITIALIZE_COMMENT_MINT => Self::InitializeCommentMint,
+            Self::TAG_TRANSFER_REVIEW => {
+                let payload = TransferReviewPayload::try_from_slice(rest).unwrap();
+                Self::TransferReview {
+                    title: payload.title,
+                    new_owner: payload.new_owner
+                }
+            }
+            Self::TAG_ADD_REVIEW_V2 => {
+                let payload = MovieReviewV2Payload::deserialize(&mut rest).unwrap();
+                Self::AddMovieReviewV2 {
+                    title: payload.title,
+                    rating: payload.rating,
+                    description: payload.description,
+                    genre: payload.genre
+                }
+            }
+            Self::TAG_CHECK_REVIEW_EXISTS => {
+                let payload = ReviewAddressPayload::try_from_slice(rest).unwrap();
+                Self::CheckReviewExists {
+                    author: payload.author,
+                    title: payload.title
+                }
+            }
+            Self::TAG_INITIALIZE_CONFIG => {
+                let payload = InitializeConfigPayload::try_from_slice(rest).unwrap();
+                Self::InitializeConfig {
+                    slash_amount: payload.slash_amount,
+                    max_rating: payload.max_rating,
+                    tip_fee_bps: payload.tip_fee_bps,
+                    max_reviews_per_author: payload.max_reviews_per_author,
+                    report_threshold: payload.report_threshold,
+                    max_comments_per_review: payload.max_comments_per_review,
+                    review_fee: payload.review_fee,
+                    allow_self_comment: payload.allow_self_comment,
+                    min_description_len: payload.min_description_len
+                }
+            }
+            Self::TAG_SLASH_REVIEW => {
+                let payload = ReviewAddressPayload::try_from_slice(rest).unwrap();
+                Self::SlashReview {
+                    author: payload.author,
+                    title: payload.title
+                }
+            }
+            Self::TAG_ENSURE_COUNTER => {
+                let payload = ReviewAddressPayload::try_from_slice(rest).unwrap();
+                Self::EnsureCounter {
+                    author: payload.author,
+                    title: payload.title
+                }
+            }
+            Self::TAG_UPDATE_COMMENT => {
+                let payload = CommentPayload::try_from_slice(rest).unwrap();
+                Self::UpdateComment {
                     comment: payload.comment
                 }
             }
-            3 => Self::InitializeMint,
+            Self::TAG_SET_SPOILER_FLAG => {
+                let payload = SpoilerFlagPayload::try_from_slice(rest).unwrap();
+                Self::SetSpoilerFlag {
+                    title: payload.title,
+                    has_spoilers: payload.has_spoilers
+                }
+            }
+            Self::TAG_BATCH_ADD_COMMENTS => {
+                check_vec_len(rest)?;
+                let payload = BatchCommentsPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::BatchAddComments {
+                    comments: payload.comments
+                }
+            }
+            Self::TAG_FREEZE_ACCOUNT => {
+                let payload = FreezeAccountPayload::try_from_slice(rest).unwrap();
+                Self::FreezeAccount {
+                    owner: payload.owner
+                }
+            }
+            Self::TAG_MIGRATE_REVIEW => {
+                let payload = MigrateReviewPayload::try_from_slice(rest).unwrap();
+                Self::MigrateReview {
+                    title: payload.title
+                }
+            }
+            Self::TAG_TIP_REVIEWER => {
+                let payload = TipPayload::try_from_slice(rest).unwrap();
+                Self::TipReviewer {
+                    amount: payload.amount
+                }
+            }
+            Self::TAG_SET_GENRE => {
+                let payload = SetGenrePayload::try_from_slice(rest).unwrap();
+                Self::SetGenre {
+                    title: payload.title,
+                    genre: payload.genre
+                }
+            }
+            Self::TAG_LOG_COMMENT => {
+                let payload = LogCommentPayload::try_from_slice(rest).unwrap();
+                Self::LogComment {
+                    author: payload.author,
+                    title: payload.title,
+                    index: payload.index
+                }
+            }
+            Self::TAG_REACT_TO_COMMENT => {
+                let payload = ReactToCommentPayload::try_from_slice(rest).unwrap();
+                Self::ReactToComment {
+                    title: payload.title,
+                    index: payload.index,
+                    reaction_kind: payload.reaction_kind
+                }
+            }
+            Self::TAG_LOG_CONFIG => Self::LogConfig,
+            Self::TAG_UPDATE_DESCRIPTION => {
+                let payload = UpdateDescriptionPayload::try_from_slice(rest).unwrap();
+                Self::UpdateDescription {
+                    title: payload.title,
+                    description: payload.description
+                }
+            }
+            Self::TAG_UPDATE_RATING => {
+                let payload = UpdateRatingPayload::try_from_slice(rest).unwrap();
+                Self::UpdateRating {
+                    title: payload.title,
+                    rating: payload.rating
+                }
+            }
+            Self::TAG_CREATE_TOKEN_METADATA => {
+                let payload = TokenMetadataPayload::try_from_slice(rest).unwrap();
+                Self::CreateTokenMetadata {
+                    name: payload.name,
+                    symbol: payload.symbol,
+                    uri: payload.uri
+                }
+            }
+            Self::TAG_LOG_TREASURY_BALANCE => Self::LogTreasuryBalance,
+            Self::TAG_SET_WATCH_STATUS => {
+                let payload = WatchStatusPayload::try_from_slice(rest).unwrap();
+                Self::SetWatchStatus {
+                    title: payload.title,
+                    status: payload.status
+                }
+            }
+            Self::TAG_IMPORT_REVIEW => {
+                let payload = ImportReviewPayload::try_from_slice(rest).unwrap();
+                Self::ImportReview {
+                    author: payload.author,
+                    title: payload.title,
+                    rating: payload.rating,
+                    description: payload.description,
+                    created_at: payload.created_at
+                }
+            }
+            Self::TAG_GET_VERSION => Self::GetVersion,
+            Self::TAG_REPORT_REVIEW => {
+                let payload = ReviewAddressPayload::try_from_slice(rest).unwrap();
+                Self::ReportReview {
+                    author: payload.author,
+                    title: payload.title
+                }
+            }
+            Self::TAG_INITIALIZE_PROGRAM => {
+                let payload = InitializeProgramPayload::try_from_slice(rest).unwrap();
+                Self::InitializeProgram {
+                    slash_amount: payload.slash_amount,
+                    freezable: payload.freezable,
+                    decimals: payload.decimals
+                }
+            }
+            Self::TAG_TRANSFER_ADMIN => {
+                let payload = TransferAdminPayload::try_from_slice(rest).unwrap();
+                Self::TransferAdmin {
+                    new_admin: payload.new_admin
+                }
+            }
+            Self::TAG_ACCEPT_ADMIN => Self::AcceptAdmin,
+            Self::TAG_SET_VERBOSE_LOGS => {
+                let payload = VerboseLogsPayload::try_from_slice(rest).unwrap();
+                Self::SetVerboseLogs {
+                    enabled: payload.enabled
+                }
+            }
+            Self::TAG_PIN_COMMENT => {
+                let payload = PinCommentPayload::try_from_slice(rest).unwrap();
+                Self::PinComment {
+                    title: payload.title,
+                    index: payload.index
+                }
+            }
+            Self::TAG_CLAIM_REWARDS => Self::ClaimRewards,
+            Self::TAG_SET_REVIEW_EXPIRY => {
+                let payload = ReviewExpiryPayload::try_from_slice(rest).unwrap();
+                Self::SetReviewExpiry {
+                    author: payload.author,
+                    title: payload.title,
+                    expires_at: payload.expires_at
+                }
+            }
+            Self::TAG_EXPORT_REVIEW => {
+                let payload = ReviewAddressPayload::try_from_slice(rest).unwrap();
+                Self::ExportReview {
+                    author: payload.author,
+                    title: payload.title
+                }
+            }
+            Self::TAG_UPVOTE_REVIEW => {
+                let payload = ReviewAddressPayload::try_from_slice(rest).unwrap();
+                Self::UpvoteReview {
+                    author: payload.author,
+                    title: payload.title
+                }
+            }
             _ => return Err(ProgramError::InvalidInstructionData)
         })
     }
 }
+
+// Upper bound on the declared length of any Vec in a payload. Borsh trusts the u32
+// length prefix, so it is checked before deserializing; handlers apply their own,
+// tighter limits, such as MovieComment::MAX_BATCH.
+const MAX_VEC_LEN: usize = 16;
+
+// For payloads that start with a Vec: rejects a length prefix above MAX_VEC_LEN
+fn check_vec_len(rest: &[u8]) -> Result<(), ProgramError> {
+    let prefix = rest.get(..4).ok_or(ProgramError::InvalidInstructionData)?;
+    let len = u32::from_le_bytes(prefix.try_into().unwrap()) as usize;
+    if len > MAX_VEC_LEN {
+        return Err(ProgramError::InvalidInstructionData)
+    }
+    Ok(())
+}
+
+// Rewarding instructions may follow their payload with a `create_ata` flag byte,
+// asking the program to create the user's ATA if it's missing. Clients from before
+// the flag don't send it. Leaves `rest` at whatever follows the flag.
+fn unpack_with_create_ata<T: BorshDeserialize>(rest: &mut &[u8]) -> (T, bool) {
+    let payload = T::deserialize(rest).unwrap();
+    let create_ata = if rest.is_empty() { false } else { bool::deserialize(rest).unwrap() };
+    (payload, create_ata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    fn batch_data(len_prefix: u32, body: &[u8]) -> Vec<u8> {
+        let mut data = vec![MovieInstruction::TAG_BATCH_ADD_COMMENTS];
+        data.extend_from_slice(&len_prefix.to_le_bytes());
+        data.extend_from_slice(body);
+        data
+    }
+
+    #[test]
+    fn batch_rejects_oversized_length_prefix() {
+        for len_prefix in [MAX_VEC_LEN as u32 + 1, 1 << 20, u32::MAX] {
+            assert!(matches!(
+                MovieInstruction::unpack(&batch_data(len_prefix, b"")),
+                Err(ProgramError::InvalidInstructionData)
+            ));
+        }
+
+        // A plausible length with too little data behind it is an error too, not a panic
+        assert!(matches!(
+            MovieInstruction::unpack(&batch_data(MAX_VEC_LEN as u32, &[1, 0, 0, 0])),
+            Err(ProgramError::InvalidInstructionData)
+        ));
+        assert!(matches!(
+            MovieInstruction::unpack(&[MovieInstruction::TAG_BATCH_ADD_COMMENTS, 1, 0]),
+            Err(ProgramError::InvalidInstructionData)
+        ));
+    }
+
+    #[test]
+    fn batch_accepts_comments_up_to_limit() {
+        let comments = vec!["Seen it twice".to_string(); MAX_VEC_LEN];
+        let mut data = vec![MovieInstruction::TAG_BATCH_ADD_COMMENTS];
+        data.append(&mut comments.try_to_vec().unwrap());
+        assert!(matches!(
+            MovieInstruction::unpack(&data),
+            Ok(MovieInstruction::BatchAddComments { comments: parsed }) if parsed == comments
+        ));
+    }
+
+    #[test]
+    fn add_review_reads_little_endian_title_length() {
+        // 300 is 0x012c, so a big-endian read of the prefix would ask for far more bytes
+        let title = "t".repeat(300);
+        let mut data = vec![MovieInstruction::TAG_ADD_REVIEW];
+        data.extend_from_slice(&[0x2c, 0x01, 0x00, 0x00]);
+        data.extend_from_slice(title.as_bytes());
+        data.push(4);
+        data.extend_from_slice(&(3u32).to_le_bytes());
+        data.extend_from_slice(b"Fun");
+        assert_eq!(&data[1..5], &(title.len() as u32).to_le_bytes());
+
+        assert!(matches!(
+            MovieInstruction::unpack(&data),
+            Ok(MovieInstruction::AddMovieReview { title: parsed, rating: 4, description, .. })
+                if parsed == title && description == "Fun"
+        ));
+    }
+}
@@ -1,5 +1,28 @@
+// Progress logging that is compiled out with the `minimal-logging` feature to save
+// compute units. Error paths keep calling `msg!` directly so failures stay diagnosable.
+macro_rules! verbose_msg {
+    ($($arg:tt)*) => {
+        if cfg!(not(feature = "minimal-logging")) {
+            solana_program::msg!($($arg)*);
+        }
+    };
+}
+
+// verbose_msg! that operators can also switch off at runtime through the config's
+// `verbose_logs`, for logs emitted once a handler has loaded the config
+macro_rules! config_msg {
+    ($config:expr, $($arg:tt)*) => {
+        if $config.verbose_logs {
+            verbose_msg!($($arg)*);
+        }
+    };
+}
+
 pub mod entrypoint;
 pub mod instruction;
 pub mod processor;
 pub mod state;
 pub mod error;
+pub mod math;
+#[cfg(any(test, feature = "client"))]
+pub mod client;
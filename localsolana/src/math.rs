@@ -0,0 +1,53 @@
+// Checked u64 arithmetic for the processor. Release builds wrap on overflow unless
+// overflow-checks is on, so counters and amounts go through these instead of the
+// bare operators, failing the instruction rather than wrapping.
+use solana_program::program_error::ProgramError;
+use crate::error::ReviewError;
+
+pub fn add_u64(a: u64, b: u64) -> Result<u64, ProgramError> {
+    a.checked_add(b).ok_or_else(|| ReviewError::ArithmeticOverflow.into())
+}
+
+pub fn add_u32(a: u32, b: u32) -> Result<u32, ProgramError> {
+    a.checked_add(b).ok_or_else(|| ReviewError::ArithmeticOverflow.into())
+}
+
+pub fn sub_u64(a: u64, b: u64) -> Result<u64, ProgramError> {
+    a.checked_sub(b).ok_or_else(|| ReviewError::ArithmeticUnderflow.into())
+}
+
+pub fn mul_u64(a: u64, b: u64) -> Result<u64, ProgramError> {
+    a.checked_mul(b).ok_or_else(|| ReviewError::ArithmeticOverflow.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_overflows_past_max() {
+        assert_eq!(add_u64(2, 3), Ok(5));
+        assert_eq!(add_u64(u64::MAX - 1, 1), Ok(u64::MAX));
+        assert_eq!(add_u64(u64::MAX, 1), Err(ReviewError::ArithmeticOverflow.into()));
+    }
+
+    #[test]
+    fn add_u32_overflows_past_max() {
+        assert_eq!(add_u32(2, 3), Ok(5));
+        assert_eq!(add_u32(u32::MAX, 1), Err(ReviewError::ArithmeticOverflow.into()));
+    }
+
+    #[test]
+    fn sub_underflows_below_zero() {
+        assert_eq!(sub_u64(5, 3), Ok(2));
+        assert_eq!(sub_u64(3, 3), Ok(0));
+        assert_eq!(sub_u64(3, 4), Err(ReviewError::ArithmeticUnderflow.into()));
+    }
+
+    #[test]
+    fn mul_overflows_past_max() {
+        assert_eq!(mul_u64(10, 2), Ok(20));
+        assert_eq!(mul_u64(u64::MAX, 0), Ok(0));
+        assert_eq!(mul_u64(u64::MAX / 2 + 1, 2), Err(ReviewError::ArithmeticOverflow.into()));
+    }
+}
@@ -1,24 +1,62 @@
 use solana_program::{
     entrypoint::ProgramResult,
-    pubkey::Pubkey,
+    pubkey::{Pubkey, MAX_SEED_LEN},
     msg,
     account_info::{next_account_info, AccountInfo},
     system_instruction,
     program_error::ProgramError,
-    sysvar::{rent::Rent, Sysvar, rent::ID as RENT_PROGRAM_ID},
-    native_token::LAMPORTS_PER_SOL,
+    instruction::{AccountMeta, Instruction},
+    sysvar::{clock::Clock, rent::Rent, Sysvar, rent::ID as RENT_PROGRAM_ID},
     system_program::ID as SYSTEM_PROGRAM_ID,
-    program::{invoke_signed},
+    program::{invoke, invoke_signed, set_return_data},
     borsh::try_from_slice_unchecked,
-    program_pack::{IsInitialized},
+    program_option::COption,
+    program_pack::{IsInitialized, Pack},
+    log::sol_log_data,
 };
-use std::convert::TryInto;
 use crate::instruction::MovieInstruction;
-use crate::state::{ MovieAccountState, MovieCommentCounter, MovieComment };
-use borsh::BorshSerialize;
+use crate::state::{ MovieAccountState, OldMovieAccountState, MovieCommentCounter, MovieComment, ProgramConfig, AuthorProfile, MovieAggregate, IndexBucket, GlobalSequence, CommenterMarker, ReportMarker, UpvoteMarker, ReactionMarker, Rating };
+use borsh::{BorshDeserialize, BorshSerialize};
 use crate::error::ReviewError;
-use spl_associated_token_account::get_associated_token_address;
-use spl_token::{instruction::{initialize_mint, mint_to}, ID as TOKEN_PROGRAM_ID};
+use crate::math::{add_u32, add_u64, mul_u64, sub_u64};
+use spl_associated_token_account::{get_associated_token_address, instruction::create_associated_token_account};
+use spl_token::{
+    instruction::{approve, burn, freeze_account, initialize_mint, mint_to_checked},
+    state::Account as TokenAccount,
+    ID as TOKEN_PROGRAM_ID,
+};
+
+// Reward amounts in whole tokens, per review and per comment, scaled to the mint
+// decimals by ProgramConfig::token_amount. The comment reward is what a review's
+// first comments earn; see comment_reward
+const REVIEW_REWARD: u64 = 10;
+const COMMENT_REWARD: u64 = 5;
+const COMMENT_HALVING_INTERVAL: u64 = 10;
+// The first author to review a movie earns this many review rewards
+const FIRST_REVIEWER_MULTIPLIER: u64 = 2;
+// Whole tokens minted to a review's referrer alongside the author's reward
+const REFERRAL_REWARD: u64 = 2;
+// Whole tokens an update mints per upvote since the last one, for at most
+// MAX_REWARDED_UPVOTES upvotes per update
+const UPVOTE_REWARD: u64 = 1;
+const MAX_REWARDED_UPVOTES: u64 = 10;
+
+// Metaplex Token Metadata. Its crate isn't a dependency, so CreateMetadataAccountV3
+// is encoded by hand in create_token_metadata
+const TOKEN_METADATA_PROGRAM_ID: Pubkey = solana_program::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+const CREATE_METADATA_ACCOUNT_V3: u8 = 33;
+// Field limits the metadata program enforces
+const MAX_METADATA_NAME_LEN: usize = 32;
+const MAX_METADATA_SYMBOL_LEN: usize = 10;
+const MAX_METADATA_URI_LEN: usize = 200;
+
+// The only key that may create the config, directly or through InitializeProgram,
+// so nobody can take the admin seat by front-running the deployment. Set it to the
+// deployer's key before deploying; unit tests sign with a keypair from a fixed seed.
+#[cfg(not(test))]
+pub const ADMIN_PUBKEY: Pubkey = solana_program::pubkey!("Admin11111111111111111111111111111111111111");
+#[cfg(test)]
+pub const ADMIN_PUBKEY: Pubkey = solana_program::pubkey!("3mMVAW736ynovUsBFTpeptDthKzSMNiZmJ33FFQskpx2");
 
 pub fn process_instruction(
     program_id: &Pubkey,
@@ -27,30 +65,173 @@ pub fn process_instruction(
   ) -> ProgramResult {
     let instruction = MovieInstruction::unpack(instruction_data)?;
     match instruction {
-      MovieInstruction::AddMovieReview { title, rating, description } => {
-        add_movie_review(program_id, accounts, title, rating, description)
+      MovieInstruction::AddMovieReview { title, rating, description, display_title, status } => {
+        add_movie_review(program_id, accounts, title, rating, description, display_title, status)
       },
       MovieInstruction::UpdateMovieReview { title, rating, description } => {
         update_movie_review(program_id, accounts, title, rating, description)
       },
-      MovieInstruction::AddComment { comment } => {
-        add_comment(program_id, accounts, comment)
+      MovieInstruction::AddComment { comment, create_ata } => {
+        add_comment(program_id, accounts, comment, create_ata)
+      },
+      MovieInstruction::InitializeMint { freezable, decimals } => {
+        initialize_token_mint(program_id, accounts, freezable, decimals)
+      },
+      MovieInstruction::InitializeCommentMint => initialize_comment_token_mint(program_id, accounts),
+      MovieInstruction::TransferReview { title, new_owner } => {
+        transfer_review(program_id, accounts, title, new_owner)
+      },
+      MovieInstruction::AddMovieReviewV2 { title, rating, description, genre } => {
+        add_movie_review_v2(program_id, accounts, title, rating, description, genre)
+      },
+      MovieInstruction::CheckReviewExists { author, title } => {
+        check_review_exists(program_id, accounts, author, title)
+      },
+      MovieInstruction::InitializeConfig {
+        slash_amount, max_rating, tip_fee_bps, max_reviews_per_author, report_threshold, max_comments_per_review, review_fee,
+        allow_self_comment, min_description_len
+      } => {
+        initialize_config(
+          program_id, accounts, slash_amount, max_rating, tip_fee_bps, max_reviews_per_author, report_threshold, max_comments_per_review, review_fee,
+          allow_self_comment, min_description_len
+        )
+      },
+      MovieInstruction::SlashReview { author, title } => {
+        slash_review(program_id, accounts, author, title)
+      },
+      MovieInstruction::EnsureCounter { author, title } => {
+        ensure_counter(program_id, accounts, author, title)
+      },
+      MovieInstruction::UpdateComment { comment } => {
+        update_comment(program_id, accounts, comment)
+      },
+      MovieInstruction::SetSpoilerFlag { title, has_spoilers } => {
+        set_spoiler_flag(program_id, accounts, title, has_spoilers)
+      },
+      MovieInstruction::BatchAddComments { comments } => {
+        batch_add_comments(program_id, accounts, comments)
+      },
+      MovieInstruction::FreezeAccount { owner } => {
+        freeze_reward_account(program_id, accounts, owner)
+      },
+      MovieInstruction::MigrateReview { title } => {
+        migrate_review(program_id, accounts, title)
+      },
+      MovieInstruction::TipReviewer { amount } => {
+        tip_reviewer(program_id, accounts, amount)
+      },
+      MovieInstruction::SetGenre { title, genre } => {
+        set_genre(program_id, accounts, title, genre)
+      },
+      MovieInstruction::LogComment { author, title, index } => {
+        log_comment(program_id, accounts, author, title, index)
+      },
+      MovieInstruction::ReactToComment { title, index, reaction_kind } => {
+        react_to_comment(program_id, accounts, title, index, reaction_kind)
+      },
+      MovieInstruction::LogConfig => {
+        log_config(program_id, accounts)
+      },
+      MovieInstruction::UpdateDescription { title, description } => {
+        update_description(program_id, accounts, title, description)
+      },
+      MovieInstruction::UpdateRating { title, rating } => {
+        update_rating(program_id, accounts, title, rating)
+      },
+      MovieInstruction::CreateTokenMetadata { name, symbol, uri } => {
+        create_token_metadata(program_id, accounts, name, symbol, uri)
+      },
+      MovieInstruction::LogTreasuryBalance => {
+        log_treasury_balance(program_id, accounts)
+      },
+      MovieInstruction::SetWatchStatus { title, status } => {
+        set_watch_status(program_id, accounts, title, status)
+      },
+      MovieInstruction::ImportReview { author, title, rating, description, created_at } => {
+        import_review(program_id, accounts, author, title, rating, description, created_at)
+      },
+      MovieInstruction::GetVersion => {
+        get_version()
+      },
+      MovieInstruction::ReportReview { author, title } => {
+        report_review(program_id, accounts, author, title)
+      },
+      MovieInstruction::InitializeProgram { slash_amount, freezable, decimals } => {
+        initialize_program(program_id, accounts, slash_amount, freezable, decimals)
       },
-      MovieInstruction::InitializeMint => initialize_token_mint(program_id, accounts)
+      MovieInstruction::TransferAdmin { new_admin } => {
+        transfer_admin(program_id, accounts, new_admin)
+      },
+      MovieInstruction::AcceptAdmin => accept_admin(program_id, accounts),
+      MovieInstruction::SetVerboseLogs { enabled } => {
+        set_verbose_logs(program_id, accounts, enabled)
+      },
+      MovieInstruction::PinComment { title, index } => {
+        pin_comment(program_id, accounts, title, index)
+      },
+      MovieInstruction::ClaimRewards => claim_rewards(program_id, accounts),
+      MovieInstruction::SetReviewExpiry { author, title, expires_at } => {
+        set_review_expiry(program_id, accounts, author, title, expires_at)
+      },
+      MovieInstruction::ExportReview { author, title } => {
+        export_review(program_id, accounts, author, title)
+      },
+      MovieInstruction::UpvoteReview { author, title } => {
+        upvote_review(program_id, accounts, author, title)
+      }
     }
 }
 
+// The author's reward accrues in their profile until ClaimRewards, so reviewing
+// takes no user ATA; ClaimRewards checks it. A watchlist `status` allows rating 0 for a movie not rated yet; such entries stay out of the aggregate
+// and earn no reward. An optional referrer token account may come last; its owner
+// gets REFERRAL_REWARD whenever the author is rewarded.
+#[allow(clippy::too_many_arguments)]
 pub fn add_movie_review(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     title: String,
     rating: u8,
-    description: String
+    description: String,
+    display_title: Option<String>,
+    status: Option<u8>
+) -> ProgramResult {
+    create_movie_review(program_id, accounts, title, display_title, rating, description, None, status)
+}
+
+// Same accounts as add_movie_review; additionally records genre and timestamps
+pub fn add_movie_review_v2(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String,
+    rating: u8,
+    description: String,
+    genre: String
+) -> ProgramResult {
+    if !MovieAccountState::GENRES.contains(&genre.as_str()) {
+        msg!("Unknown genre: {}", genre);
+        return Err(ReviewError::InvalidGenre.into())
+    }
+
+    create_movie_review(program_id, accounts, title, None, rating, description, Some(genre), None)
+}
+
+// Shared by both review versions; `genre` is only present for V2
+#[allow(clippy::too_many_arguments)]
+fn create_movie_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String,
+    display_title: Option<String>,
+    rating: u8,
+    description: String,
+    genre: Option<String>,
+    status: Option<u8>
 ) -> ProgramResult {
-    msg!("Adding movie review...");
-    msg!("Title: {}", title);
-    msg!("Rating: {}", rating);
-    msg!("Description: {}", description);
+    verbose_msg!("Adding movie review...");
+    verbose_msg!("Title: {}", title);
+    verbose_msg!("Rating: {}", rating);
+    verbose_msg!("Description: {}", description);
 
     let account_info_iter = &mut accounts.iter();
 
@@ -59,53 +240,57 @@ pub fn add_movie_review(
     let pda_counter = next_account_info(account_info_iter)?;
     let token_mint = next_account_info(account_info_iter)?;
     let mint_auth = next_account_info(account_info_iter)?;
-    let user_ata = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
     let token_program = next_account_info(account_info_iter)?;
+    // Appended last so the earlier account positions stay where clients expect them
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_profile = next_account_info(account_info_iter)?;
+    let pda_aggregate = next_account_info(account_info_iter)?;
+    let pda_index = next_account_info(account_info_iter)?;
+    let pda_global_seq = next_account_info(account_info_iter)?;
+    // Leaving it out and passing the default pubkey both mean no referrer
+    let referrer_ata = account_info_iter.next().filter(|account| *account.key != Pubkey::default());
+    // Follows the referrer slot and is only required while config.review_fee is
+    // nonzero, so clients paying the fee without a referrer pass the default pubkey there
+    let pda_treasury = account_info_iter.next();
 
     if !initializer.is_signer {
         msg!("Missing required signature");
         return Err(ProgramError::MissingRequiredSignature)
     }
+    assert_not_signer(mint_auth, "Mint authority")?;
 
-    let (pda, bump_seed) = Pubkey::find_program_address(&[initializer.key.as_ref(), title.as_bytes().as_ref(),], program_id);
-    if pda != *pda_account.key {
-        msg!("Invalid seeds for PDA");
-        return Err(ProgramError::InvalidArgument)
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        msg!("Incorrect system program");
+        return Err(ReviewError::IncorrectAccountError.into());
     }
 
-    let (counter_pda, counter_bump) = Pubkey::find_program_address(
-        &[pda.as_ref(), "comment".as_ref()],
-        program_id
-    );
-
-    if counter_pda != *pda_counter.key {
-        msg!("Invalid seeds for counter PDA");
-        return Err(ProgramError::InvalidArgument);
-    }
+    assert_valid_seed(&title)?;
+    let bump_seed = validate_pda(program_id, &[initializer.key.as_ref(), title.as_bytes()], pda_account, "PDA", ProgramError::InvalidArgument)?;
+    let pda = *pda_account.key;
 
-    if rating > 5 || rating < 1 {
-        msg!("Rating cannot be higher than 5");
-        return Err(ReviewError::InvalidRating.into())
+    // create_account would fail on an existing review with an opaque "already in use"
+    if pda_account.lamports() > 0 || pda_account.owner == program_id {
+        msg!("A review of \"{}\" by this author already exists; update it instead", title);
+        return Err(ReviewError::ReviewAlreadyExists.into())
     }
 
-    msg!("Deriving mint authority");
-    let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[b"token_mint"], program_id);
-    let (mint_auth_pda, mint_auth_bump) = Pubkey::find_program_address(&[b"token_auth"], program_id);
+    let counter_bump = validate_pda(program_id, &[pda.as_ref(), b"comment"], pda_counter, "counter PDA", ProgramError::InvalidArgument)?;
 
-    if *token_mint.key != mint_pda {
-        msg!("Incorrect token mint");
-        return Err(ReviewError::IncorrectAccountError.into());
+    // An empty title would still derive a valid PDA, but is almost certainly a client bug
+    if title.is_empty() {
+        msg!("Title cannot be empty");
+        return Err(ReviewError::EmptyField.into())
     }
 
-    if *mint_auth.key != mint_auth_pda {
-        msg!("Mint passed in and mint derived do not match");
-        return Err(ReviewError::InvalidPDA.into());
+    if description.is_empty() {
+        msg!("Description cannot be empty");
+        return Err(ReviewError::EmptyField.into())
     }
 
-    if *user_ata.key != get_associated_token_address(initializer.key, token_mint.key) {
-        msg!("Incorrect token mint");
-        return Err(ReviewError::IncorrectAccountError.into());
+    if description.len() > MovieAccountState::MAX_DESCRIPTION_LEN {
+        msg!("Description cannot be longer than {} bytes", MovieAccountState::MAX_DESCRIPTION_LEN);
+        return Err(ReviewError::DescriptionTooLong.into())
     }
 
     if *token_program.key != TOKEN_PROGRAM_ID {
@@ -113,14 +298,64 @@ pub fn add_movie_review(
         return Err(ReviewError::IncorrectAccountError.into());
     }
 
+    let config_data = load_config(program_id, pda_config)?;
+
+    config_msg!(config_data, "Deriving mint authority");
+    check_stored_pda(program_id, b"token_mint", config_data.mint_bump, token_mint, "token mint", ReviewError::IncorrectAccountError.into())?;
+    let mint_auth_bump = check_stored_pda(program_id, b"token_auth", config_data.mint_auth_bump, mint_auth, "mint authority", ReviewError::InvalidPDA.into())?;
+
+    // Defense in depth: distinct seeds make a collision between these addresses
+    // practically impossible, but the check is cheap and keeps a review or counter
+    // from ever being created over the mint or its authority
+    let created = [pda, *pda_counter.key];
+    if pda == *pda_counter.key || created.contains(token_mint.key) || created.contains(mint_auth.key) {
+        msg!("Review and counter PDAs must differ from each other and from the mint PDAs");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if let Some(referrer_ata) = referrer_ata {
+        check_referrer_ata(referrer_ata, token_mint, initializer)?;
+    }
+
+    check_min_description_len(&description, &config_data)?;
+    let status = status.unwrap_or(MovieAccountState::STATUS_REVIEWED);
+    check_status(status)?;
+    let unrated = status != MovieAccountState::STATUS_REVIEWED && rating == 0;
+    let rating: u8 = if unrated { 0 } else { check_rating(rating, &config_data)?.into() };
+
+    let treasury = if config_data.review_fee > 0 {
+        let pda_treasury = pda_treasury.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let treasury_bump = validate_pda(program_id, &[b"treasury"], pda_treasury, "treasury PDA", ReviewError::InvalidPDA.into())?;
+        Some((pda_treasury, treasury_bump))
+    } else {
+        None
+    };
+
+    let profile_bump = validate_pda(program_id, &[initializer.key.as_ref(), b"profile"], pda_profile, "profile PDA", ReviewError::InvalidPDA.into())?;
+
+    let title_hash = MovieAggregate::title_hash(&title);
+    let aggregate_bump = validate_pda(program_id, &[title_hash.as_ref(), b"aggregate"], pda_aggregate, "aggregate PDA", ReviewError::InvalidPDA.into())?;
 
-    let account_len: usize = 1000;
+    let index_key = IndexBucket::key(&title);
+    let index_bump = validate_pda(program_id, &[b"index", &[index_key]], pda_index, "index PDA", ReviewError::InvalidPDA.into())?;
+    let global_seq_bump = validate_pda(program_id, &[b"global_seq"], pda_global_seq, "global sequence PDA", ReviewError::InvalidPDA.into())?;
 
-    if MovieAccountState::get_account_size(title.clone(), description.clone()) > account_len {
-        msg!("Data length is larger than 1000 bytes");
+    let account_len = MovieAccountState::ACCOUNT_LEN;
+
+    let display_title = display_title.unwrap_or_else(|| title.clone());
+    if display_title.is_empty() {
+        msg!("Display title cannot be empty");
+        return Err(ReviewError::EmptyField.into())
+    }
+
+    if MovieAccountState::get_account_size(&title, &display_title, &description, genre.as_deref().unwrap_or_default()) > account_len {
+        msg!("Data length is larger than {} bytes", account_len);
         return Err(ReviewError::InvalidDataLength.into())
     }
 
+    // Rent comes from the sysvar syscall rather than a passed-in account, which keeps
+    // the account list short. Only initialize_mint needs the rent account, because the
+    // token program reads it.
     let rent = Rent::get()?;
     let rent_lamports = rent.minimum_balance(account_len);
 
@@ -129,95 +364,107 @@ pub fn add_movie_review(
         initializer.key,
         pda_account.key,
         rent_lamports,
-        account_len.try_into().unwrap(),
+        account_len as u64,
         program_id,
         ),
         &[initializer.clone(), pda_account.clone(), system_program.clone()],
         &[&[initializer.key.as_ref(), title.as_bytes().as_ref(), &[bump_seed]]],
     )?;
 
-    msg!("PDA created: {}", pda);
+    config_msg!(config_data, "PDA created: {}", pda);
 
-    msg!("unpacking state account");
+    config_msg!(config_data, "unpacking state account");
     let mut account_data = try_from_slice_unchecked::<MovieAccountState>(&pda_account.data.borrow()).unwrap();
-    msg!("borrowed account data");
+    config_msg!(config_data, "borrowed account data");
 
-    msg!("checking if movie account is already initialized");
+    config_msg!(config_data, "checking if movie account is already initialized");
     if account_data.is_initialized() {
         msg!("Account already initialized");
         return Err(ProgramError::AccountAlreadyInitialized);
     }
 
     account_data.discriminator = MovieAccountState::DISCRIMINATOR.to_string();
+    account_data.version = MovieAccountState::VERSION;
     account_data.review = *initializer.key;
     account_data.title = title;
     account_data.rating = rating;
     account_data.description = description;
     account_data.is_initialized = true;
+    // Stored so later instructions can re-derive the PDA without a bump search
+    account_data.bump = bump_seed;
+    account_data.has_spoilers = false;
+    account_data.display_title = display_title;
+    account_data.status = status;
 
-    msg!("serializing account");
-    account_data.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
-    msg!("state account serialized");
-
+    if let Some(genre) = genre {
+        let now = Clock::get()?.unix_timestamp;
+        account_data.discriminator = MovieAccountState::DISCRIMINATOR_V2.to_string();
+        account_data.genre = genre;
+        account_data.created_at = now;
+        account_data.updated_at = now;
+    }
 
-    msg!("Creating comment counter");
-    let counter_rent_lamports = rent.minimum_balance(MovieCommentCounter::SIZE);
+    let first_review = if unrated {
+        false
+    } else {
+        let (first_review, next_review) = record_review_in_aggregate(
+            program_id, initializer, pda_aggregate, system_program, &title_hash, aggregate_bump, &pda, rating
+        )?;
+        account_data.next_review = next_review;
+        first_review
+    };
+    account_data.global_seq = next_global_seq(program_id, initializer, pda_global_seq, system_program, global_seq_bump)?;
+    config_msg!(config_data, "Global sequence: {}", account_data.global_seq);
 
-    // Derives address and validates that correct seeds were passed in
-    let (counter, counter_bump) = Pubkey::find_program_address(&[pda.as_ref(), "comment".as_ref()], program_id);
-    if counter != *pda_counter.key {
-        msg!("Invalid seeds for PDA");
-        return Err(ProgramError::InvalidArgument);
-    }
+    config_msg!(config_data, "serializing account");
+    check_fits(account_data.serialized_len(), pda_account)?;
+    serialize_into(&account_data, pda_account)?;
+    config_msg!(config_data, "state account serialized");
 
-    // Creating the comment counter account 
-    invoke_signed(
-        &system_instruction::create_account(
-        initializer.key, // rent payer
-        pda_counter.key, // address who we're creating the account for
-        counter_rent_lamports, // amount of rent to put into account
-        MovieCommentCounter::SIZE.try_into().unwrap(), // size of account
-        program_id,
-        ),
-        // List of accounts that will be read from/written to
-        &[initializer.clone(), pda_counter.clone(), system_program.clone()],
-        // Seeds for the PDA
-        &[&[pda.as_ref(), "comment".as_ref(), &[counter_bump]]],
-    )?;
-    msg!("Comment counter created");
 
-    // Deserialize the newly created counter account
-    let mut counter_data = try_from_slice_unchecked::<MovieCommentCounter>(&pda_counter.data.borrow()).unwrap();
+    create_comment_counter(program_id, initializer, pda_counter, system_program, &pda, counter_bump)?;
+    record_review_in_index(program_id, initializer, pda_index, system_program, index_key, index_bump)?;
 
-    msg!("Checking if counter account is already initialized");
-    if counter_data.is_initialized() {
-        msg!("Account already initialized");
-        return Err(ProgramError::AccountAlreadyInitialized);
+    // Charged once the new accounts' rent is paid and before any reward is minted
+    if let Some((pda_treasury, treasury_bump)) = treasury {
+        let treasury_rent = if pda_treasury.owner != program_id { rent.minimum_balance(0) } else { 0 };
+        if initializer.lamports() < add_u64(config_data.review_fee, treasury_rent)? {
+            msg!("Initializer cannot cover the {} lamport review fee on top of rent", config_data.review_fee);
+            return Err(ReviewError::InsufficientFunds.into())
+        }
+        create_treasury_if_missing(program_id, initializer, pda_treasury, system_program, treasury_bump)?;
+        config_msg!(config_data, "Review fee: {} to treasury", config_data.review_fee);
+        invoke(
+            &system_instruction::transfer(initializer.key, pda_treasury.key, config_data.review_fee),
+            &[initializer.clone(), pda_treasury.clone(), system_program.clone()]
+        )?;
     }
 
-    counter_data.discriminator = MovieCommentCounter::DISCRIMINATOR.to_string();
-    counter_data.counter = 0;
-    counter_data.is_initialized = true;
-    msg!("Comment count: {}", counter_data.counter);
-    counter_data.serialize(&mut &mut pda_counter.data.borrow_mut()[..])?;
-    msg!("Comment counter initialized");
+    let review_limit = config_data.max_reviews_per_author;
+    if unrated {
+        // Counts toward max_reviews_per_author like any review, since rating it
+        // later makes it one
+        record_review_in_profile(program_id, initializer, pda_profile, system_program, profile_bump, 0, review_limit)?;
+        config_msg!(config_data, "Unrated watchlist entry; no reward");
+        return Ok(())
+    }
 
 
-    msg!("Minting 10 token to User associated token account");
-    invoke_signed(
-        &spl_token::instruction::mint_to(
-            token_program.key,
-            token_mint.key,
-            user_ata.key,
-            mint_auth.key,
-            &[],
-            10 * LAMPORTS_PER_SOL
-        )?, // ? unwraps and returns the error if there is one
-        // Account infos
-        &[token_mint.clone(), user_ata.clone(), mint_auth.clone()],
-        // Seeds
-        &[&[b"token_auth", &[mint_auth_bump]]]
-    )?;
+    let mut reward_amount = config_data.token_amount(REVIEW_REWARD)?;
+    if first_review {
+        config_msg!(config_data, "first reviewer bonus applied");
+        reward_amount = mul_u64(reward_amount, FIRST_REVIEWER_MULTIPLIER)?;
+    }
+    if record_review_in_profile(program_id, initializer, pda_profile, system_program, profile_bump, reward_amount, review_limit)? {
+        config_msg!(config_data, "{} reward pending until ClaimRewards", reward_amount);
+        if let Some(referrer_ata) = referrer_ata {
+            config_msg!(config_data, "Minting referral bonus to {}", referrer_ata.key);
+            let referral_amount = config_data.token_amount(REFERRAL_REWARD)?;
+            mint_reward(pda_config, token_program, token_mint, referrer_ata, mint_auth, &[b"token_auth", &[mint_auth_bump]], referral_amount)?;
+        }
+    } else {
+        msg!("reward skipped due to cooldown");
+    }
 
 
     Ok(())
@@ -229,13 +476,58 @@ pub fn update_movie_review(
     _title: String,
     rating: u8,
     description: String
-) -> ProgramResult {    
-    msg!("Updating movie review...");
+) -> ProgramResult {
+    update_review_fields(program_id, accounts, Some(rating), Some(description))
+}
+
+// Same accounts as update_movie_review; the rating is left as it is
+pub fn update_description(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _title: String,
+    description: String
+) -> ProgramResult {
+    update_review_fields(program_id, accounts, None, Some(description))
+}
+
+// Same accounts as update_movie_review; the description is left as it is
+pub fn update_rating(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _title: String,
+    rating: u8
+) -> ProgramResult {
+    update_review_fields(program_id, accounts, Some(rating), None)
+}
+
+// Shared by the update instructions. As in update_movie_review, the PDA is
+// re-derived from the signer and the stored title, so the payload title isn't used.
+// The reward mint, its authority, the author's ATA and the token program may follow
+// the system program, with the config writable; then the update also mints the
+// helpful-votes bonus for upvotes received since the last one.
+fn update_review_fields(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    rating: Option<u8>,
+    description: Option<String>
+) -> ProgramResult {
+    verbose_msg!("Updating movie review...");
 
     let account_info_iter = &mut accounts.iter();
 
     let initializer = next_account_info(account_info_iter)?;
     let pda_account = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    // Appended for the rating histogram, so only required when the rating changes
+    let pda_aggregate = account_info_iter.next();
+    let pda_aggregate = if rating.is_some() { Some(pda_aggregate.ok_or(ProgramError::NotEnoughAccountKeys)?) } else { None };
+    // Follows the aggregate and is only required to rate an unrated watchlist entry,
+    // which may have to create the aggregate
+    let system_program = account_info_iter.next();
+    let token_mint = account_info_iter.next();
+    let mint_auth = account_info_iter.next();
+    let user_ata = account_info_iter.next();
+    let token_program = account_info_iter.next();
 
     if pda_account.owner != program_id {
       return Err(ProgramError::IllegalOwner)
@@ -246,413 +538,8127 @@ pub fn update_movie_review(
         return Err(ProgramError::MissingRequiredSignature)
     }
 
-    msg!("unpacking state account");
+    verbose_msg!("unpacking state account");
+    check_discriminator(pda_account, &MovieAccountState::DISCRIMINATORS)?;
     let mut account_data = try_from_slice_unchecked::<MovieAccountState>(&pda_account.data.borrow()).unwrap();
-    msg!("review title: {}", account_data.title);
 
-    let (pda, _bump_seed) = Pubkey::find_program_address(&[initializer.key.as_ref(), account_data.title.as_bytes().as_ref(),], program_id);
+    // Nothing read from an uninitialized account means anything, so check before using it
+    verbose_msg!("checking if movie account is initialized");
+    if !account_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(account_data.version, MovieAccountState::VERSION)?;
+    verbose_msg!("review title: {}", account_data.title);
+
+    let pda = Pubkey::create_program_address(
+        &[initializer.key.as_ref(), account_data.title.as_bytes(), &[account_data.bump]],
+        program_id
+    ).map_err(|_| ReviewError::InvalidPDA)?;
     if pda != *pda_account.key {
         msg!("Invalid seeds for PDA");
         return Err(ReviewError::InvalidPDA.into())
     }
 
-    msg!("checking if movie account is initialized");
-    if !account_data.is_initialized() {
-        msg!("Account is not initialized");
-        return Err(ReviewError::UninitializedAccount.into());
+    if account_data.is_expired(Clock::get()?.unix_timestamp) {
+        msg!("Review expired at {}", account_data.expires_at);
+        return Err(ReviewError::ReviewExpired.into())
     }
 
-    if rating > 5 || rating < 1 {
-        msg!("Invalid Rating");
-        return Err(ReviewError::InvalidRating.into())
+    let config_data = load_config(program_id, pda_config)?;
+    let rating = rating.map(|rating| check_rating(rating, &config_data)).transpose()?;
+
+    if let Some(description) = &description {
+        if description.is_empty() {
+            msg!("Description cannot be empty");
+            return Err(ReviewError::EmptyField.into())
+        }
+        check_min_description_len(description, &config_data)?;
+
+        if description.len() > MovieAccountState::MAX_DESCRIPTION_LEN {
+            msg!("Description cannot be longer than {} bytes", MovieAccountState::MAX_DESCRIPTION_LEN);
+            return Err(ReviewError::DescriptionTooLong.into())
+        }
+
+        // Sized the same way create_movie_review checks a new review
+        let update_len = MovieAccountState::get_account_size(
+            &account_data.title, &account_data.display_title, description, &account_data.genre
+        );
+        if update_len > pda_account.data_len() {
+            msg!("Data length is larger than {} bytes", pda_account.data_len());
+            return Err(ReviewError::InvalidDataLength.into())
+        }
     }
 
-    let update_len: usize = 1 + 1 + (4 + description.len()) + account_data.title.len();
-    if update_len > 1000 {
-        msg!("Data length is larger than 1000 bytes");
-        return Err(ReviewError::InvalidDataLength.into())
+    config_msg!(config_data, "Review before update:");
+    config_msg!(config_data, "Title: {}", account_data.title);
+    config_msg!(config_data, "Rating: {}", account_data.rating);
+    config_msg!(config_data, "Description: {}", account_data.description);
+
+    if let (Some(rating), Some(pda_aggregate)) = (rating, pda_aggregate) {
+        if account_data.rating == 0 {
+            // An unrated watchlist entry was left out of the aggregate and its review
+            // list when created, so it joins both on its first rating
+            let system_program = system_program.ok_or(ProgramError::NotEnoughAccountKeys)?;
+            if *system_program.key != SYSTEM_PROGRAM_ID {
+                msg!("Incorrect system program");
+                return Err(ReviewError::IncorrectAccountError.into());
+            }
+            let title_hash = MovieAggregate::title_hash(&account_data.title);
+            let aggregate_bump = validate_pda(program_id, &[title_hash.as_ref(), b"aggregate"], pda_aggregate, "aggregate PDA", ReviewError::InvalidPDA.into())?;
+            let (_first_review, next_review) = record_review_in_aggregate(
+                program_id, initializer, pda_aggregate, system_program, &title_hash, aggregate_bump, pda_account.key, rating.into()
+            )?;
+            account_data.next_review = next_review;
+        } else {
+            move_rating_in_aggregate(program_id, pda_aggregate, &account_data.title, account_data.rating, rating.into())?;
+        }
+        account_data.rating = rating.into();
+    }
+    if let Some(description) = description {
+        account_data.description = description;
+    }
+    account_data.edited = true;
+    if account_data.discriminator == MovieAccountState::DISCRIMINATOR_V2 {
+        account_data.updated_at = Clock::get()?.unix_timestamp;
     }
 
-    msg!("Review before update:");
-    msg!("Title: {}", account_data.title);
-    msg!("Rating: {}", account_data.rating);
-    msg!("Description: {}", account_data.description);
+    if let (Some(token_mint), Some(mint_auth), Some(user_ata), Some(token_program)) = (token_mint, mint_auth, user_ata, token_program) {
+        assert_not_signer(mint_auth, "Mint authority")?;
+        check_stored_pda(program_id, b"token_mint", config_data.mint_bump, token_mint, "token mint", ReviewError::IncorrectAccountError.into())?;
+        let mint_auth_bump = check_stored_pda(program_id, b"token_auth", config_data.mint_auth_bump, mint_auth, "mint authority", ReviewError::InvalidPDA.into())?;
 
-    account_data.rating = rating;
-    account_data.description = description;
+        if *user_ata.key != get_associated_token_address(initializer.key, token_mint.key) {
+            msg!("Incorrect token mint");
+            return Err(ReviewError::IncorrectAccountError.into());
+        }
+        check_ata_exists(user_ata)?;
+
+        if *token_program.key != TOKEN_PROGRAM_ID {
+            msg!("Incorrect token program");
+            return Err(ReviewError::IncorrectAccountError.into());
+        }
+
+        // Upvotes past the cap are forfeited rather than carried to the next update
+        let new_upvotes = sub_u64(account_data.upvotes.into(), account_data.rewarded_upvotes.into())?.min(MAX_REWARDED_UPVOTES);
+        let bonus = mul_u64(config_data.token_amount(UPVOTE_REWARD)?, new_upvotes)?;
+        config_msg!(config_data, "Helpful votes bonus: {}", bonus);
+        if bonus > 0 {
+            mint_reward(pda_config, token_program, token_mint, user_ata, mint_auth, &[b"token_auth", &[mint_auth_bump]], bonus)?;
+            delegate_reward(token_program, user_ata, mint_auth, initializer, bonus)?;
+        }
+        account_data.rewarded_upvotes = account_data.upvotes;
+    }
 
-    msg!("Review after update:");
-    msg!("Title: {}", account_data.title);
-    msg!("Rating: {}", account_data.rating);
-    msg!("Description: {}", account_data.description);
+    config_msg!(config_data, "Review after update:");
+    config_msg!(config_data, "Title: {}", account_data.title);
+    config_msg!(config_data, "Rating: {}", account_data.rating);
+    config_msg!(config_data, "Description: {}", account_data.description);
 
-    msg!("serializing account");
-    account_data.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
-    msg!("state account serialized");
+    config_msg!(config_data, "serializing account");
+    check_fits(account_data.serialized_len(), pda_account)?;
+    serialize_into(&account_data, pda_account)?;
+    config_msg!(config_data, "state account serialized");
 
     Ok(())
 }
 
-pub fn add_comment(
+// Like update_movie_review, only the author in the PDA seeds can set the flag
+pub fn set_spoiler_flag(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    comment: String
+    title: String,
+    has_spoilers: bool
 ) -> ProgramResult {
-    msg!("Adding comment...");
-    msg!("Comment: {}", comment);
+    verbose_msg!("Setting spoiler flag...");
 
     let account_info_iter = &mut accounts.iter();
 
-    let commenter = next_account_info(account_info_iter)?;
-    let pda_review = next_account_info(account_info_iter)?;
-    let pda_counter = next_account_info(account_info_iter)?;
-    let pda_comment = next_account_info(account_info_iter)?;
-    let token_mint = next_account_info(account_info_iter)?;
-    let mint_auth = next_account_info(account_info_iter)?;
-    let user_ata = next_account_info(account_info_iter)?;
-    let system_program = next_account_info(account_info_iter)?;
-    let token_program = next_account_info(account_info_iter)?;
-
-    let mut counter_data = try_from_slice_unchecked::<MovieCommentCounter>(&pda_counter.data.borrow()).unwrap();
+    let initializer = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
 
-    let account_len = MovieComment::get_account_size(comment.clone());
+    if pda_account.owner != program_id {
+      return Err(ProgramError::IllegalOwner)
+    }
 
-    let rent = Rent::get()?;
-    let rent_lamports = rent.minimum_balance(account_len);
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
 
-    let (pda, bump_seed) = Pubkey::find_program_address(
-        &[pda_review.key.as_ref(), counter_data.counter.to_be_bytes().as_ref()],
-        program_id
-    );
+    check_discriminator(pda_account, &MovieAccountState::DISCRIMINATORS)?;
+    let mut account_data = try_from_slice_unchecked::<MovieAccountState>(&pda_account.data.borrow()).unwrap();
 
-    if pda != *pda_comment.key {
-        msg!("Invalid seeds for PDA");
-        return Err(ReviewError::InvalidPDA.into());
+    if !account_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
     }
 
-    msg!("Deriving mint authority");
-    let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[b"token_mint"], program_id);
-    let (mint_auth_pda, mint_auth_bump) = Pubkey::find_program_address(&[b"token_auth"], program_id);
+    check_version(account_data.version, MovieAccountState::VERSION)?;
 
-    if *token_mint.key != mint_pda {
-        msg!("Incorrect token mint");
-        return Err(ReviewError::IncorrectAccountError.into());
+    assert_valid_seed(&title)?;
+    let pda = Pubkey::create_program_address(
+        &[initializer.key.as_ref(), title.as_bytes(), &[account_data.bump]],
+        program_id
+    ).map_err(|_| ReviewError::InvalidPDA)?;
+    if pda != *pda_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into())
     }
 
-    if *mint_auth.key != mint_auth_pda {
-        msg!("Mint passed in and mint derived do not match");
-        return Err(ReviewError::InvalidPDA.into());
-    }
+    verbose_msg!("Has spoilers: {}", has_spoilers);
+    account_data.has_spoilers = has_spoilers;
 
-    if *user_ata.key != get_associated_token_address(commenter.key, token_mint.key) {
-        msg!("Incorrect token mint");
-        return Err(ReviewError::IncorrectAccountError.into());
-    }
+    serialize_into(&account_data, pda_account)?;
+    verbose_msg!("state account serialized");
 
-    if *token_program.key != TOKEN_PROGRAM_ID {
-        msg!("Incorrect token program");
-        return Err(ReviewError::IncorrectAccountError.into());
-    }
+    Ok(())
+}
 
-    invoke_signed(
-        &system_instruction::create_account(
-            commenter.key,
-            pda_comment.key,
-            rent_lamports,
-            account_len.try_into().unwrap(),
-            program_id
-        ),
-        &[commenter.clone(), pda_comment.clone(), system_program.clone()],
-        &[&[pda_review.key.as_ref(), counter_data.counter.to_be_bytes().as_ref(), &[bump_seed]]]
-    )?;
-    msg!("Created comment account");
+/// Pins comment `index` on the signer's review, or unpins with None. The index has
+/// to be below the review's comment count. Accounts: author (signer), review PDA,
+/// comment counter PDA.
+pub fn pin_comment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String,
+    index: Option<u64>
+) -> ProgramResult {
+    verbose_msg!("Pinning comment...");
 
-    let mut comment_data = try_from_slice_unchecked::<MovieComment>(&pda_comment.data.borrow()).unwrap();
+    let account_info_iter = &mut accounts.iter();
 
-    msg!("Checking if comment account is already initialized");
-    if comment_data.is_initialized {
-        msg!("Account already initialized");
-        return Err(ProgramError::AccountAlreadyInitialized);
-    }
+    let initializer = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
 
-    comment_data.discriminator = MovieComment::DISCRIMINATOR.to_string();
-    comment_data.review = *pda_review.key;
-    comment_data.commenter = *commenter.key;
-    comment_data.comment = comment;
-    comment_data.is_initialized = true;
-    comment_data.serialize(&mut & mut pda_comment.data.borrow_mut()[..])?;
+    if pda_account.owner != program_id {
+      return Err(ProgramError::IllegalOwner)
+    }
 
-    msg!("Comment count: {}", counter_data.counter);
-    counter_data.counter += 1;
-    counter_data.serialize(&mut &mut pda_counter.data.borrow_mut()[..])?;
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
 
+    check_discriminator(pda_account, &MovieAccountState::DISCRIMINATORS)?;
+    let mut account_data = try_from_slice_unchecked::<MovieAccountState>(&pda_account.data.borrow()).unwrap();
 
-    msg!("Minting 5 tokens to User associated token account");
-    invoke_signed(
-        // Instruction
-        &spl_token::instruction::mint_to(
-            token_program.key,
-            token_mint.key,
-            user_ata.key,
-            mint_auth.key,
-            &[],
-            5 * LAMPORTS_PER_SOL
-        )?,
-        // Account Infos
-        &[token_mint.clone(), user_ata.clone(), mint_auth.clone()],
-        // Seeds
-        &[&[b"token_auth", &[mint_auth_bump]]]
+    if !account_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(account_data.version, MovieAccountState::VERSION)?;
+
+    assert_valid_seed(&title)?;
+    let pda = Pubkey::create_program_address(
+        &[initializer.key.as_ref(), title.as_bytes(), &[account_data.bump]],
+        program_id
+    ).map_err(|_| ReviewError::InvalidPDA)?;
+    if pda != *pda_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if let Some(index) = index {
+        validate_pda(program_id, &[pda.as_ref(), b"comment"], pda_counter, "counter PDA", ReviewError::InvalidPDA.into())?;
+        if pda_counter.owner != program_id || pda_counter.data_len() < MovieCommentCounter::SIZE {
+            msg!("Comment counter {} does not exist", pda_counter.key);
+            return Err(ReviewError::CounterNotInitialized.into())
+        }
+
+        check_discriminator(pda_counter, &[MovieCommentCounter::DISCRIMINATOR])?;
+        let counter_data = try_from_slice_unchecked::<MovieCommentCounter>(&pda_counter.data.borrow()).unwrap();
+        check_version(counter_data.version, MovieCommentCounter::VERSION)?;
+
+        if index >= counter_data.counter {
+            msg!("Comment index {} is out of range; the review has {} comments", index, counter_data.counter);
+            return Err(ReviewError::CommentIndexOutOfRange.into())
+        }
+    }
+
+    verbose_msg!("Pinned comment: {:?}", index);
+    account_data.pinned_comment = index;
+
+    serialize_into(&account_data, pda_account)?;
+    verbose_msg!("state account serialized");
+
+    Ok(())
+}
+
+// Moves a review between reviewed and the watchlist statuses. Like set_spoiler_flag,
+// only the author in the PDA seeds can do this. An unrated entry has to be rated
+// with UpdateRating before it can be marked reviewed.
+pub fn set_watch_status(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String,
+    status: u8
+) -> ProgramResult {
+    verbose_msg!("Setting watch status...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+
+    if pda_account.owner != program_id {
+      return Err(ProgramError::IllegalOwner)
+    }
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    check_status(status)?;
+
+    check_discriminator(pda_account, &MovieAccountState::DISCRIMINATORS)?;
+    let mut account_data = try_from_slice_unchecked::<MovieAccountState>(&pda_account.data.borrow()).unwrap();
+
+    if !account_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(account_data.version, MovieAccountState::VERSION)?;
+
+    assert_valid_seed(&title)?;
+    let pda = Pubkey::create_program_address(
+        &[initializer.key.as_ref(), title.as_bytes(), &[account_data.bump]],
+        program_id
+    ).map_err(|_| ReviewError::InvalidPDA)?;
+    if pda != *pda_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if status == MovieAccountState::STATUS_REVIEWED && account_data.rating == 0 {
+        msg!("Rate the movie with UpdateRating before marking it reviewed");
+        return Err(ReviewError::InvalidRating.into())
+    }
+
+    verbose_msg!("Watch status: {} -> {}", account_data.status, status);
+    account_data.status = status;
+
+    serialize_into(&account_data, pda_account)?;
+    verbose_msg!("state account serialized");
+
+    Ok(())
+}
+
+// Recategorizes a review without resubmitting it. Like set_spoiler_flag, only the
+// author in the PDA seeds can do this; they also pay for any growth in the account.
+pub fn set_genre(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String,
+    genre: String
+) -> ProgramResult {
+    verbose_msg!("Setting genre...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if pda_account.owner != program_id {
+      return Err(ProgramError::IllegalOwner)
+    }
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if !MovieAccountState::GENRES.contains(&genre.as_str()) {
+        msg!("Unknown genre: {}", genre);
+        return Err(ReviewError::InvalidGenre.into())
+    }
+
+    check_discriminator(pda_account, &MovieAccountState::DISCRIMINATORS)?;
+    let mut account_data = try_from_slice_unchecked::<MovieAccountState>(&pda_account.data.borrow()).unwrap();
+
+    if !account_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(account_data.version, MovieAccountState::VERSION)?;
+
+    assert_valid_seed(&title)?;
+    let pda = Pubkey::create_program_address(
+        &[initializer.key.as_ref(), title.as_bytes(), &[account_data.bump]],
+        program_id
+    ).map_err(|_| ReviewError::InvalidPDA)?;
+    if pda != *pda_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        msg!("Incorrect system program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    verbose_msg!("Genre: {} -> {}", account_data.genre, genre);
+    account_data.genre = genre;
+    if account_data.discriminator == MovieAccountState::DISCRIMINATOR_V2 {
+        account_data.updated_at = Clock::get()?.unix_timestamp;
+    }
+
+    // Reviews normally have room to spare, but one sized exactly to its contents
+    // has to grow for a longer genre
+    let account_len = account_data.serialized_len();
+    if account_len > pda_account.data_len() {
+        let rent_lamports = Rent::get()?.minimum_balance(account_len);
+        let current_lamports = pda_account.lamports();
+        if rent_lamports > current_lamports {
+            invoke(
+                &system_instruction::transfer(initializer.key, pda_account.key, sub_u64(rent_lamports, current_lamports)?),
+                &[initializer.clone(), pda_account.clone(), system_program.clone()]
+            )?;
+        }
+        pda_account.realloc(account_len, false)?;
+    }
+
+    check_fits(account_data.serialized_len(), pda_account)?;
+    serialize_into(&account_data, pda_account)?;
+    verbose_msg!("state account serialized");
+
+    Ok(())
+}
+
+/// Hands the stored authorship of a review to `new_owner`. The PDA stays at
+/// `[original_author, title]` so existing comments keep pointing at it, which also
+/// means `update_movie_review` must still be signed by that original seed author.
+pub fn transfer_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String,
+    new_owner: Pubkey
+) -> ProgramResult {
+    verbose_msg!("Transferring movie review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let owner = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+
+    if pda_account.owner != program_id {
+      return Err(ProgramError::IllegalOwner)
+    }
+
+    if !owner.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    check_discriminator(pda_account, &MovieAccountState::DISCRIMINATORS)?;
+    let mut account_data = try_from_slice_unchecked::<MovieAccountState>(&pda_account.data.borrow()).unwrap();
+
+    if !account_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(account_data.version, MovieAccountState::VERSION)?;
+
+    if account_data.title != title {
+        msg!("Review title does not match");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if account_data.review != *owner.key {
+        msg!("Only the current author can transfer a review");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    verbose_msg!("New owner: {}", new_owner);
+    account_data.review = new_owner;
+
+    serialize_into(&account_data, pda_account)?;
+    verbose_msg!("state account serialized");
+
+    Ok(())
+}
+
+/// Rewrites a review stored in the pre-version `OldMovieAccountState` layout as a
+/// current `MovieAccountState`, growing the account to the ACCOUNT_LEN new reviews get.
+/// Added fields start out as a V1 review's would: no genre, zero timestamps, no
+/// spoilers. Either the author or the config admin may migrate; the signer pays any
+/// extra rent.
+pub fn migrate_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String
+) -> ProgramResult {
+    verbose_msg!("Migrating movie review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let signer = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if pda_account.owner != program_id {
+      return Err(ProgramError::IllegalOwner)
+    }
+
+    if !signer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    let old_data = try_from_slice_unchecked::<OldMovieAccountState>(&pda_account.data.borrow()).map_err(|_| {
+        msg!("Account is not in the legacy review layout");
+        ProgramError::InvalidAccountData
+    })?;
+
+    if !old_data.is_initialized || old_data.discriminator != MovieAccountState::DISCRIMINATOR {
+        msg!("Account is not an initialized legacy review");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    assert_valid_seed(&title)?;
+    // A current-layout account misread as the legacy one yields a garbled author,
+    // so this also refuses to migrate the same review twice
+    let (pda, bump_seed) = Pubkey::find_program_address(&[old_data.review.as_ref(), title.as_bytes()], program_id);
+    if pda != *pda_account.key || old_data.title != title {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    let config_data = load_config(program_id, pda_config)?;
+    if old_data.review != *signer.key && config_data.admin != *signer.key {
+        msg!("Only the author or the config admin can migrate a review");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        msg!("Incorrect system program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    let account_data = MovieAccountState::new(
+        old_data.review, old_data.title, old_data.rating, old_data.description, bump_seed
+    );
+
+    // Same size create_movie_review allocates, so later updates have the same room
+    let account_len = pda_account.data_len().max(MovieAccountState::ACCOUNT_LEN);
+    let rent_lamports = Rent::get()?.minimum_balance(account_len);
+    let current_lamports = pda_account.lamports();
+
+    if rent_lamports > current_lamports {
+        invoke(
+            &system_instruction::transfer(signer.key, pda_account.key, sub_u64(rent_lamports, current_lamports)?),
+            &[signer.clone(), pda_account.clone(), system_program.clone()]
+        )?;
+    }
+
+    pda_account.realloc(account_len, true)?;
+
+    config_msg!(config_data, "serializing account");
+    check_fits(account_data.serialized_len(), pda_account)?;
+    serialize_into(&account_data, pda_account)?;
+    config_msg!(config_data, "Review migrated to version {}", MovieAccountState::VERSION);
+
+    Ok(())
+}
+
+/// Writes a review from historical data on `author`'s behalf, keeping its original
+/// `created_at`. Only the config admin may import, and pays for the accounts. The
+/// review gets its comment counter and is counted in the movie aggregate and index
+/// like any other, but no reward is minted and the author's profile is untouched.
+/// It takes the next global_seq, so it sorts after every review already written.
+/// Accounts: admin, config PDA, review PDA, counter PDA, aggregate PDA, index PDA,
+/// system program, global sequence PDA.
+pub fn import_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    author: Pubkey,
+    title: String,
+    rating: u8,
+    description: String,
+    created_at: i64
+) -> ProgramResult {
+    verbose_msg!("Importing movie review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_account = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let pda_aggregate = next_account_info(account_info_iter)?;
+    let pda_index = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let pda_global_seq = next_account_info(account_info_iter)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    let config_data = load_config(program_id, pda_config)?;
+    if config_data.admin != *admin.key {
+        msg!("Only the config admin can import reviews");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        msg!("Incorrect system program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    assert_valid_seed(&title)?;
+    let (pda, bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], program_id);
+    if pda != *pda_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if pda_account.lamports() > 0 || pda_account.owner == program_id {
+        msg!("A review of \"{}\" by {} already exists", title, author);
+        return Err(ReviewError::ReviewAlreadyExists.into())
+    }
+
+    let (counter_pda, counter_bump) = Pubkey::find_program_address(&[pda.as_ref(), "comment".as_ref()], program_id);
+    if counter_pda != *pda_counter.key {
+        msg!("Invalid seeds for counter PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    let title_hash = MovieAggregate::title_hash(&title);
+    let (aggregate_pda, aggregate_bump) = Pubkey::find_program_address(&[title_hash.as_ref(), b"aggregate"], program_id);
+    if aggregate_pda != *pda_aggregate.key {
+        msg!("Invalid seeds for aggregate PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    let index_key = IndexBucket::key(&title);
+    let (index_pda, index_bump) = Pubkey::find_program_address(&[b"index", &[index_key]], program_id);
+    if index_pda != *pda_index.key {
+        msg!("Invalid seeds for index PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+    let global_seq_bump = validate_pda(program_id, &[b"global_seq"], pda_global_seq, "global sequence PDA", ReviewError::InvalidPDA.into())?;
+
+    if created_at < 0 || created_at > Clock::get()?.unix_timestamp {
+        msg!("Imported timestamp {} is before the epoch or in the future", created_at);
+        return Err(ReviewError::InvalidTimestamp.into())
+    }
+
+    if title.is_empty() || description.is_empty() {
+        msg!("Title and description cannot be empty");
+        return Err(ReviewError::EmptyField.into())
+    }
+
+    if description.len() > MovieAccountState::MAX_DESCRIPTION_LEN {
+        msg!("Description cannot be longer than {} bytes", MovieAccountState::MAX_DESCRIPTION_LEN);
+        return Err(ReviewError::DescriptionTooLong.into())
+    }
+
+    // Same size create_movie_review allocates
+    let account_len = MovieAccountState::ACCOUNT_LEN;
+    if MovieAccountState::get_account_size(&title, &title, &description, "") > account_len {
+        msg!("Data length is larger than {} bytes", account_len);
+        return Err(ReviewError::InvalidDataLength.into())
+    }
+
+    let rating: u8 = check_rating(rating, &config_data)?.into();
+
+    // Written in the V2 layout, whose timestamps are what an import preserves; the
+    // genre is left for SetGenre
+    let (_first_review, next_review) = record_review_in_aggregate(
+        program_id, admin, pda_aggregate, system_program, &title_hash, aggregate_bump, &pda, rating
     )?;
+    let account_data = MovieAccountState {
+        discriminator: MovieAccountState::DISCRIMINATOR_V2.to_string(),
+        created_at,
+        updated_at: created_at,
+        next_review,
+        global_seq: next_global_seq(program_id, admin, pda_global_seq, system_program, global_seq_bump)?,
+        ..MovieAccountState::new(author, title, rating, description, bump_seed)
+    };
+
+    invoke_signed(
+        &system_instruction::create_account(
+        admin.key,
+        pda_account.key,
+        Rent::get()?.minimum_balance(account_len),
+        account_len as u64,
+        program_id,
+        ),
+        &[admin.clone(), pda_account.clone(), system_program.clone()],
+        &[&[author.as_ref(), account_data.title.as_bytes(), &[bump_seed]]],
+    )?;
+
+    check_fits(account_data.serialized_len(), pda_account)?;
+    serialize_into(&account_data, pda_account)?;
+    config_msg!(config_data, "Imported review created at {}", created_at);
+
+    create_comment_counter(program_id, admin, pda_counter, system_program, &pda, counter_bump)?;
+    record_review_in_index(program_id, admin, pda_index, system_program, index_key, index_bump)?;
+
+    Ok(())
+}
+
+/// Sends `amount` lamports from the tipper to a review's current author, less the
+/// configured `tip_fee_bps` share, which goes to the `[b"treasury"]` PDA. The
+/// treasury is a data-less program-owned account, created on the first tip at the
+/// tipper's expense.
+pub fn tip_reviewer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64
+) -> ProgramResult {
+    verbose_msg!("Tipping reviewer...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let tipper = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let author = next_account_info(account_info_iter)?;
+    let pda_treasury = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !tipper.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    // A transferred review keeps its original seeds, so it can't be re-derived from
+    // the author; the owner check plus discriminator is what pins it as a review
+    if pda_review.owner != program_id {
+      return Err(ProgramError::IllegalOwner)
+    }
+
+    check_discriminator(pda_review, &MovieAccountState::DISCRIMINATORS)?;
+    let review_data = try_from_slice_unchecked::<MovieAccountState>(&pda_review.data.borrow()).unwrap();
+
+    if !review_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(review_data.version, MovieAccountState::VERSION)?;
+
+    if review_data.review != *author.key {
+        msg!("Tips go to the review's current author");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    let (treasury_pda, treasury_bump) = Pubkey::find_program_address(&[b"treasury"], program_id);
+    if treasury_pda != *pda_treasury.key {
+        msg!("Invalid seeds for treasury PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        msg!("Incorrect system program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    let config_data = load_config(program_id, pda_config)?;
+    let (fee, author_share) = match config_data.split_tip(amount) {
+        Ok(split) => split,
+        Err(e) => {
+            msg!("Tip of {} lamports is too small to split", amount);
+            return Err(e.into())
+        }
+    };
+
+    create_treasury_if_missing(program_id, tipper, pda_treasury, system_program, treasury_bump)?;
+
+    config_msg!(config_data, "Tip: {} to author, {} to treasury", author_share, fee);
+    invoke(
+        &system_instruction::transfer(tipper.key, author.key, author_share),
+        &[tipper.clone(), author.clone(), system_program.clone()]
+    )?;
+    if fee > 0 {
+        invoke(
+            &system_instruction::transfer(tipper.key, pda_treasury.key, fee),
+            &[tipper.clone(), pda_treasury.clone(), system_program.clone()]
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Read-only existence check meant for simulation. The answer is logged as
+/// `sol_log_data([review_pda, [exists]])` rather than returned as an error, so the
+/// instruction succeeds whether or not the review is there.
+pub fn check_review_exists(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    author: Pubkey,
+    title: String
+) -> ProgramResult {
+    verbose_msg!("Checking movie review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let pda_account = next_account_info(account_info_iter)?;
+
+    assert_valid_seed(&title)?;
+    let (pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], program_id);
+    if pda != *pda_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    // An uncreated PDA is owned by the system program and holds no data
+    let exists = pda_account.owner == program_id
+        && try_from_slice_unchecked::<MovieAccountState>(&pda_account.data.borrow())
+            .map(|account_data| account_data.is_initialized())
+            .unwrap_or(false);
+
+    msg!("Review exists: {}", exists);
+    sol_log_data(&[pda_account.key.as_ref(), &[exists as u8]]);
+
+    Ok(())
+}
+
+/// Read-only snapshot of a review for backup tooling, meant for simulation. The
+/// whole `MovieAccountState` is Borsh-serialized, without the account's padding,
+/// and logged as a single `sol_log_data` field. The same bytes are set as return
+/// data; a review account is at most ACCOUNT_LEN bytes, which is exactly what
+/// return data can hold. Nothing is written. Accounts: review PDA.
+pub fn export_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    author: Pubkey,
+    title: String
+) -> ProgramResult {
+    verbose_msg!("Exporting movie review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let pda_review = next_account_info(account_info_iter)?;
+
+    assert_valid_seed(&title)?;
+    validate_pda(program_id, &[author.as_ref(), title.as_bytes()], pda_review, "PDA", ReviewError::InvalidPDA.into())?;
+
+    if pda_review.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    check_discriminator(pda_review, &MovieAccountState::DISCRIMINATORS)?;
+    let review_data = try_from_slice_unchecked::<MovieAccountState>(&pda_review.data.borrow()).unwrap();
+    if !review_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(review_data.version, MovieAccountState::VERSION)?;
+
+    let snapshot = review_data.try_to_vec()?;
+    msg!("Review snapshot: {} bytes", snapshot.len());
+    sol_log_data(&[&snapshot]);
+    set_return_data(&snapshot);
+
+    Ok(())
+}
+
+/// Read-only lookup of one comment, meant for simulation. The comment is logged as
+/// `sol_log_data([comment_pda, commenter, comment, count])` alongside a `msg!` line;
+/// nothing is written. Accounts: review PDA, counter PDA, comment PDA.
+pub fn log_comment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    author: Pubkey,
+    title: String,
+    index: u64
+) -> ProgramResult {
+    verbose_msg!("Looking up comment {}...", index);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let pda_comment = next_account_info(account_info_iter)?;
+
+    assert_valid_seed(&title)?;
+    let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], program_id);
+    if review_pda != *pda_review.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    let (counter_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), b"comment"], program_id);
+    if counter_pda != *pda_counter.key {
+        msg!("Invalid seeds for counter PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if pda_counter.owner != program_id || pda_counter.data_len() < MovieCommentCounter::SIZE {
+        msg!("Comment counter {} does not exist", pda_counter.key);
+        return Err(ReviewError::CounterNotInitialized.into())
+    }
+
+    check_discriminator(pda_counter, &[MovieCommentCounter::DISCRIMINATOR])?;
+    let counter_data = try_from_slice_unchecked::<MovieCommentCounter>(&pda_counter.data.borrow()).unwrap();
+    check_version(counter_data.version, MovieCommentCounter::VERSION)?;
+
+    if index >= counter_data.counter {
+        msg!("Comment index {} is out of range; the review has {} comments", index, counter_data.counter);
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    let (comment_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), index.to_be_bytes().as_ref()], program_id);
+    if comment_pda != *pda_comment.key {
+        msg!("Invalid seeds for comment PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if pda_comment.owner != program_id {
+      return Err(ProgramError::IllegalOwner)
+    }
+
+    check_discriminator(pda_comment, &[MovieComment::DISCRIMINATOR])?;
+    let comment_data = try_from_slice_unchecked::<MovieComment>(&pda_comment.data.borrow()).unwrap();
+
+    if !comment_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(comment_data.version, MovieComment::VERSION)?;
+
+    msg!("Comment {} by {}: {}", comment_data.count, comment_data.commenter, comment_data.comment);
+    sol_log_data(&[
+        pda_comment.key.as_ref(),
+        comment_data.commenter.as_ref(),
+        comment_data.comment.as_bytes(),
+        &comment_data.count.to_le_bytes(),
+    ]);
+
+    Ok(())
+}
+
+/// Adds one reaction to a comment. Each reactor gets a single reaction per comment,
+/// recorded by a ReactionMarker at the `[comment_pda, reactor, b"react"]` PDA; the
+/// reactor pays its rent. The review is addressed by its stored author and the
+/// given title, so reactors don't need to know who wrote it.
+pub fn react_to_comment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String,
+    index: u64,
+    reaction_kind: u8
+) -> ProgramResult {
+    verbose_msg!("Reacting to comment {}...", index);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let reactor = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_comment = next_account_info(account_info_iter)?;
+    let pda_reaction = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !reactor.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if reaction_kind as usize >= MovieComment::REACTION_KINDS {
+        msg!("Unknown reaction kind: {}", reaction_kind);
+        return Err(ReviewError::InvalidReactionKind.into())
+    }
+
+    if pda_review.owner != program_id || pda_comment.owner != program_id {
+      return Err(ProgramError::IllegalOwner)
+    }
+
+    check_discriminator(pda_review, &MovieAccountState::DISCRIMINATORS)?;
+    let review_data = try_from_slice_unchecked::<MovieAccountState>(&pda_review.data.borrow()).unwrap();
+
+    if !review_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(review_data.version, MovieAccountState::VERSION)?;
+
+    assert_valid_seed(&title)?;
+    let review_pda = Pubkey::create_program_address(
+        &[review_data.review.as_ref(), title.as_bytes(), &[review_data.bump]],
+        program_id
+    ).map_err(|_| ReviewError::InvalidPDA)?;
+    if review_pda != *pda_review.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    check_discriminator(pda_comment, &[MovieComment::DISCRIMINATOR])?;
+    let mut comment_data = try_from_slice_unchecked::<MovieComment>(&pda_comment.data.borrow()).unwrap();
+
+    if !comment_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(comment_data.version, MovieComment::VERSION)?;
+
+    let comment_pda = Pubkey::create_program_address(
+        &[review_pda.as_ref(), index.to_be_bytes().as_ref(), &[comment_data.bump]],
+        program_id
+    ).map_err(|_| ReviewError::InvalidPDA)?;
+    if comment_pda != *pda_comment.key || comment_data.review != review_pda {
+        msg!("Invalid seeds for comment PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    let (reaction_pda, reaction_bump) = Pubkey::find_program_address(
+        &[comment_pda.as_ref(), reactor.key.as_ref(), b"react"],
+        program_id
+    );
+    if reaction_pda != *pda_reaction.key {
+        msg!("Invalid seeds for reaction PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    // Only the program can hand the PDA to itself, so lamports someone sent there
+    // don't count as a reaction
+    if pda_reaction.owner == program_id {
+        msg!("{} has already reacted to comment {}", reactor.key, index);
+        return Err(ReviewError::AlreadyReacted.into())
+    }
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        msg!("Incorrect system program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    create_pda_account(
+        program_id,
+        reactor,
+        pda_reaction,
+        ReactionMarker::SIZE,
+        system_program,
+        &[comment_pda.as_ref(), reactor.key.as_ref(), b"react", &[reaction_bump]]
+    )?;
+
+    let marker_data = ReactionMarker {
+        discriminator: ReactionMarker::DISCRIMINATOR.to_string(),
+        version: ReactionMarker::VERSION,
+        is_initialized: true,
+        bump: reaction_bump,
+        reaction_kind,
+    };
+    serialize_into(&marker_data, pda_reaction)?;
+
+    let count = &mut comment_data.reactions[reaction_kind as usize];
+    *count = add_u32(*count, 1)?;
+    verbose_msg!("Reaction {} count: {}", reaction_kind, count);
+
+    serialize_into(&comment_data, pda_comment)?;
+
+    Ok(())
+}
+
+/// Recreates the comment counter of a review that lacks one (created before
+/// counters existed, or with its counter closed). Anyone may pay for it; if the
+/// counter is already there this does nothing.
+pub fn ensure_counter(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    author: Pubkey,
+    title: String
+) -> ProgramResult {
+    verbose_msg!("Ensuring comment counter...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let payer = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !payer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    assert_valid_seed(&title)?;
+    let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], program_id);
+    if review_pda != *pda_review.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if pda_review.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    check_discriminator(pda_review, &MovieAccountState::DISCRIMINATORS)?;
+    let review_data = try_from_slice_unchecked::<MovieAccountState>(&pda_review.data.borrow()).unwrap();
+    if !review_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    let (counter_pda, counter_bump) = Pubkey::find_program_address(&[review_pda.as_ref(), "comment".as_ref()], program_id);
+    if counter_pda != *pda_counter.key {
+        msg!("Invalid seeds for counter PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        msg!("Incorrect system program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if pda_counter.owner == program_id {
+        check_discriminator(pda_counter, &[MovieCommentCounter::DISCRIMINATOR])?;
+        if pda_counter.data_len() < MovieCommentCounter::SIZE {
+            // Grows a counter from before unique_commenters; commenters it already
+            // had are not counted
+            verbose_msg!("Resizing comment counter");
+            let rent_lamports = Rent::get()?.minimum_balance(MovieCommentCounter::SIZE);
+            let current_lamports = pda_counter.lamports();
+            if rent_lamports > current_lamports {
+                invoke(
+                    &system_instruction::transfer(payer.key, pda_counter.key, sub_u64(rent_lamports, current_lamports)?),
+                    &[payer.clone(), pda_counter.clone(), system_program.clone()]
+                )?;
+            }
+            pda_counter.realloc(MovieCommentCounter::SIZE, true)?;
+        }
+        let counter_data = try_from_slice_unchecked::<MovieCommentCounter>(&pda_counter.data.borrow()).unwrap();
+        if counter_data.is_initialized() {
+            msg!("Comment counter already exists at {}, nothing to do", counter_pda);
+            return Ok(())
+        }
+    }
+
+    create_comment_counter(program_id, payer, pda_counter, system_program, &review_pda, counter_bump)
+}
+
+/// Comments live at `[review_pda, counter]`. The counter only ever increases, so
+/// an index is never handed out twice, even once comment accounts can be closed.
+///
+/// The counter seed is the 8 byte big-endian `u64` (`counter.to_be_bytes()`), as
+/// the frontend's `toArrayLike(Buffer, "be", 8)` expects.
+// The commenter's `[review_pda, commenter, "commented"]` marker PDA follows
+// pda_config. With `create_ata`, the associated token program comes after it and a
+// missing user ATA is created, paid for by the commenter
+pub fn add_comment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    comment: String,
+    create_ata: bool
+) -> ProgramResult {
+    verbose_msg!("Adding comment...");
+    verbose_msg!("Comment: {}", comment);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let commenter = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let pda_comment = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let user_ata = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_marker = next_account_info(account_info_iter)?;
+    let ata_program = if create_ata { Some(next_account_info(account_info_iter)?) } else { None };
+
+    let comment_accounts = CommentAccounts {
+        commenter, pda_review, pda_counter, token_mint, mint_auth, user_ata, system_program, token_program, pda_config,
+        pda_marker, ata_program,
+    };
+    post_comments(program_id, &comment_accounts, &mut std::iter::once(pda_comment), vec![comment])
+}
+
+/// Posts up to `MovieComment::MAX_BATCH` comments for one review in a single
+/// instruction, with one reward mint covering all of them. Accounts are
+/// add_comment's minus the comment PDA, followed by one comment PDA per entry in
+/// order: `[review_pda, counter]`, `[review_pda, counter + 1]`, ...
+pub fn batch_add_comments(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    comments: Vec<String>
+) -> ProgramResult {
+    verbose_msg!("Adding {} comments...", comments.len());
+
+    if comments.len() > MovieComment::MAX_BATCH {
+        msg!("At most {} comments per batch", MovieComment::MAX_BATCH);
+        return Err(ReviewError::BatchTooLarge.into())
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let commenter = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_counter = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let user_ata = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_marker = next_account_info(account_info_iter)?;
+
+    // The comment PDAs take up the tail of the list, so batches can't ask for ATA creation
+    let comment_accounts = CommentAccounts {
+        commenter, pda_review, pda_counter, token_mint, mint_auth, user_ata, system_program, token_program, pda_config,
+        pda_marker, ata_program: None,
+    };
+    post_comments(program_id, &comment_accounts, account_info_iter, comments)
+}
+
+// Everything add_comment and batch_add_comments share besides the comment PDAs
+struct CommentAccounts<'a, 'b> {
+    commenter: &'b AccountInfo<'a>,
+    pda_review: &'b AccountInfo<'a>,
+    pda_counter: &'b AccountInfo<'a>,
+    token_mint: &'b AccountInfo<'a>,
+    mint_auth: &'b AccountInfo<'a>,
+    user_ata: &'b AccountInfo<'a>,
+    system_program: &'b AccountInfo<'a>,
+    token_program: &'b AccountInfo<'a>,
+    pda_config: &'b AccountInfo<'a>,
+    pda_marker: &'b AccountInfo<'a>,
+    // Only set when the client asked for the user ATA to be created
+    ata_program: Option<&'b AccountInfo<'a>>,
+}
+
+// Creates one comment account per entry at consecutive counter indices, taking the
+// matching PDA from `pda_comments` each time, then mints comment_reward for each.
+// The first index used is set as the return data, as a little-endian u64 (which the
+// runtime trims of trailing zero bytes).
+fn post_comments<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &CommentAccounts<'a, 'b>,
+    pda_comments: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+    comments: Vec<String>
+) -> ProgramResult {
+    let CommentAccounts {
+        commenter, pda_review, pda_counter, token_mint, mint_auth, user_ata, system_program, token_program, pda_config,
+        pda_marker, ata_program,
+    } = *accounts;
+
+    if !commenter.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+    assert_not_signer(mint_auth, "Mint authority")?;
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        msg!("Incorrect system program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    // The review is only a seed for the counter and comment PDAs, so nothing else
+    // would stop comments, and their rewards, on a review that was never created
+    if pda_review.owner != program_id {
+        msg!("Review {} does not exist", pda_review.key);
+        return Err(ReviewError::ReviewDoesNotExist.into())
+    }
+
+    check_discriminator(pda_review, &MovieAccountState::DISCRIMINATORS)?;
+    let review_data = try_from_slice_unchecked::<MovieAccountState>(&pda_review.data.borrow()).unwrap();
+    if !review_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(review_data.version, MovieAccountState::VERSION)?;
+
+    // Reviews from before counters were created alongside them have none; reading
+    // an empty account would be garbage, so send the client to EnsureCounter instead
+    if *pda_counter.owner == SYSTEM_PROGRAM_ID || pda_counter.data_len() < MovieCommentCounter::SIZE {
+        msg!("Comment counter {} does not exist or predates unique_commenters; run EnsureCounter first", pda_counter.key);
+        return Err(ReviewError::CounterNotInitialized.into())
+    }
+
+    if pda_counter.owner != program_id {
+        msg!("Comment counter is not owned by this program");
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    let config_data = load_config(program_id, pda_config)?;
+
+    if !config_data.allow_self_comment && *commenter.key == review_data.review {
+        msg!("{} wrote this review and self-comments are disabled", commenter.key);
+        return Err(ReviewError::SelfCommentNotAllowed.into())
+    }
+
+    check_discriminator(pda_counter, &[MovieCommentCounter::DISCRIMINATOR])?;
+    let mut counter_data = try_from_slice_unchecked::<MovieCommentCounter>(&pda_counter.data.borrow()).unwrap();
+
+    if !counter_data.is_initialized() {
+        msg!("Comment counter is not initialized; create it with EnsureCounter first");
+        return Err(ReviewError::CounterNotInitialized.into())
+    }
+
+    let counter_pda = Pubkey::create_program_address(
+        &[pda_review.key.as_ref(), "comment".as_ref(), &[counter_data.bump]],
+        program_id
+    ).map_err(|_| ReviewError::InvalidPDA)?;
+    if counter_pda != *pda_counter.key {
+        msg!("Invalid seeds for counter PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    check_version(counter_data.version, MovieCommentCounter::VERSION)?;
+    let counter_before = counter_data.counter;
+
+    let marker_bump = validate_pda(
+        program_id,
+        &[pda_review.key.as_ref(), commenter.key.as_ref(), b"commented"],
+        pda_marker,
+        "commenter marker PDA",
+        ReviewError::InvalidPDA.into()
+    )?;
+
+    if comments.is_empty() || comments.iter().any(|comment| comment.is_empty()) {
+        msg!("Comment cannot be empty");
+        return Err(ReviewError::EmptyField.into())
+    }
+
+    // Comments are rewarded from their own mint, separate from the reviewer token
+    config_msg!(config_data, "Deriving comment mint authority");
+    check_stored_pda(program_id, b"comment_token_mint", config_data.comment_mint_bump, token_mint, "token mint", ReviewError::IncorrectAccountError.into())?;
+    let mint_auth_bump = check_stored_pda(program_id, b"comment_token_auth", config_data.comment_mint_auth_bump, mint_auth, "mint authority", ReviewError::InvalidPDA.into())?;
+
+    if *user_ata.key != get_associated_token_address(commenter.key, token_mint.key) {
+        msg!("Incorrect token mint");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if *token_program.key != TOKEN_PROGRAM_ID {
+        msg!("Incorrect token program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    // Only once every account and argument is validated, so no unchecked program
+    // reaches the CPI
+    if let Some(ata_program) = ata_program {
+        create_ata_if_missing(commenter, user_ata, token_mint, system_program, token_program, ata_program)?;
+    }
+    check_ata_exists(user_ata)?;
+
+    // Read via syscall, as in add_movie_review
+    let rent = Rent::get()?;
+    let mut reward_amount = 0;
+    let mut posted = Vec::with_capacity(comments.len());
+
+    for comment in comments {
+        let pda_comment = pda_comments.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+        if counter_data.counter >= config_data.max_comments_per_review {
+            msg!("Review has reached the limit of {} comments", config_data.max_comments_per_review);
+            return Err(ReviewError::CommentLimitReached.into())
+        }
+
+        let account_len = MovieComment::get_account_size(&comment);
+        let rent_lamports = rent.minimum_balance(account_len);
+
+        let bump_seed = validate_pda(
+            program_id,
+            &[pda_review.key.as_ref(), counter_data.counter.to_be_bytes().as_ref()],
+            pda_comment,
+            "PDA",
+            ReviewError::InvalidPDA.into()
+        )?;
+
+        // Anything already at the derived address was squatted or never cleaned up,
+        // and create_account would fail on it with an opaque system program error
+        if pda_comment.lamports() > 0 || !pda_comment.data_is_empty() {
+            msg!("Comment account {} already exists", pda_comment.key);
+            return Err(ReviewError::CommentSlotOccupied.into());
+        }
+
+        invoke_signed(
+            &system_instruction::create_account(
+                commenter.key,
+                pda_comment.key,
+                rent_lamports,
+                account_len as u64,
+                program_id
+            ),
+            &[commenter.clone(), pda_comment.clone(), system_program.clone()],
+            &[&[pda_review.key.as_ref(), counter_data.counter.to_be_bytes().as_ref(), &[bump_seed]]]
+        )?;
+        config_msg!(config_data, "Created comment account");
+
+        let mut comment_data = try_from_slice_unchecked::<MovieComment>(&pda_comment.data.borrow()).unwrap();
+
+        config_msg!(config_data, "Checking if comment account is already initialized");
+        if comment_data.is_initialized {
+            msg!("Account already initialized");
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        comment_data.discriminator = MovieComment::DISCRIMINATOR.to_string();
+        comment_data.version = MovieComment::VERSION;
+        comment_data.review = *pda_review.key;
+        comment_data.commenter = *commenter.key;
+        comment_data.comment = comment;
+        comment_data.count = counter_data.counter;
+        comment_data.is_initialized = true;
+        comment_data.bump = bump_seed;
+
+        config_msg!(config_data, "Comment count: {}", counter_data.counter);
+        reward_amount = add_u64(reward_amount, config_data.token_amount(comment_reward(counter_data.counter))?)?;
+        counter_data.counter = add_u64(counter_data.counter, 1)?;
+        posted.push((pda_comment, comment_data));
+    }
+
+    if record_commenter(program_id, commenter, pda_review, pda_marker, system_program, marker_bump)? {
+        counter_data.unique_commenters = add_u64(counter_data.unique_commenters, 1)?;
+    }
+    config_msg!(config_data, "Unique commenters: {}", counter_data.unique_commenters);
+    serialize_into(&counter_data, pda_counter)?;
+
+
+    config_msg!(config_data, "Minting {} to User associated token account", reward_amount);
+    mint_reward(pda_config, token_program, token_mint, user_ata, mint_auth, &[b"comment_token_auth", &[mint_auth_bump]], reward_amount)?;
+
+    // Only creation pays out; update_comment never mints
+    for (pda_comment, mut comment_data) in posted {
+        comment_data.rewarded = true;
+        check_fits(comment_data.serialized_len(), pda_comment)?;
+        serialize_into(&comment_data, pda_comment)?;
+    }
+
+    // Set last: the mint CPI above clears any return data set before it
+    set_return_data(&counter_before.to_le_bytes());
+
+    Ok(())
+}
+
+// Creates the `seeds` PDA with `space` bytes, owned by the program and paid for by
+// `payer`. create_account refuses an address that already holds lamports, and
+// anyone can send some to a PDA, so a prefunded one is topped up to rent exemption,
+// then allocated and assigned instead.
+fn create_pda_account<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda: &AccountInfo<'a>,
+    space: usize,
+    system_program: &AccountInfo<'a>,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let rent_lamports = Rent::get()?.minimum_balance(space);
+    if pda.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(payer.key, pda.key, rent_lamports, space as u64, program_id),
+            &[payer.clone(), pda.clone(), system_program.clone()],
+            &[seeds]
+        )
+    }
+
+    let shortfall = rent_lamports.saturating_sub(pda.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, pda.key, shortfall),
+            &[payer.clone(), pda.clone(), system_program.clone()]
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(pda.key, space as u64),
+        &[pda.clone(), system_program.clone()],
+        &[seeds]
+    )?;
+    invoke_signed(
+        &system_instruction::assign(pda.key, program_id),
+        &[pda.clone(), system_program.clone()],
+        &[seeds]
+    )
+}
+
+// Creates the commenter's marker PDA for the review unless it already exists.
+// Returns whether it was created, i.e. whether this is their first comment there.
+fn record_commenter<'a>(
+    program_id: &Pubkey,
+    commenter: &AccountInfo<'a>,
+    pda_review: &AccountInfo<'a>,
+    pda_marker: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    marker_bump: u8,
+) -> Result<bool, ProgramError> {
+    if pda_marker.owner == program_id {
+        return Ok(false)
+    }
+
+    verbose_msg!("Creating commenter marker");
+    invoke_signed(
+        &system_instruction::create_account(
+            commenter.key,
+            pda_marker.key,
+            Rent::get()?.minimum_balance(CommenterMarker::SIZE),
+            CommenterMarker::SIZE as u64,
+            program_id,
+        ),
+        &[commenter.clone(), pda_marker.clone(), system_program.clone()],
+        &[&[pda_review.key.as_ref(), commenter.key.as_ref(), b"commented", &[marker_bump]]],
+    )?;
+
+    let marker_data = CommenterMarker {
+        discriminator: CommenterMarker::DISCRIMINATOR.to_string(),
+        version: CommenterMarker::VERSION,
+        is_initialized: true,
+        bump: marker_bump,
+    };
+    serialize_into(&marker_data, pda_marker)?;
+
+    Ok(true)
+}
+
+// Reward in whole tokens for the comment at position `counter` on its review. It
+// halves every COMMENT_HALVING_INTERVAL comments, down to a floor of one token.
+fn comment_reward(counter: u64) -> u64 {
+    let halvings = counter / COMMENT_HALVING_INTERVAL;
+    let tokens = u32::try_from(halvings).ok()
+        .and_then(|halvings| COMMENT_REWARD.checked_shr(halvings))
+        .unwrap_or(0);
+    tokens.max(1)
+}
+
+/// Replaces the text of a comment, resizing its account to fit. Only the original
+/// commenter may edit, and edits are never rewarded: the comment was marked
+/// `rewarded` when `add_comment` minted for it, so no token accounts are taken here.
+pub fn update_comment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    comment: String
+) -> ProgramResult {
+    verbose_msg!("Updating comment...");
+    verbose_msg!("Comment: {}", comment);
+
+    let account_info_iter = &mut accounts.iter();
+
+    let commenter = next_account_info(account_info_iter)?;
+    let pda_comment = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if pda_comment.owner != program_id {
+      return Err(ProgramError::IllegalOwner)
+    }
+
+    if !commenter.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    check_discriminator(pda_comment, &[MovieComment::DISCRIMINATOR])?;
+    let mut comment_data = try_from_slice_unchecked::<MovieComment>(&pda_comment.data.borrow()).unwrap();
+
+    if !comment_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(comment_data.version, MovieComment::VERSION)?;
+
+    let pda = Pubkey::create_program_address(
+        &[comment_data.review.as_ref(), comment_data.count.to_be_bytes().as_ref(), &[comment_data.bump]],
+        program_id
+    ).map_err(|_| ReviewError::InvalidPDA)?;
+    if pda != *pda_comment.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if comment_data.commenter != *commenter.key {
+        msg!("Only the commenter can edit a comment");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if comment.is_empty() {
+        msg!("Comment cannot be empty");
+        return Err(ReviewError::EmptyField.into())
+    }
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        msg!("Incorrect system program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    // Keep the account exactly rent exempt at its new size: the commenter tops up
+    // for longer text and gets the difference back for shorter text
+    let account_len = MovieComment::get_account_size(&comment);
+    let rent_lamports = Rent::get()?.minimum_balance(account_len);
+    let current_lamports = pda_comment.lamports();
+
+    if rent_lamports > current_lamports {
+        invoke(
+            &system_instruction::transfer(commenter.key, pda_comment.key, sub_u64(rent_lamports, current_lamports)?),
+            &[commenter.clone(), pda_comment.clone(), system_program.clone()]
+        )?;
+    }
+
+    pda_comment.realloc(account_len, false)?;
+
+    if current_lamports > rent_lamports {
+        let excess = sub_u64(current_lamports, rent_lamports)?;
+        **pda_comment.try_borrow_mut_lamports()? = rent_lamports;
+        **commenter.try_borrow_mut_lamports()? = add_u64(commenter.lamports(), excess)?;
+    }
+
+    comment_data.comment = comment;
+    check_fits(comment_data.serialized_len(), pda_comment)?;
+    serialize_into(&comment_data, pda_comment)?;
+    verbose_msg!("Comment updated");
+
+    Ok(())
+}
+
+// Bumps the author's review count, creating their `[author, b"profile"]` PDA on
+// their first review, and fails once the count would pass `review_limit`. Returns
+// whether this review earns `reward_amount`, which is only added to the total
+// outside `AuthorProfile::REWARD_COOLDOWN`.
+fn record_review_in_profile<'a>(
+    program_id: &Pubkey,
+    author: &AccountInfo<'a>,
+    pda_profile: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    profile_bump: u8,
+    reward_amount: u64,
+    review_limit: u64,
+) -> Result<bool, ProgramError> {
+    if pda_profile.owner != program_id {
+        verbose_msg!("Creating author profile");
+        invoke_signed(
+            &system_instruction::create_account(
+            author.key,
+            pda_profile.key,
+            Rent::get()?.minimum_balance(AuthorProfile::SIZE),
+            AuthorProfile::SIZE as u64,
+            program_id,
+            ),
+            &[author.clone(), pda_profile.clone(), system_program.clone()],
+            &[&[author.key.as_ref(), b"profile", &[profile_bump]]],
+        )?;
+    } else {
+        check_discriminator(pda_profile, &[AuthorProfile::DISCRIMINATOR])?;
+    }
+
+    let mut profile_data = try_from_slice_unchecked::<AuthorProfile>(&pda_profile.data.borrow()).unwrap();
+
+    if !profile_data.is_initialized() {
+        profile_data.discriminator = AuthorProfile::DISCRIMINATOR.to_string();
+        profile_data.version = AuthorProfile::VERSION;
+        profile_data.is_initialized = true;
+        profile_data.author = *author.key;
+        profile_data.bump = profile_bump;
+    }
+
+    check_version(profile_data.version, AuthorProfile::VERSION)?;
+
+    if profile_data.review_count >= review_limit {
+        msg!("Author {} has reached the limit of {} reviews", author.key, review_limit);
+        return Err(ReviewError::ReviewLimitReached.into())
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    // A zero reward, as for a watchlist entry, counts the review without starting
+    // the cooldown. Saturating, so a clock behind last_review_ts reads as still
+    // cooling down
+    let rewarded = reward_amount > 0 && (profile_data.review_count == 0
+        || now.saturating_sub(profile_data.last_review_ts) >= AuthorProfile::REWARD_COOLDOWN);
+
+    profile_data.review_count = add_u64(profile_data.review_count, 1)?;
+    if rewarded {
+        profile_data.total_rewards = add_u64(profile_data.total_rewards, reward_amount)?;
+        profile_data.pending_rewards = add_u64(profile_data.pending_rewards, reward_amount)?;
+        profile_data.last_review_ts = now;
+    }
+    verbose_msg!("Author review count: {}", profile_data.review_count);
+    serialize_into(&profile_data, pda_profile)?;
+
+    Ok(rewarded)
+}
+
+// Adds `rating` to the movie's aggregate, creating the aggregate PDA on the
+// movie's first review by any author, and makes `review` the head of the movie's
+// review list. Returns whether this was that first review, and the previous head
+// for the new review's next_review.
+#[allow(clippy::too_many_arguments)]
+fn record_review_in_aggregate<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda_aggregate: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    title_hash: &[u8; 32],
+    aggregate_bump: u8,
+    review: &Pubkey,
+    rating: u8,
+) -> Result<(bool, Option<Pubkey>), ProgramError> {
+    if pda_aggregate.owner != program_id {
+        verbose_msg!("Creating movie aggregate");
+        invoke_signed(
+            &system_instruction::create_account(
+            payer.key,
+            pda_aggregate.key,
+            Rent::get()?.minimum_balance(MovieAggregate::SIZE),
+            MovieAggregate::SIZE as u64,
+            program_id,
+            ),
+            &[payer.clone(), pda_aggregate.clone(), system_program.clone()],
+            &[&[title_hash.as_ref(), b"aggregate", &[aggregate_bump]]],
+        )?;
+    } else {
+        check_discriminator(pda_aggregate, &[MovieAggregate::DISCRIMINATOR])?;
+        if pda_aggregate.data_len() < MovieAggregate::SIZE {
+            // Grows an aggregate from before rating_counts or head_review; reviews it
+            // already counted are missing from the histogram and the review list
+            verbose_msg!("Resizing movie aggregate");
+            let rent_lamports = Rent::get()?.minimum_balance(MovieAggregate::SIZE);
+            let current_lamports = pda_aggregate.lamports();
+            if rent_lamports > current_lamports {
+                invoke(
+                    &system_instruction::transfer(payer.key, pda_aggregate.key, sub_u64(rent_lamports, current_lamports)?),
+                    &[payer.clone(), pda_aggregate.clone(), system_program.clone()]
+                )?;
+            }
+            pda_aggregate.realloc(MovieAggregate::SIZE, true)?;
+        }
+    }
+
+    let mut aggregate_data = try_from_slice_unchecked::<MovieAggregate>(&pda_aggregate.data.borrow()).unwrap();
+
+    if !aggregate_data.is_initialized() {
+        aggregate_data.discriminator = MovieAggregate::DISCRIMINATOR.to_string();
+        aggregate_data.version = MovieAggregate::VERSION;
+        aggregate_data.is_initialized = true;
+        aggregate_data.title_hash = *title_hash;
+        aggregate_data.bump = aggregate_bump;
+    }
+
+    check_version(aggregate_data.version, MovieAggregate::VERSION)?;
+
+    let first_review = aggregate_data.review_count == 0;
+    aggregate_data.review_count = add_u64(aggregate_data.review_count, 1)?;
+    aggregate_data.rating_sum = add_u64(aggregate_data.rating_sum, rating as u64)?;
+    if let Some(bucket) = MovieAggregate::rating_bucket(rating) {
+        aggregate_data.rating_counts[bucket] = add_u64(aggregate_data.rating_counts[bucket], 1)?;
+    }
+    let next_review = aggregate_data.head_review.replace(*review);
+    verbose_msg!("Movie review count: {}", aggregate_data.review_count);
+    serialize_into(&aggregate_data, pda_aggregate)?;
+
+    Ok((first_review, next_review))
+}
+
+// Counts a review in its title's index bucket, creating the bucket PDA on the
+// first review filed under that letter
+fn record_review_in_index<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda_index: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    index_key: u8,
+    index_bump: u8,
+) -> ProgramResult {
+    if pda_index.owner != program_id {
+        verbose_msg!("Creating index bucket");
+        invoke_signed(
+            &system_instruction::create_account(
+            payer.key,
+            pda_index.key,
+            Rent::get()?.minimum_balance(IndexBucket::SIZE),
+            IndexBucket::SIZE as u64,
+            program_id,
+            ),
+            &[payer.clone(), pda_index.clone(), system_program.clone()],
+            &[&[b"index", &[index_key], &[index_bump]]],
+        )?;
+    } else {
+        check_discriminator(pda_index, &[IndexBucket::DISCRIMINATOR])?;
+    }
+
+    let mut index_data = try_from_slice_unchecked::<IndexBucket>(&pda_index.data.borrow()).unwrap();
+
+    if !index_data.is_initialized() {
+        index_data.discriminator = IndexBucket::DISCRIMINATOR.to_string();
+        index_data.version = IndexBucket::VERSION;
+        index_data.is_initialized = true;
+        index_data.key = index_key;
+        index_data.bump = index_bump;
+    }
+
+    check_version(index_data.version, IndexBucket::VERSION)?;
+
+    index_data.review_count = add_u64(index_data.review_count, 1)?;
+    verbose_msg!("Index bucket review count: {}", index_data.review_count);
+    serialize_into(&index_data, pda_index)?;
+
+    Ok(())
+}
+
+// Creates the `[b"global_seq"]` PDA with nothing assigned yet, if it doesn't exist
+fn create_global_seq_if_missing<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda_global_seq: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    global_seq_bump: u8,
+) -> ProgramResult {
+    if pda_global_seq.owner == program_id {
+        return check_discriminator(pda_global_seq, &[GlobalSequence::DISCRIMINATOR])
+    }
+
+    verbose_msg!("Creating global sequence");
+    invoke_signed(
+        &system_instruction::create_account(
+        payer.key,
+        pda_global_seq.key,
+        Rent::get()?.minimum_balance(GlobalSequence::SIZE),
+        GlobalSequence::SIZE as u64,
+        program_id,
+        ),
+        &[payer.clone(), pda_global_seq.clone(), system_program.clone()],
+        &[&[b"global_seq", &[global_seq_bump]]],
+    )?;
+
+    let sequence_data = GlobalSequence {
+        discriminator: GlobalSequence::DISCRIMINATOR.to_string(),
+        version: GlobalSequence::VERSION,
+        is_initialized: true,
+        last_seq: 0,
+        bump: global_seq_bump,
+    };
+    serialize_into(&sequence_data, pda_global_seq)
+}
+
+// Assigns the next global_seq. Deployments set up before InitializeProgram created
+// the sequence PDA get it on their next review, paid for by its author.
+fn next_global_seq<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda_global_seq: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    global_seq_bump: u8,
+) -> Result<u64, ProgramError> {
+    create_global_seq_if_missing(program_id, payer, pda_global_seq, system_program, global_seq_bump)?;
+
+    let mut sequence_data = try_from_slice_unchecked::<GlobalSequence>(&pda_global_seq.data.borrow()).unwrap();
+    check_version(sequence_data.version, GlobalSequence::VERSION)?;
+
+    sequence_data.last_seq = add_u64(sequence_data.last_seq, 1)?;
+    serialize_into(&sequence_data, pda_global_seq)?;
+
+    Ok(sequence_data.last_seq)
+}
+
+// Moves a review from `old_rating` to `new_rating` in the movie's aggregate. An
+// aggregate that doesn't exist yet, or predates rating_counts or head_review, is
+// left alone; the next review of the movie creates or resizes it.
+fn move_rating_in_aggregate(
+    program_id: &Pubkey,
+    pda_aggregate: &AccountInfo,
+    title: &str,
+    old_rating: u8,
+    new_rating: u8,
+) -> ProgramResult {
+    let title_hash = MovieAggregate::title_hash(title);
+    let (aggregate_pda, _aggregate_bump) = Pubkey::find_program_address(&[title_hash.as_ref(), b"aggregate"], program_id);
+    if aggregate_pda != *pda_aggregate.key {
+        msg!("Invalid seeds for aggregate PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if pda_aggregate.owner != program_id || pda_aggregate.data_len() < MovieAggregate::SIZE {
+        verbose_msg!("No movie aggregate to update");
+        return Ok(())
+    }
+
+    check_discriminator(pda_aggregate, &[MovieAggregate::DISCRIMINATOR])?;
+    let mut aggregate_data = try_from_slice_unchecked::<MovieAggregate>(&pda_aggregate.data.borrow()).unwrap();
+    check_version(aggregate_data.version, MovieAggregate::VERSION)?;
+
+    // Saturating here and for rating_counts, since a review added before the
+    // aggregate existed was never counted
+    aggregate_data.rating_sum = add_u64(aggregate_data.rating_sum.saturating_sub(old_rating as u64), new_rating as u64)?;
+    if let Some(bucket) = MovieAggregate::rating_bucket(old_rating) {
+        aggregate_data.rating_counts[bucket] = aggregate_data.rating_counts[bucket].saturating_sub(1);
+    }
+    if let Some(bucket) = MovieAggregate::rating_bucket(new_rating) {
+        aggregate_data.rating_counts[bucket] = add_u64(aggregate_data.rating_counts[bucket], 1)?;
+    }
+    serialize_into(&aggregate_data, pda_aggregate)?;
+
+    Ok(())
+}
+
+// Creates and initializes the `[review, "comment"]` counter PDA at zero
+fn create_comment_counter<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda_counter: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    review: &Pubkey,
+    counter_bump: u8,
+) -> ProgramResult {
+    verbose_msg!("Creating comment counter");
+    let counter_rent_lamports = Rent::get()?.minimum_balance(MovieCommentCounter::SIZE);
+
+    // Creating the comment counter account 
+    invoke_signed(
+        &system_instruction::create_account(
+        payer.key, // rent payer
+        pda_counter.key, // address who we're creating the account for
+        counter_rent_lamports, // amount of rent to put into account
+        MovieCommentCounter::SIZE as u64, // size of account
+        program_id,
+        ),
+        // List of accounts that will be read from/written to
+        &[payer.clone(), pda_counter.clone(), system_program.clone()],
+        // Seeds for the PDA
+        &[&[review.as_ref(), "comment".as_ref(), &[counter_bump]]],
+    )?;
+    verbose_msg!("Comment counter created");
+
+    // Deserialize the newly created counter account
+    let mut counter_data = try_from_slice_unchecked::<MovieCommentCounter>(&pda_counter.data.borrow()).unwrap();
+
+    verbose_msg!("Checking if counter account is already initialized");
+    if counter_data.is_initialized() {
+        msg!("Account already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    counter_data.discriminator = MovieCommentCounter::DISCRIMINATOR.to_string();
+    counter_data.version = MovieCommentCounter::VERSION;
+    counter_data.counter = 0;
+    counter_data.is_initialized = true;
+    counter_data.bump = counter_bump;
+    verbose_msg!("Comment count: {}", counter_data.counter);
+    serialize_into(&counter_data, pda_counter)?;
+    verbose_msg!("Comment counter initialized");
+
+    Ok(())
+}
+
+// Ratings are 1 through the configured max_rating
+fn check_rating(rating: u8, config_data: &ProgramConfig) -> Result<Rating, ProgramError> {
+    Rating::new(rating, config_data.max_rating).map_err(|e| {
+        msg!("Rating must be between {} and {}", Rating::MIN, config_data.max_rating);
+        e.into()
+    })
+}
+
+fn check_status(status: u8) -> ProgramResult {
+    if status > MovieAccountState::STATUS_WATCHING {
+        msg!("Unknown watch status {}", status);
+        return Err(ReviewError::InvalidStatus.into())
+    }
+    Ok(())
+}
+
+// Derives the PDA for `seeds`, checks that `account` is it and returns the bump.
+// `name` completes the log line, and `error` is what the caller returns for a
+// mismatch on that account.
+fn validate_pda(
+    program_id: &Pubkey,
+    seeds: &[&[u8]],
+    account: &AccountInfo,
+    name: &str,
+    error: ProgramError,
+) -> Result<u8, ProgramError> {
+    let (pda, bump_seed) = Pubkey::find_program_address(seeds, program_id);
+    if pda != *account.key {
+        msg!("Invalid seeds for {}", name);
+        return Err(error)
+    }
+    Ok(bump_seed)
+}
+
+// validate_pda for a single-seed PDA whose bump the config recorded at creation,
+// re-deriving it with create_program_address instead of searching for the bump
+fn check_stored_pda(
+    program_id: &Pubkey,
+    seed: &[u8],
+    bump: u8,
+    account: &AccountInfo,
+    name: &str,
+    error: ProgramError,
+) -> Result<u8, ProgramError> {
+    let pda = Pubkey::create_program_address(&[seed, &[bump]], program_id).map_err(|_| error.clone())?;
+    if pda != *account.key {
+        msg!("Invalid seeds for {}", name);
+        return Err(error)
+    }
+    Ok(bump)
+}
+
+// For any string used as a PDA seed. Past MAX_SEED_LEN bytes the address derivation
+// itself fails, with an error that doesn't say which input was at fault.
+fn assert_valid_seed(s: &str) -> Result<(), ProgramError> {
+    if s.len() > MAX_SEED_LEN {
+        msg!("Seed is {} bytes, at most {} allowed", s.len(), MAX_SEED_LEN);
+        return Err(ReviewError::SeedTooLong.into())
+    }
+    Ok(())
+}
+
+// Like MAX_DESCRIPTION_LEN, the configured minimum counts UTF-8 bytes
+fn check_min_description_len(description: &str, config_data: &ProgramConfig) -> ProgramResult {
+    if (description.len() as u64) < config_data.min_description_len {
+        msg!("Description must be at least {} bytes", config_data.min_description_len);
+        return Err(ReviewError::DescriptionTooShort.into())
+    }
+    Ok(())
+}
+
+// For PDAs the program signs for itself through invoke_signed. A client can't sign
+// for them, so a signer flag there points at a client bug worth surfacing early.
+fn assert_not_signer(account: &AccountInfo, name: &str) -> ProgramResult {
+    if account.is_signer {
+        msg!("{} {} must not be a signer", name, account.key);
+        return Err(ReviewError::UnexpectedSigner.into())
+    }
+    Ok(())
+}
+
+// Guards against allocation mistakes: the populated struct must fit the account
+// it is about to be written to
+fn check_fits(serialized_len: usize, account: &AccountInfo) -> ProgramResult {
+    if serialized_len > account.data_len() {
+        msg!("Serialized size {} exceeds account size {}", serialized_len, account.data_len());
+        return Err(ReviewError::InvalidDataLength.into())
+    }
+    Ok(())
+}
+
+// Creates `payer`'s ATA for `token_mint` through the associated token program,
+// unless it already exists
+fn create_ata_if_missing<'a>(
+    payer: &AccountInfo<'a>,
+    user_ata: &AccountInfo<'a>,
+    token_mint: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    ata_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    if *ata_program.key != spl_associated_token_account::ID {
+        msg!("Incorrect associated token program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if *user_ata.owner == TOKEN_PROGRAM_ID && !user_ata.data_is_empty() {
+        return Ok(())
+    }
+
+    verbose_msg!("Creating associated token account {}", user_ata.key);
+    invoke(
+        &create_associated_token_account(payer.key, payer.key, token_mint.key),
+        &[
+            payer.clone(),
+            user_ata.clone(),
+            token_mint.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            ata_program.clone(),
+        ]
+    )
+}
+
+// The treasury is data-less, so the caller validates its seeds and passes the bump
+fn create_treasury_if_missing<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda_treasury: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    treasury_bump: u8,
+) -> ProgramResult {
+    if pda_treasury.owner != program_id {
+        verbose_msg!("Creating treasury");
+        invoke_signed(
+            &system_instruction::create_account(
+            payer.key,
+            pda_treasury.key,
+            Rent::get()?.minimum_balance(0),
+            0,
+            program_id,
+            ),
+            &[payer.clone(), pda_treasury.clone(), system_program.clone()],
+            &[&[b"treasury", &[treasury_bump]]],
+        )?;
+    }
+    Ok(())
+}
+
+// Unless created above, reward ATAs come from the client; catch a missing one here rather than
+// letting mint_to_checked fail with an opaque token program error
+fn check_ata_exists(user_ata: &AccountInfo) -> ProgramResult {
+    if *user_ata.owner != TOKEN_PROGRAM_ID || user_ata.data_is_empty() {
+        msg!("Token account {} does not exist; create the associated token account before this instruction", user_ata.key);
+        return Err(ReviewError::AssociatedTokenAccountMissing.into())
+    }
+    Ok(())
+}
+
+// A referrer is paid into any token account of the review mint, as long as it
+// isn't the author's own
+fn check_referrer_ata(referrer_ata: &AccountInfo, token_mint: &AccountInfo, author: &AccountInfo) -> ProgramResult {
+    let referrer = (*referrer_ata.owner == TOKEN_PROGRAM_ID)
+        .then(|| TokenAccount::unpack(&referrer_ata.data.borrow()).ok())
+        .flatten();
+    match referrer {
+        Some(referrer) if referrer.mint == *token_mint.key && referrer.owner != *author.key => Ok(()),
+        _ => {
+            msg!("Referrer {} is not another user's token account for the review mint", referrer_ata.key);
+            Err(ReviewError::IncorrectAccountError.into())
+        }
+    }
+}
+
+// Borsh reports a too-small buffer as a bare io error; name the account so a
+// forgotten realloc is diagnosable from the logs
+fn serialize_into<T: BorshSerialize>(data: &T, account: &AccountInfo) -> ProgramResult {
+    data.serialize(&mut &mut account.data.borrow_mut()[..]).map_err(|e| {
+        msg!("Failed to serialize account {}: {}", account.key, e);
+        ReviewError::SerializationFailed.into()
+    })
+}
+
+// Closes `account` by moving all of its lamports to `recipient` and zeroing its data,
+// so nothing readable is left behind if the account is funded again in the same
+// transaction. Callers must have checked that the program owns `account` and that
+// the signer may close it; the runtime garbage-collects it once the transaction ends.
+pub fn close_account(account: &AccountInfo, recipient: &AccountInfo) -> ProgramResult {
+    if account.key == recipient.key {
+        return Err(ReviewError::IncorrectAccountError.into())
+    }
+
+    let lamports = account.lamports();
+    **recipient.try_borrow_mut_lamports()? = add_u64(recipient.lamports(), lamports)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.try_borrow_mut_data()?.fill(0);
+    Ok(())
+}
+
+// Every state account starts with its Borsh-encoded discriminator. Reading just that
+// prefix first rejects an account of another type before it is misread as this one,
+// or fails to deserialize at all. Zeroed data reads as an empty discriminator and is
+// let through, so the caller's is_initialized check reports it as uninitialized.
+fn check_discriminator(account: &AccountInfo, expected: &[&str]) -> ProgramResult {
+    let discriminator = String::deserialize(&mut &account.data.borrow()[..]).ok();
+    match discriminator.as_deref() {
+        Some(found) if found.is_empty() || expected.contains(&found) => Ok(()),
+        _ => {
+            msg!("Account {} has discriminator {:?}, expected one of {:?}", account.key, discriminator, expected);
+            Err(ReviewError::WrongDiscriminator.into())
+        }
+    }
+}
+
+// State accounts carry a layout version right after the discriminator; reject
+// any this build doesn't know how to read
+fn check_version(version: u8, supported: u8) -> ProgramResult {
+    if version != supported {
+        msg!("Unsupported account version {}", version);
+        return Err(ReviewError::UnsupportedVersion.into())
+    }
+    Ok(())
+}
+
+// Mints `amount` reward tokens to `user_ata`, signing as the mint authority PDA.
+// Both reward mints count towards one `total_minted`, capped at
+// `ProgramConfig::max_supply`. The mint must carry the config's decimals, which
+// the token program checks. Callers must have validated `pda_config` with
+// `load_config` already.
+fn mint_reward<'a>(
+    pda_config: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    token_mint: &AccountInfo<'a>,
+    user_ata: &AccountInfo<'a>,
+    mint_auth: &AccountInfo<'a>,
+    mint_auth_seeds: &[&[u8]],
+    amount: u64,
+) -> ProgramResult {
+    let mut config_data = try_from_slice_unchecked::<ProgramConfig>(&pda_config.data.borrow())?;
+
+    let max_supply = config_data.max_supply()?;
+    let total_minted = config_data.total_minted.checked_add(amount)
+        .filter(|total| *total <= max_supply)
+        .ok_or_else(|| {
+            msg!("Minting {} would exceed the supply cap of {}", amount, max_supply);
+            ProgramError::from(ReviewError::SupplyCapExceeded)
+        })?;
+
+    invoke_signed(
+        &mint_to_checked(
+            token_program.key,
+            token_mint.key,
+            user_ata.key,
+            mint_auth.key,
+            &[],
+            amount,
+            config_data.decimals
+        )?, // ? unwraps and returns the error if there is one
+        // Account infos
+        &[token_mint.clone(), user_ata.clone(), mint_auth.clone()],
+        // Seeds
+        &[mint_auth_seeds]
+    )?;
+
+    config_data.total_minted = total_minted;
+    serialize_into(&config_data, pda_config)?;
+
+    Ok(())
+}
+
+// Approves the mint authority PDA as delegate over `amount` more tokens in
+// `user_ata`, so review rewards stay burnable by SlashReview. `owner` must have
+// signed the outer transaction.
+fn delegate_reward<'a>(
+    token_program: &AccountInfo<'a>,
+    user_ata: &AccountInfo<'a>,
+    mint_auth: &AccountInfo<'a>,
+    owner: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    let ata_data = TokenAccount::unpack(&user_ata.data.borrow())?;
+    // Approving replaces any existing delegation, so carry over what was already
+    // delegated to the mint authority
+    let delegated = if ata_data.delegate == COption::Some(*mint_auth.key) {
+        ata_data.delegated_amount
+    } else {
+        0
+    };
+
+    invoke(
+        &approve(
+            token_program.key,
+            user_ata.key,
+            mint_auth.key,
+            owner.key,
+            &[],
+            add_u64(delegated, amount)?
+        )?,
+        &[user_ata.clone(), mint_auth.clone(), owner.clone()]
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    slash_amount: u64,
+    max_rating: Option<u8>,
+    tip_fee_bps: Option<u16>,
+    max_reviews_per_author: Option<u64>,
+    report_threshold: Option<u64>,
+    max_comments_per_review: Option<u64>,
+    review_fee: Option<u64>,
+    allow_self_comment: Option<bool>,
+    min_description_len: Option<u64>
+) -> ProgramResult {
+    verbose_msg!("Initializing config...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // The initializer becomes the config admin
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if *admin.key != ADMIN_PUBKEY {
+        msg!("Only {} can initialize the config", ADMIN_PUBKEY);
+        return Err(ReviewError::UnauthorizedInitializer.into())
+    }
+
+    let (config_pda, config_bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *pda_config.key {
+        msg!("Invalid seeds for config PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        msg!("Incorrect system program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    let max_rating = max_rating.unwrap_or(ProgramConfig::DEFAULT_MAX_RATING);
+    if max_rating == 0 {
+        msg!("Max rating must be at least 1");
+        return Err(ReviewError::InvalidRating.into())
+    }
+
+    let tip_fee_bps = tip_fee_bps.unwrap_or(ProgramConfig::DEFAULT_TIP_FEE_BPS);
+    if tip_fee_bps > ProgramConfig::MAX_TIP_FEE_BPS {
+        msg!("Tip fee cannot exceed {} basis points", ProgramConfig::MAX_TIP_FEE_BPS);
+        return Err(ProgramError::InvalidInstructionData)
+    }
+
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(ProgramConfig::SIZE);
+
+    invoke_signed(
+        &system_instruction::create_account(
+        admin.key,
+        pda_config.key,
+        rent_lamports,
+        ProgramConfig::SIZE as u64,
+        program_id,
+        ),
+        &[admin.clone(), pda_config.clone(), system_program.clone()],
+        &[&[b"config", &[config_bump]]],
+    )?;
+
+    let mut config_data = try_from_slice_unchecked::<ProgramConfig>(&pda_config.data.borrow()).unwrap();
+
+    if config_data.is_initialized() {
+        msg!("Account already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    config_data.discriminator = ProgramConfig::DISCRIMINATOR.to_string();
+    config_data.version = ProgramConfig::VERSION;
+    config_data.is_initialized = true;
+    config_data.admin = *admin.key;
+    config_data.slash_amount = slash_amount;
+    config_data.bump = config_bump;
+    config_data.total_minted = 0;
+    config_data.max_rating = max_rating;
+    config_data.tip_fee_bps = tip_fee_bps;
+    config_data.max_reviews_per_author = max_reviews_per_author.unwrap_or(AuthorProfile::MAX_REVIEWS_PER_AUTHOR);
+    config_data.decimals = ProgramConfig::DEFAULT_DECIMALS;
+    config_data.report_threshold = report_threshold.unwrap_or(ProgramConfig::DEFAULT_REPORT_THRESHOLD);
+    config_data.max_comments_per_review = max_comments_per_review.unwrap_or(MovieComment::MAX_COMMENTS_PER_REVIEW);
+    config_data.pending_admin = Pubkey::default();
+    config_data.verbose_logs = true;
+    config_data.review_fee = review_fee.unwrap_or(0);
+    config_data.allow_self_comment = allow_self_comment.unwrap_or(true);
+    config_data.min_description_len = min_description_len.unwrap_or(0);
+    // Set by InitializeMint and InitializeCommentMint
+    config_data.mint_bump = 0;
+    config_data.mint_auth_bump = 0;
+    config_data.comment_mint_bump = 0;
+    config_data.comment_mint_auth_bump = 0;
+
+    config_msg!(config_data, "Config admin: {}", config_data.admin);
+    serialize_into(&config_data, pda_config)?;
+    config_msg!(config_data, "Config initialized");
+
+    Ok(())
+}
+
+/// Deploy-time shortcut for InitializeConfig followed by InitializeMint and
+/// InitializeCommentMint that also creates the `[b"treasury"]` and `[b"global_seq"]`
+/// PDAs, so a deployment never has a mint without a config or reviews without
+/// comments. The signer must be ADMIN_PUBKEY and becomes the config admin; config
+/// options other than `slash_amount` take their defaults. Any failing step fails
+/// the instruction and the runtime discards the accounts created before it.
+/// Accounts: admin (signer), config PDA, token mint PDA, mint auth PDA, treasury
+/// PDA, system program, token program, rent sysvar, global sequence PDA, comment
+/// mint PDA, comment mint auth PDA.
+pub fn initialize_program(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    slash_amount: u64,
+    freezable: bool,
+    decimals: Option<u8>
+) -> ProgramResult {
+    verbose_msg!("Initializing program...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let pda_treasury = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let sysvar_rent = next_account_info(account_info_iter)?;
+    let pda_global_seq = next_account_info(account_info_iter)?;
+    let comment_mint = next_account_info(account_info_iter)?;
+    let comment_mint_auth = next_account_info(account_info_iter)?;
+
+    let (treasury_pda, treasury_bump) = Pubkey::find_program_address(&[b"treasury"], program_id);
+    if treasury_pda != *pda_treasury.key {
+        msg!("Invalid seeds for treasury PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+    let global_seq_bump = validate_pda(program_id, &[b"global_seq"], pda_global_seq, "global sequence PDA", ReviewError::InvalidPDA.into())?;
+
+    // Both handlers validate the rest of the accounts, including the system program
+    initialize_config(
+        program_id,
+        &[admin.clone(), pda_config.clone(), system_program.clone()],
+        slash_amount,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None
+    )?;
+    initialize_token_mint(
+        program_id,
+        &[
+            admin.clone(),
+            token_mint.clone(),
+            mint_auth.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            sysvar_rent.clone(),
+            pda_config.clone(),
+        ],
+        freezable,
+        decimals
+    )?;
+    initialize_comment_token_mint(
+        program_id,
+        &[
+            admin.clone(),
+            comment_mint.clone(),
+            comment_mint_auth.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            sysvar_rent.clone(),
+            pda_config.clone(),
+        ]
+    )?;
+    create_treasury_if_missing(program_id, admin, pda_treasury, system_program, treasury_bump)?;
+    create_global_seq_if_missing(program_id, admin, pda_global_seq, system_program, global_seq_bump)?;
+    verbose_msg!("Program initialized");
+
+    Ok(())
+}
+
+/// First step of rotating the config admin: records `new_admin` as pending, and
+/// the admin stays unchanged until that key signs AcceptAdmin, so a mistyped or
+/// unreachable key can't take the seat. A new proposal replaces a pending one, and
+/// proposing the default pubkey cancels it. Accounts: admin (signer), config PDA.
+pub fn transfer_admin(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_admin: Pubkey
+) -> ProgramResult {
+    verbose_msg!("Proposing new admin...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    let mut config_data = load_config(program_id, pda_config)?;
+    if config_data.admin != *admin.key {
+        msg!("Only the config admin can transfer the admin seat");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    config_data.pending_admin = new_admin;
+    config_msg!(config_data, "Pending admin: {}", new_admin);
+    serialize_into(&config_data, pda_config)
+}
+
+/// Second step of rotating the config admin: the pending admin from TransferAdmin
+/// signs to take over. Accounts: pending admin (signer), config PDA.
+pub fn accept_admin(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo]
+) -> ProgramResult {
+    verbose_msg!("Accepting admin...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let new_admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+
+    if !new_admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    let mut config_data = load_config(program_id, pda_config)?;
+    if config_data.pending_admin == Pubkey::default() {
+        msg!("No admin transfer is pending");
+        return Err(ReviewError::NoPendingAdmin.into());
+    }
+    if config_data.pending_admin != *new_admin.key {
+        msg!("Only the pending admin can accept the admin seat");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    config_data.admin = *new_admin.key;
+    config_data.pending_admin = Pubkey::default();
+    config_msg!(config_data, "Config admin: {}", config_data.admin);
+    serialize_into(&config_data, pda_config)
+}
+
+/// Switches the config-gated progress logs on or off without a redeploy, for
+/// example off on mainnet to save compute and on for a devnet deployment. Logs
+/// before a handler loads the config, and all error logs, are unaffected; the
+/// `minimal-logging` feature still removes the progress logs entirely.
+/// Accounts: admin (signer), config PDA.
+pub fn set_verbose_logs(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    enabled: bool
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    let mut config_data = load_config(program_id, pda_config)?;
+    if config_data.admin != *admin.key {
+        msg!("Only the config admin can change logging");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    config_data.verbose_logs = enabled;
+    msg!("Verbose logs: {}", enabled);
+    serialize_into(&config_data, pda_config)
+}
+
+/// Sets when a review expires, for reviews written as part of a time-limited
+/// campaign. From `expires_at` on, updates to the review are rejected and frontends
+/// treat it as archived; 0 clears the expiry. A time already past archives the review
+/// right away. Accounts: admin (signer), config PDA, review PDA.
+pub fn set_review_expiry(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    author: Pubkey,
+    title: String,
+    expires_at: i64
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    let config_data = load_config(program_id, pda_config)?;
+    if config_data.admin != *admin.key {
+        msg!("Only the config admin can set a review expiry");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    assert_valid_seed(&title)?;
+    validate_pda(program_id, &[author.as_ref(), title.as_bytes()], pda_review, "PDA", ReviewError::InvalidPDA.into())?;
+
+    if pda_review.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    check_discriminator(pda_review, &MovieAccountState::DISCRIMINATORS)?;
+    let mut review_data = try_from_slice_unchecked::<MovieAccountState>(&pda_review.data.borrow()).unwrap();
+    if !review_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(review_data.version, MovieAccountState::VERSION)?;
+
+    review_data.expires_at = expires_at;
+    config_msg!(config_data, "Review {} expires at {}", pda_review.key, expires_at);
+    serialize_into(&review_data, pda_review)
+}
+
+/// Mints the review rewards pending in the signer's `[author, b"profile"]` PDA in one
+/// go and zeroes them. Like the direct mints before it, the claimed amount is
+/// delegated to the mint authority so SlashReview can still burn it. Claiming with
+/// nothing pending succeeds without minting. Accounts: author (signer), profile PDA,
+/// token mint PDA, mint auth PDA, author ATA, config PDA, token program.
+pub fn claim_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo]
+) -> ProgramResult {
+    verbose_msg!("Claiming rewards...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let author = next_account_info(account_info_iter)?;
+    let pda_profile = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let user_ata = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    if !author.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+    assert_not_signer(mint_auth, "Mint authority")?;
+
+    validate_pda(program_id, &[author.key.as_ref(), b"profile"], pda_profile, "profile PDA", ReviewError::InvalidPDA.into())?;
+    if pda_profile.owner != program_id {
+        msg!("Author {} has no profile yet", author.key);
+        return Err(ReviewError::UninitializedAccount.into())
+    }
+
+    check_discriminator(pda_profile, &[AuthorProfile::DISCRIMINATOR])?;
+    let mut profile_data = try_from_slice_unchecked::<AuthorProfile>(&pda_profile.data.borrow()).unwrap();
+
+    if !profile_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(profile_data.version, AuthorProfile::VERSION)?;
+
+    let config_data = load_config(program_id, pda_config)?;
+    check_stored_pda(program_id, b"token_mint", config_data.mint_bump, token_mint, "token mint", ReviewError::IncorrectAccountError.into())?;
+    let mint_auth_bump = check_stored_pda(program_id, b"token_auth", config_data.mint_auth_bump, mint_auth, "mint authority", ReviewError::InvalidPDA.into())?;
+
+    if *user_ata.key != get_associated_token_address(author.key, token_mint.key) {
+        msg!("Incorrect token mint");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+    check_ata_exists(user_ata)?;
+
+    if *token_program.key != TOKEN_PROGRAM_ID {
+        msg!("Incorrect token program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    let amount = profile_data.pending_rewards;
+    if amount == 0 {
+        config_msg!(config_data, "No rewards to claim");
+        return Ok(())
+    }
+
+    config_msg!(config_data, "Minting {} pending reward to {}", amount, user_ata.key);
+    mint_reward(pda_config, token_program, token_mint, user_ata, mint_auth, &[b"token_auth", &[mint_auth_bump]], amount)?;
+    delegate_reward(token_program, user_ata, mint_auth, author, amount)?;
+
+    profile_data.pending_rewards = 0;
+    serialize_into(&profile_data, pda_profile)
+}
+
+/// Read-only dump of the `[b"config"]` PDA for operators, meant for simulation. Logs
+/// `sol_log_data([admin, total_minted, max_rating, review_reward, comment_reward])`
+/// as little-endian integers, alongside `msg!` lines. Accounts: config PDA.
+pub fn log_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo]
+) -> ProgramResult {
+    verbose_msg!("Reading config...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let pda_config = next_account_info(account_info_iter)?;
+
+    let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *pda_config.key {
+        msg!("Invalid seeds for config PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if pda_config.owner != program_id || pda_config.data_is_empty() {
+        msg!("Config does not exist yet; run InitializeConfig first");
+        return Err(ReviewError::UninitializedAccount.into())
+    }
+
+    let config_data = load_config(program_id, pda_config)?;
+
+    let review_reward = config_data.token_amount(REVIEW_REWARD)?;
+    let comment_reward = config_data.token_amount(COMMENT_REWARD)?;
+
+    msg!("Config admin: {}", config_data.admin);
+    msg!("Total minted: {} of {}", config_data.total_minted, config_data.max_supply()?);
+    msg!("Max rating: {}", config_data.max_rating);
+    msg!("Rewards: {} per review, {} per comment before halving", review_reward, comment_reward);
+    sol_log_data(&[
+        config_data.admin.as_ref(),
+        &config_data.total_minted.to_le_bytes(),
+        &[config_data.max_rating],
+        &review_reward.to_le_bytes(),
+        &comment_reward.to_le_bytes(),
+    ]);
+
+    Ok(())
+}
+
+/// Read-only report of the `[b"treasury"]` PDA for dashboards, meant for simulation.
+/// Logs `sol_log_data([lamports, fees])` as little-endian integers, where `fees` is
+/// the balance above the treasury's rent-exempt floor; both are zero before the
+/// first tip creates it. Accounts: treasury PDA.
+pub fn log_treasury_balance(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo]
+) -> ProgramResult {
+    verbose_msg!("Reading treasury...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let pda_treasury = next_account_info(account_info_iter)?;
+
+    let (treasury_pda, _bump_seed) = Pubkey::find_program_address(&[b"treasury"], program_id);
+    if treasury_pda != *pda_treasury.key {
+        msg!("Invalid seeds for treasury PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    let (lamports, fees) = if pda_treasury.owner == program_id {
+        let lamports = pda_treasury.lamports();
+        // Saturating, since a treasury below the rent-exempt minimum has no fees to sweep
+        (lamports, lamports.saturating_sub(Rent::get()?.minimum_balance(0)))
+    } else {
+        msg!("Treasury does not exist yet; the first tip creates it");
+        (0, 0)
+    };
+
+    msg!("Treasury balance: {} lamports, {} from fees", lamports, fees);
+    sol_log_data(&[&lamports.to_le_bytes(), &fees.to_le_bytes()]);
+
+    Ok(())
+}
+
+/// Reports the crate version this build was compiled from, so tooling can confirm
+/// which build is deployed. Logs it with `msg!` and `sol_log_data([version])`, and
+/// sets it as the return data, all as the UTF-8 string. Takes no accounts.
+pub fn get_version() -> ProgramResult {
+    let version = env!("CARGO_PKG_VERSION");
+    msg!("Program version: {}", version);
+    sol_log_data(&[version.as_bytes()]);
+    set_return_data(version.as_bytes());
+
+    Ok(())
+}
+
+// Reads the `[b"config"]` PDA, checking its address, owner and initialization
+fn load_config(program_id: &Pubkey, pda_config: &AccountInfo) -> Result<ProgramConfig, ProgramError> {
+    if pda_config.owner != program_id {
+        msg!("Config account is not owned by this program");
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    check_discriminator(pda_config, &[ProgramConfig::DISCRIMINATOR])?;
+    let config_data = try_from_slice_unchecked::<ProgramConfig>(&pda_config.data.borrow())?;
+
+    if !config_data.is_initialized() {
+        msg!("Config is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(config_data.version, ProgramConfig::VERSION)?;
+
+    let config_pda = Pubkey::create_program_address(&[b"config", &[config_data.bump]], program_id)
+        .map_err(|_| ReviewError::InvalidPDA)?;
+    if config_pda != *pda_config.key {
+        msg!("Invalid seeds for config PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    Ok(config_data)
+}
+
+/// Records one report against a review, at most once per reporter, and hides the
+/// review once its distinct reports reach `config.report_threshold`. Anyone may
+/// report. Accounts: reporter (signer, pays for the marker), review PDA, the
+/// `[review_pda, reporter, "report"]` marker PDA, config PDA, system program.
+pub fn report_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    author: Pubkey,
+    title: String
+) -> ProgramResult {
+    verbose_msg!("Reporting movie review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let reporter = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_marker = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !reporter.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        msg!("Incorrect system program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    let config_data = load_config(program_id, pda_config)?;
+
+    assert_valid_seed(&title)?;
+    let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], program_id);
+    if review_pda != *pda_review.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if pda_review.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    check_discriminator(pda_review, &MovieAccountState::DISCRIMINATORS)?;
+    let mut review_data = try_from_slice_unchecked::<MovieAccountState>(&pda_review.data.borrow()).unwrap();
+    if !review_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(review_data.version, MovieAccountState::VERSION)?;
+
+    let (marker_pda, marker_bump) = Pubkey::find_program_address(
+        &[pda_review.key.as_ref(), reporter.key.as_ref(), b"report"],
+        program_id
+    );
+    if marker_pda != *pda_marker.key {
+        msg!("Invalid seeds for report marker PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if pda_marker.owner == program_id {
+        msg!("{} has already reported this review", reporter.key);
+        return Err(ReviewError::AlreadyReported.into())
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            reporter.key,
+            pda_marker.key,
+            Rent::get()?.minimum_balance(ReportMarker::SIZE),
+            ReportMarker::SIZE as u64,
+            program_id,
+        ),
+        &[reporter.clone(), pda_marker.clone(), system_program.clone()],
+        &[&[pda_review.key.as_ref(), reporter.key.as_ref(), b"report", &[marker_bump]]],
+    )?;
+
+    let marker_data = ReportMarker {
+        discriminator: ReportMarker::DISCRIMINATOR.to_string(),
+        version: ReportMarker::VERSION,
+        is_initialized: true,
+        bump: marker_bump,
+    };
+    serialize_into(&marker_data, pda_marker)?;
+
+    review_data.report_count = add_u64(review_data.report_count, 1)?;
+    config_msg!(config_data, "Report count: {}", review_data.report_count);
+    if !review_data.hidden && config_data.report_threshold > 0 && review_data.report_count >= config_data.report_threshold {
+        msg!("Review hidden after {} reports", review_data.report_count);
+        review_data.hidden = true;
+    }
+
+    check_fits(review_data.serialized_len(), pda_review)?;
+    serialize_into(&review_data, pda_review)?;
+
+    Ok(())
+}
+
+/// Records one upvote for a review, at most once per voter. The author earns a
+/// bonus for the upvotes on their next update. Accounts: voter (signer, pays for
+/// the marker), review PDA, the `[review_pda, voter, "upvote"]` marker PDA, config
+/// PDA, system program.
+pub fn upvote_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    author: Pubkey,
+    title: String
+) -> ProgramResult {
+    verbose_msg!("Upvoting movie review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let voter = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let pda_marker = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !voter.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        msg!("Incorrect system program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    // Otherwise authors could upvote their own reviews into a bonus
+    if *voter.key == author {
+        msg!("Review authors cannot upvote their own reviews");
+        return Err(ReviewError::SelfUpvoteNotAllowed.into())
+    }
+
+    let config_data = load_config(program_id, pda_config)?;
+
+    assert_valid_seed(&title)?;
+    let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], program_id);
+    if review_pda != *pda_review.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if pda_review.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    check_discriminator(pda_review, &MovieAccountState::DISCRIMINATORS)?;
+    let mut review_data = try_from_slice_unchecked::<MovieAccountState>(&pda_review.data.borrow()).unwrap();
+    if !review_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(review_data.version, MovieAccountState::VERSION)?;
+
+    let (marker_pda, marker_bump) = Pubkey::find_program_address(
+        &[pda_review.key.as_ref(), voter.key.as_ref(), b"upvote"],
+        program_id
+    );
+    if marker_pda != *pda_marker.key {
+        msg!("Invalid seeds for upvote marker PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if pda_marker.owner == program_id {
+        msg!("{} has already upvoted this review", voter.key);
+        return Err(ReviewError::AlreadyUpvoted.into())
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            voter.key,
+            pda_marker.key,
+            Rent::get()?.minimum_balance(UpvoteMarker::SIZE),
+            UpvoteMarker::SIZE as u64,
+            program_id,
+        ),
+        &[voter.clone(), pda_marker.clone(), system_program.clone()],
+        &[&[pda_review.key.as_ref(), voter.key.as_ref(), b"upvote", &[marker_bump]]],
+    )?;
+
+    let marker_data = UpvoteMarker {
+        discriminator: UpvoteMarker::DISCRIMINATOR.to_string(),
+        version: UpvoteMarker::VERSION,
+        is_initialized: true,
+        bump: marker_bump,
+    };
+    serialize_into(&marker_data, pda_marker)?;
+
+    review_data.upvotes = add_u32(review_data.upvotes, 1)?;
+    config_msg!(config_data, "Upvotes: {}", review_data.upvotes);
+
+    check_fits(review_data.serialized_len(), pda_review)?;
+    serialize_into(&review_data, pda_review)?;
+
+    Ok(())
+}
+
+/// Burns `config.slash_amount` reward tokens from the review author's token
+/// account. The burn is signed by the mint authority PDA, which every review
+/// reward delegates to (see `delegate_reward`); an author who has since revoked
+/// or replaced that delegation can't be slashed.
+pub fn slash_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    author: Pubkey,
+    title: String
+) -> ProgramResult {
+    verbose_msg!("Slashing movie review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let pda_review = next_account_info(account_info_iter)?;
+    let author_ata = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    let config_data = load_config(program_id, pda_config)?;
+    if config_data.admin != *admin.key {
+        msg!("Only the config admin can slash reviews");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    assert_valid_seed(&title)?;
+    let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], program_id);
+    if review_pda != *pda_review.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if pda_review.owner != program_id {
+        return Err(ProgramError::IllegalOwner)
+    }
+
+    check_discriminator(pda_review, &MovieAccountState::DISCRIMINATORS)?;
+    let review_data = try_from_slice_unchecked::<MovieAccountState>(&pda_review.data.borrow()).unwrap();
+    if !review_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    check_version(review_data.version, MovieAccountState::VERSION)?;
+
+    let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[b"token_mint"], program_id);
+    let (mint_auth_pda, mint_auth_bump) = Pubkey::find_program_address(&[b"token_auth"], program_id);
+
+    if *token_mint.key != mint_pda {
+        msg!("Incorrect token mint");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if *mint_auth.key != mint_auth_pda {
+        msg!("Mint passed in and mint derived do not match");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    // The reward was minted to the seed author, who keeps it after a transfer
+    if *author_ata.key != get_associated_token_address(&author, token_mint.key) {
+        msg!("Incorrect author token account");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if *token_program.key != TOKEN_PROGRAM_ID {
+        msg!("Incorrect token program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    let balance = TokenAccount::unpack(&author_ata.data.borrow())?.amount;
+    if balance < config_data.slash_amount {
+        msg!("Author holds {} tokens, slash needs {}", balance, config_data.slash_amount);
+        return Err(ReviewError::InsufficientTokensToSlash.into())
+    }
+
+    config_msg!(config_data, "Burning {} tokens from {}", config_data.slash_amount, author_ata.key);
+    invoke_signed(
+        &burn(
+            token_program.key,
+            author_ata.key,
+            token_mint.key,
+            mint_auth.key,
+            &[],
+            config_data.slash_amount
+        )?,
+        &[author_ata.clone(), token_mint.clone(), mint_auth.clone()],
+        &[&[b"token_auth", &[mint_auth_bump]]]
+    )
+}
+
+/// Freezes `owner`'s review reward token account. Only possible on a mint created
+/// with `InitializeMint { freezable: true }`; the token program rejects it otherwise.
+pub fn freeze_reward_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    owner: Pubkey
+) -> ProgramResult {
+    verbose_msg!("Freezing reward account...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let owner_ata = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    let config_data = load_config(program_id, pda_config)?;
+    if config_data.admin != *admin.key {
+        msg!("Only the config admin can freeze accounts");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[b"token_mint"], program_id);
+    let (mint_auth_pda, mint_auth_bump) = Pubkey::find_program_address(&[b"token_auth"], program_id);
+
+    if *token_mint.key != mint_pda {
+        msg!("Incorrect token mint");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if *mint_auth.key != mint_auth_pda {
+        msg!("Mint passed in and mint derived do not match");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if *owner_ata.key != get_associated_token_address(&owner, token_mint.key) {
+        msg!("Incorrect owner token account");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if *token_program.key != TOKEN_PROGRAM_ID {
+        msg!("Incorrect token program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    config_msg!(config_data, "Freezing {}", owner_ata.key);
+    invoke_signed(
+        &freeze_account(
+            token_program.key,
+            owner_ata.key,
+            token_mint.key,
+            mint_auth.key,
+            &[]
+        )?,
+        &[owner_ata.clone(), token_mint.clone(), mint_auth.clone()],
+        &[&[b"token_auth", &[mint_auth_bump]]]
+    )
+}
+
+/// Creates the Metaplex metadata account for the `[b"token_mint"]` reward mint, so
+/// wallets can name the token. The `[b"token_auth"]` PDA signs as mint authority and
+/// becomes the update authority; the admin pays. Accounts: admin, config, token mint,
+/// mint authority, metadata PDA, token metadata program, system program.
+pub fn create_token_metadata(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name: String,
+    symbol: String,
+    uri: String
+) -> ProgramResult {
+    verbose_msg!("Creating token metadata...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?;
+    let pda_config = next_account_info(account_info_iter)?;
+    let token_mint = next_account_info(account_info_iter)?;
+    let mint_auth = next_account_info(account_info_iter)?;
+    let pda_metadata = next_account_info(account_info_iter)?;
+    let metadata_program = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature)
+    }
+
+    let config_data = load_config(program_id, pda_config)?;
+    if config_data.admin != *admin.key {
+        msg!("Only the config admin can create token metadata");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    let (mint_pda, _mint_bump) = Pubkey::find_program_address(&[b"token_mint"], program_id);
+    let (mint_auth_pda, mint_auth_bump) = Pubkey::find_program_address(&[b"token_auth"], program_id);
+
+    if *token_mint.key != mint_pda {
+        msg!("Incorrect token mint");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if *mint_auth.key != mint_auth_pda {
+        msg!("Mint passed in and mint derived do not match");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if *metadata_program.key != TOKEN_METADATA_PROGRAM_ID {
+        msg!("Incorrect token metadata program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    let (metadata_pda, _metadata_bump) = Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint_pda.as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID
+    );
+    if metadata_pda != *pda_metadata.key {
+        msg!("Invalid seeds for metadata PDA");
+        return Err(ReviewError::InvalidPDA.into())
+    }
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        msg!("Incorrect system program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if name.is_empty() || symbol.is_empty() || uri.is_empty() {
+        msg!("Name, symbol and uri cannot be empty");
+        return Err(ReviewError::EmptyField.into())
+    }
+
+    if name.len() > MAX_METADATA_NAME_LEN || symbol.len() > MAX_METADATA_SYMBOL_LEN || uri.len() > MAX_METADATA_URI_LEN {
+        msg!(
+            "Name, symbol and uri are limited to {}, {} and {} bytes",
+            MAX_METADATA_NAME_LEN, MAX_METADATA_SYMBOL_LEN, MAX_METADATA_URI_LEN
+        );
+        return Err(ReviewError::InvalidDataLength.into())
+    }
+
+    // CreateMetadataAccountV3 { data: DataV2, is_mutable, collection_details }
+    let mut data = vec![CREATE_METADATA_ACCOUNT_V3];
+    (name, symbol, uri, 0u16).serialize(&mut data)?;
+    // DataV2 creators, collection and uses are all None
+    data.extend_from_slice(&[0, 0, 0]);
+    // Mutable, with no collection details
+    data.extend_from_slice(&[1, 0]);
+
+    config_msg!(config_data, "Metadata: {}", pda_metadata.key);
+    invoke_signed(
+        &Instruction {
+            program_id: TOKEN_METADATA_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*pda_metadata.key, false),
+                AccountMeta::new_readonly(*token_mint.key, false),
+                AccountMeta::new_readonly(*mint_auth.key, true),
+                AccountMeta::new(*admin.key, true),
+                AccountMeta::new_readonly(*mint_auth.key, true),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            ],
+            data,
+        },
+        &[pda_metadata.clone(), token_mint.clone(), mint_auth.clone(), admin.clone(), system_program.clone()],
+        &[&[b"token_auth", &[mint_auth_bump]]]
+    )?;
+    config_msg!(config_data, "Token metadata created");
+
+    Ok(())
+}
+
+// Also records `decimals` in the config, where reward amounts are scaled by it
+pub fn initialize_token_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    freezable: bool,
+    decimals: Option<u8>
+) -> ProgramResult {
+    let decimals = decimals.unwrap_or(ProgramConfig::DEFAULT_DECIMALS);
+    if decimals > ProgramConfig::MAX_DECIMALS {
+        msg!("Mint decimals must be at most {}", ProgramConfig::MAX_DECIMALS);
+        return Err(ReviewError::InvalidDecimals.into())
+    }
+    initialize_mint_pda(program_id, accounts, b"token_mint", b"token_auth", freezable, Some(decimals))
+}
+
+// Uses the decimals InitializeMint recorded, so run that first
+pub fn initialize_comment_token_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo]
+) -> ProgramResult {
+    initialize_mint_pda(program_id, accounts, b"comment_token_mint", b"comment_token_auth", false, None)
+}
+
+// Creates and initializes the mint PDA derived from `mint_seed`, with the
+// `auth_seed` PDA as its mint authority and, if `freezable`, freeze authority.
+// Only the config admin may do this, so InitializeConfig has to run first.
+// `decimals` is written to the config; None takes the config's.
+fn initialize_mint_pda(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint_seed: &[u8],
+    auth_seed: &[u8],
+    freezable: bool,
+    decimals: Option<u8>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // The order of the following accounts is not arbitrary!
+    // They are sent by the client in this order
+
+    // Sender of transaction
+    let initializer = next_account_info(account_info_iter)?;
+    // Token mint PDA - derived on the clinet
+    let token_mint = next_account_info(account_info_iter)?;
+    // Token mint authority (should be me)
+    let mint_auth = next_account_info(account_info_iter)?;
+    // System program to create a new account
+    let system_program = next_account_info(account_info_iter)?;
+    // Solana Token program address
+    let token_program = next_account_info(account_info_iter)?;
+    // System account to calculate the rent
+    let sysvar_rent = next_account_info(account_info_iter)?;
+    // Config PDA, whose admin is the only allowed initializer
+    let pda_config = next_account_info(account_info_iter)?;
+
+    // Derive the mint PDA and its authority again to validate
+    let mint_bump = validate_pda(program_id, &[mint_seed], token_mint, "token mint", ReviewError::IncorrectAccountError.into())?;
+    let auth_bump = validate_pda(program_id, &[auth_seed], mint_auth, "mint authority", ReviewError::IncorrectAccountError.into())?;
+
+    verbose_msg!("Token mint: {:?}", token_mint.key);
+    verbose_msg!("Mint authority: {:?}", mint_auth.key);
+
+    // Validate the important accounts passed in
+    if *token_program.key != TOKEN_PROGRAM_ID {
+        msg!("Incorrect token program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if *system_program.key != SYSTEM_PROGRAM_ID {
+        msg!("Incorrect system program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    if *sysvar_rent.key != RENT_PROGRAM_ID {
+        msg!("Incorrect rent program");
+        return Err(ReviewError::IncorrectAccountError.into());
+    }
+
+    let mut config_data = load_config(program_id, pda_config)?;
+    if !initializer.is_signer || config_data.admin != *initializer.key {
+        msg!("Only the config admin can initialize a mint");
+        return Err(ReviewError::UnauthorizedInitializer.into());
+    }
+
+    let decimals = match decimals {
+        Some(decimals) => {
+            config_data.decimals = decimals;
+            decimals
+        }
+        None => config_data.decimals,
+    };
+    // Rewarding instructions re-derive the mint PDAs from these
+    if mint_seed == b"token_mint" {
+        config_data.mint_bump = mint_bump;
+        config_data.mint_auth_bump = auth_bump;
+    } else {
+        config_data.comment_mint_bump = mint_bump;
+        config_data.comment_mint_auth_bump = auth_bump;
+    }
+    serialize_into(&config_data, pda_config)?;
+
+    let rent = Rent::get()?;
+    // The size of a mint account is 82! Remember this!
+    let rent_lamports = rent.minimum_balance(82);
+
+    // Create the token mint PDA
+    invoke_signed(
+        &system_instruction::create_account(
+            initializer.key,
+            token_mint.key,
+            rent_lamports,
+            82, // size of token mint account
+            token_program.key
+        ),
+        // Accounts we're reading from or writing to
+        &[
+          initializer.clone(),
+          token_mint.clone(),
+          system_program.clone(),
+        ],
+        // Seeds for our token mint account
+        &[&[mint_seed, &[mint_bump]]]
+    )?;
+
+    config_msg!(config_data, "Created token mint account");
+
+    // Initialize mint account
+    invoke_signed(
+        &initialize_mint(
+            token_program.key,
+            token_mint.key,
+            mint_auth.key,
+            // Freeze authority - unless asked for, we don't want anyone to be able to freeze
+            if freezable { Some(mint_auth.key) } else { None },
+            decimals,
+        )?,
+        // Which accounts we're reading from or writing to
+        &[token_mint.clone(), sysvar_rent.clone(), mint_auth.clone()],
+        // Seeds for our token mint PDA
+        &[&[mint_seed, &[mint_bump]]]
+    )?;
+
+    config_msg!(config_data, "Initialized token mint");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::client,
+        assert_matches::*,
+        solana_program::{
+            instruction::{AccountMeta, Instruction},
+            native_token::LAMPORTS_PER_SOL,
+            program_pack::Pack,
+            system_instruction::SystemError,
+            system_program::ID as SYSTEM_PROGRAM_ID,
+        },
+        solana_program_test::*,
+        solana_sdk::{
+            account::Account,
+            compute_budget::ComputeBudgetInstruction,
+            hash::Hash,
+            instruction::InstructionError,
+            signature::{keypair_from_seed, Keypair, Signer},
+            transaction::{Transaction, TransactionError},
+            sysvar::rent::ID as SYSVAR_RENT_ID,
+        },
+        spl_associated_token_account::{
+            get_associated_token_address,
+            instruction::create_associated_token_account
+        },
+        spl_token::ID as TOKEN_PROGRAM_ID,
+        std::convert::TryInto
+    };
+
+    // Holds ADMIN_PUBKEY in test builds
+    fn test_admin() -> Keypair {
+        keypair_from_seed(b"localsolana test admin keypair!!").unwrap()
+    }
+
+    // ProgramTest::start, but paying with the test admin so the payer may initialize
+    // the config, as the tests assume
+    async fn start_as_admin(mut program_test: ProgramTest) -> (BanksClient, Keypair, Hash) {
+        let admin = test_admin();
+        program_test.add_account(admin.pubkey(), Account::new(1_000_000 * LAMPORTS_PER_SOL, 0, &SYSTEM_PROGRAM_ID));
+        let (banks_client, _payer, recent_blockhash) = program_test.start().await;
+        (banks_client, admin, recent_blockhash)
+    }
+
+    // start_as_admin for tests that need a ProgramTestContext
+    async fn start_with_context_as_admin(mut program_test: ProgramTest) -> ProgramTestContext {
+        let admin = test_admin();
+        program_test.add_account(admin.pubkey(), Account::new(1_000_000 * LAMPORTS_PER_SOL, 0, &SYSTEM_PROGRAM_ID));
+        let mut context = program_test.start_with_context().await;
+        context.payer = admin;
+        context
+    }
+
+    fn create_init_mint_ix(payer: Pubkey, program_id: Pubkey) -> (Pubkey, Pubkey, Instruction) {
+        create_init_mint_pda_ix(payer, program_id, b"token_mint", b"token_auth", MovieInstruction::TAG_INITIALIZE_MINT)
+    }
+
+    fn create_init_comment_mint_ix(payer: Pubkey, program_id: Pubkey) -> (Pubkey, Pubkey, Instruction) {
+        create_init_mint_pda_ix(payer, program_id, b"comment_token_mint", b"comment_token_auth", MovieInstruction::TAG_INITIALIZE_COMMENT_MINT)
+    }
+
+    fn create_init_mint_pda_ix(
+        payer: Pubkey,
+        program_id: Pubkey,
+        mint_seed: &[u8],
+        auth_seed: &[u8],
+        tag: u8,
+    ) -> (Pubkey, Pubkey, Instruction) {
+        // Derive PDA for token mint authority
+        let (mint, _bump_seed) = Pubkey::find_program_address(&[mint_seed], &program_id);
+        let (mint_auth, _bump_seed) = Pubkey::find_program_address(&[auth_seed], &program_id);
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+
+        let init_mint_ix = Instruction {
+            program_id: program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(payer, true),
+                AccountMeta::new(mint, false),
+                AccountMeta::new(mint_auth, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+                AccountMeta::new(config_pda, false),
+            ],
+            data: vec![tag]
+        };
+        (mint, mint_auth, init_mint_ix)
+    }
+
+    fn create_freeze_account_ix(admin: Pubkey, program_id: Pubkey, owner: Pubkey) -> Instruction {
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let (mint, _bump_seed) = Pubkey::find_program_address(&[b"token_mint"], &program_id);
+        let (mint_auth, _bump_seed) = Pubkey::find_program_address(&[b"token_auth"], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_FREEZE_ACCOUNT];
+        data.append(&mut owner.try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(admin, true),
+                AccountMeta::new_readonly(config_pda, false),
+                AccountMeta::new(get_associated_token_address(&owner, &mint), false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new_readonly(mint_auth, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_add_review_ix(
+        payer: Pubkey,
+        program_id: Pubkey,
+        title: &str,
+        rating: u8,
+        description: &str,
+    ) -> (Pubkey, Pubkey, Instruction) {
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[payer.as_ref(), title.as_bytes()], &program_id);
+        let (counter_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), b"comment"], &program_id);
+        let (mint, _bump_seed) = Pubkey::find_program_address(&[b"token_mint"], &program_id);
+        let (mint_auth, _bump_seed) = Pubkey::find_program_address(&[b"token_auth"], &program_id);
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let (profile_pda, _bump_seed) = Pubkey::find_program_address(&[payer.as_ref(), b"profile"], &program_id);
+        let aggregate_pda = get_aggregate_pda(program_id, title);
+        let index_pda = get_index_pda(program_id, title);
+        let (global_seq_pda, _bump_seed) = Pubkey::find_program_address(&[b"global_seq"], &program_id);
+
+        // Tag followed by the Borsh-encoded (title, rating, description) payload
+        let mut data = vec![MovieInstruction::TAG_ADD_REVIEW];
+        data.append(&mut (title, rating, description).try_to_vec().unwrap());
+
+        let add_review_ix = Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(payer, true),
+                AccountMeta::new(review_pda, false),
+                AccountMeta::new(counter_pda, false),
+                AccountMeta::new(mint, false),
+                AccountMeta::new_readonly(mint_auth, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                AccountMeta::new(config_pda, false),
+                AccountMeta::new(profile_pda, false),
+                AccountMeta::new(aggregate_pda, false),
+                AccountMeta::new(index_pda, false),
+                AccountMeta::new(global_seq_pda, false),
+            ],
+            data,
+        };
+        (review_pda, counter_pda, add_review_ix)
+    }
+
+    fn get_aggregate_pda(program_id: Pubkey, title: &str) -> Pubkey {
+        let title_hash = MovieAggregate::title_hash(title);
+        Pubkey::find_program_address(&[title_hash.as_ref(), b"aggregate"], &program_id).0
+    }
+
+    fn get_index_pda(program_id: Pubkey, title: &str) -> Pubkey {
+        Pubkey::find_program_address(&[b"index", &[IndexBucket::key(title)]], &program_id).0
+    }
+
+    fn get_commenter_marker_pda(program_id: Pubkey, review_pda: Pubkey, commenter: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[review_pda.as_ref(), commenter.as_ref(), b"commented"], &program_id).0
+    }
+
+    fn create_tip_reviewer_ix(tipper: Pubkey, program_id: Pubkey, review_pda: Pubkey, author: Pubkey, amount: u64) -> Instruction {
+        let (treasury_pda, _bump_seed) = Pubkey::find_program_address(&[b"treasury"], &program_id);
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_TIP_REVIEWER];
+        data.append(&mut amount.try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(tipper, true),
+                AccountMeta::new_readonly(review_pda, false),
+                AccountMeta::new(author, false),
+                AccountMeta::new(treasury_pda, false),
+                AccountMeta::new_readonly(config_pda, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_update_review_ix(
+        payer: Pubkey,
+        program_id: Pubkey,
+        title: &str,
+        rating: u8,
+        description: &str,
+    ) -> Instruction {
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[payer.as_ref(), title.as_bytes()], &program_id);
+
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let aggregate_pda = get_aggregate_pda(program_id, title);
+
+        let mut data = vec![MovieInstruction::TAG_UPDATE_REVIEW];
+        data.append(&mut (title, rating, description).try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(payer, true),
+                AccountMeta::new(review_pda, false),
+                AccountMeta::new(config_pda, false),
+                AccountMeta::new(aggregate_pda, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_export_review_ix(program_id: Pubkey, author: Pubkey, title: &str) -> Instruction {
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_EXPORT_REVIEW];
+        data.append(&mut (author, title).try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![AccountMeta::new_readonly(review_pda, false)],
+            data,
+        }
+    }
+
+    fn create_check_review_exists_ix(program_id: Pubkey, author: Pubkey, title: &str) -> (Pubkey, Instruction) {
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_CHECK_REVIEW_EXISTS];
+        data.append(&mut (author, title).try_to_vec().unwrap());
+
+        (
+            review_pda,
+            Instruction {
+                program_id,
+                accounts: vec![AccountMeta::new_readonly(review_pda, false)],
+                data,
+            },
+        )
+    }
+
+    fn create_log_comment_ix(program_id: Pubkey, author: Pubkey, title: &str, index: u64) -> Instruction {
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], &program_id);
+        let (counter_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), b"comment"], &program_id);
+        let (comment_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), index.to_be_bytes().as_ref()], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_LOG_COMMENT];
+        data.append(&mut (author, title, index).try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(review_pda, false),
+                AccountMeta::new_readonly(counter_pda, false),
+                AccountMeta::new_readonly(comment_pda, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_react_ix(reactor: Pubkey, program_id: Pubkey, author: Pubkey, title: &str, index: u64, reaction_kind: u8) -> (Pubkey, Instruction) {
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], &program_id);
+        let (comment_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), index.to_be_bytes().as_ref()], &program_id);
+        let (reaction_pda, _bump_seed) = Pubkey::find_program_address(&[comment_pda.as_ref(), reactor.as_ref(), b"react"], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_REACT_TO_COMMENT];
+        data.append(&mut (title, index, reaction_kind).try_to_vec().unwrap());
+
+        (comment_pda, Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(reactor, true),
+                AccountMeta::new_readonly(review_pda, false),
+                AccountMeta::new(comment_pda, false),
+                AccountMeta::new(reaction_pda, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            ],
+            data,
+        })
+    }
+
+    fn create_token_metadata_ix(admin: Pubkey, program_id: Pubkey, name: &str) -> Instruction {
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let (mint, _bump_seed) = Pubkey::find_program_address(&[b"token_mint"], &program_id);
+        let (mint_auth, _bump_seed) = Pubkey::find_program_address(&[b"token_auth"], &program_id);
+        let (metadata_pda, _bump_seed) = Pubkey::find_program_address(
+            &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+            &TOKEN_METADATA_PROGRAM_ID
+        );
+
+        let mut data = vec![MovieInstruction::TAG_CREATE_TOKEN_METADATA];
+        data.append(&mut (name, "MRT", "https://example.com/mrt.json").try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(admin, true),
+                AccountMeta::new_readonly(config_pda, false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new_readonly(mint_auth, false),
+                AccountMeta::new(metadata_pda, false),
+                AccountMeta::new_readonly(TOKEN_METADATA_PROGRAM_ID, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            ],
+            data,
+        }
+    }
+
+    // Stands in for Metaplex, which program-test can't load: accepts only a well-formed
+    // CreateMetadataAccountV3 with the mint authority signing
+    fn token_metadata_stub(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+        let (&tag, mut rest) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+        let (name, _symbol, _uri, _fee) = <(String, String, String, u16) as borsh::BorshDeserialize>::deserialize(&mut rest)?;
+        if tag != CREATE_METADATA_ACCOUNT_V3 || rest != [0, 0, 0, 1, 0] || !accounts[2].is_signer {
+            return Err(ProgramError::InvalidInstructionData)
+        }
+        msg!("Metadata name: {}", name);
+        Ok(())
+    }
+
+    fn create_init_config_ix(
+        admin: Pubkey,
+        program_id: Pubkey,
+        slash_amount: u64,
+        max_rating: Option<u8>,
+        max_reviews_per_author: Option<u64>,
+    ) -> (Pubkey, Instruction) {
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_INITIALIZE_CONFIG];
+        data.append(&mut (slash_amount, max_rating, None::<u16>, max_reviews_per_author, None::<u64>, None::<u64>, None::<u64>, None::<bool>, None::<u64>).try_to_vec().unwrap());
+
+        (
+            config_pda,
+            Instruction {
+                program_id,
+                accounts: vec![
+                    AccountMeta::new_readonly(admin, true),
+                    AccountMeta::new(config_pda, false),
+                    AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                ],
+                data,
+            },
+        )
+    }
+
+    fn create_slash_review_ix(admin: Pubkey, program_id: Pubkey, author: Pubkey, title: &str) -> Instruction {
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], &program_id);
+        let (mint, _bump_seed) = Pubkey::find_program_address(&[b"token_mint"], &program_id);
+        let (mint_auth, _bump_seed) = Pubkey::find_program_address(&[b"token_auth"], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_SLASH_REVIEW];
+        data.append(&mut (author, title).try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(admin, true),
+                AccountMeta::new_readonly(config_pda, false),
+                AccountMeta::new_readonly(review_pda, false),
+                AccountMeta::new(get_associated_token_address(&author, &mint), false),
+                AccountMeta::new(mint, false),
+                AccountMeta::new_readonly(mint_auth, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_upvote_review_ix(voter: Pubkey, program_id: Pubkey, author: Pubkey, title: &str) -> Instruction {
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], &program_id);
+        let (marker_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), voter.as_ref(), b"upvote"], &program_id);
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_UPVOTE_REVIEW];
+        data.append(&mut (author, title).try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(voter, true),
+                AccountMeta::new(review_pda, false),
+                AccountMeta::new(marker_pda, false),
+                AccountMeta::new_readonly(config_pda, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_report_review_ix(reporter: Pubkey, program_id: Pubkey, author: Pubkey, title: &str) -> Instruction {
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], &program_id);
+        let (marker_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), reporter.as_ref(), b"report"], &program_id);
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_REPORT_REVIEW];
+        data.append(&mut (author, title).try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(reporter, true),
+                AccountMeta::new(review_pda, false),
+                AccountMeta::new(marker_pda, false),
+                AccountMeta::new_readonly(config_pda, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_init_program_ix(admin: Pubkey, program_id: Pubkey, decimals: Option<u8>) -> Instruction {
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let (mint, _bump_seed) = Pubkey::find_program_address(&[b"token_mint"], &program_id);
+        let (mint_auth, _bump_seed) = Pubkey::find_program_address(&[b"token_auth"], &program_id);
+        let (treasury_pda, _bump_seed) = Pubkey::find_program_address(&[b"treasury"], &program_id);
+        let (global_seq_pda, _bump_seed) = Pubkey::find_program_address(&[b"global_seq"], &program_id);
+        let (comment_mint, _bump_seed) = Pubkey::find_program_address(&[b"comment_token_mint"], &program_id);
+        let (comment_mint_auth, _bump_seed) = Pubkey::find_program_address(&[b"comment_token_auth"], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_INITIALIZE_PROGRAM];
+        data.append(&mut (TEST_SLASH_AMOUNT, false, decimals).try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(admin, true),
+                AccountMeta::new(config_pda, false),
+                AccountMeta::new(mint, false),
+                AccountMeta::new_readonly(mint_auth, false),
+                AccountMeta::new(treasury_pda, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                AccountMeta::new_readonly(RENT_PROGRAM_ID, false),
+                AccountMeta::new(global_seq_pda, false),
+                AccountMeta::new(comment_mint, false),
+                AccountMeta::new_readonly(comment_mint_auth, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_transfer_admin_ix(admin: Pubkey, program_id: Pubkey, new_admin: Pubkey) -> Instruction {
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_TRANSFER_ADMIN];
+        data.append(&mut new_admin.try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(admin, true),
+                AccountMeta::new(config_pda, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_set_verbose_logs_ix(admin: Pubkey, program_id: Pubkey, enabled: bool) -> Instruction {
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_SET_VERBOSE_LOGS];
+        data.append(&mut enabled.try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(admin, true),
+                AccountMeta::new(config_pda, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_set_review_expiry_ix(admin: Pubkey, program_id: Pubkey, author: Pubkey, title: &str, expires_at: i64) -> Instruction {
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_SET_REVIEW_EXPIRY];
+        data.append(&mut (author, title, expires_at).try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(admin, true),
+                AccountMeta::new_readonly(config_pda, false),
+                AccountMeta::new(review_pda, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_accept_admin_ix(new_admin: Pubkey, program_id: Pubkey) -> Instruction {
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(new_admin, true),
+                AccountMeta::new(config_pda, false),
+            ],
+            data: vec![MovieInstruction::TAG_ACCEPT_ADMIN],
+        }
+    }
+
+    // Builds an initialized review account directly, for states the instructions
+    // can't produce (other layout versions, reviews without a counter)
+    fn create_review_account(program_id: Pubkey, author: Pubkey, title: &str, version: u8) -> (Pubkey, Account) {
+        let (review_pda, bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], &program_id);
+        let mut data = MovieAccountState {
+            version,
+            ..MovieAccountState::new(author, title.to_string(), 3, "Imported".to_string(), bump_seed)
+        }.try_to_vec().unwrap();
+        data.resize(1000, 0);
+
+        let account = Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        };
+        (review_pda, account)
+    }
+
+    // A review as the program wrote it before accounts carried a version byte
+    fn create_old_review_account(program_id: Pubkey, author: Pubkey, title: &str) -> (Pubkey, Account) {
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], &program_id);
+        let data = OldMovieAccountState {
+            discriminator: MovieAccountState::DISCRIMINATOR.to_string(),
+            is_initialized: true,
+            review: author,
+            rating: 4,
+            title: title.to_string(),
+            description: "Written before versioning".to_string(),
+        }.try_to_vec().unwrap();
+
+        let account = Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        };
+        (review_pda, account)
+    }
+
+    fn create_migrate_review_ix(signer: Pubkey, program_id: Pubkey, author: Pubkey, title: &str) -> Instruction {
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], &program_id);
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_MIGRATE_REVIEW];
+        data.append(&mut title.try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(signer, true),
+                AccountMeta::new(review_pda, false),
+                AccountMeta::new_readonly(config_pda, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_ensure_counter_ix(payer: Pubkey, program_id: Pubkey, author: Pubkey, title: &str) -> (Pubkey, Instruction) {
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], &program_id);
+        let (counter_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), b"comment"], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_ENSURE_COUNTER];
+        data.append(&mut (author, title).try_to_vec().unwrap());
+
+        (
+            counter_pda,
+            Instruction {
+                program_id,
+                accounts: vec![
+                    AccountMeta::new(payer, true),
+                    AccountMeta::new_readonly(review_pda, false),
+                    AccountMeta::new(counter_pda, false),
+                    AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                ],
+                data,
+            },
+        )
+    }
+
+    fn create_add_comment_ix(
+        commenter: Pubkey,
+        program_id: Pubkey,
+        review_pda: Pubkey,
+        index: u64,
+        comment: &str,
+    ) -> (Pubkey, Instruction) {
+        let (counter_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), b"comment"], &program_id);
+        let (comment_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), index.to_be_bytes().as_ref()], &program_id);
+        let (mint, _bump_seed) = Pubkey::find_program_address(&[b"comment_token_mint"], &program_id);
+        let (mint_auth, _bump_seed) = Pubkey::find_program_address(&[b"comment_token_auth"], &program_id);
+        let user_ata = get_associated_token_address(&commenter, &mint);
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let marker_pda = get_commenter_marker_pda(program_id, review_pda, commenter);
+
+        let mut data = vec![MovieInstruction::TAG_ADD_COMMENT];
+        data.append(&mut comment.try_to_vec().unwrap());
+
+        let add_comment_ix = Instruction {
+            program_id,
+            accounts: vec![
+                // Writable: the commenter pays rent for the comment account
+                AccountMeta::new(commenter, true),
+                AccountMeta::new_readonly(review_pda, false),
+                AccountMeta::new(counter_pda, false),
+                AccountMeta::new(comment_pda, false),
+                AccountMeta::new(mint, false),
+                AccountMeta::new_readonly(mint_auth, false),
+                AccountMeta::new(user_ata, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                AccountMeta::new(config_pda, false),
+                AccountMeta::new(marker_pda, false),
+            ],
+            data,
+        };
+        (comment_pda, add_comment_ix)
+    }
+
+    fn create_batch_add_comments_ix(
+        commenter: Pubkey,
+        program_id: Pubkey,
+        review_pda: Pubkey,
+        first_index: u64,
+        comments: &[&str],
+    ) -> (Vec<Pubkey>, Instruction) {
+        // Same accounts as AddComment without the comment PDA, which moves to the end
+        let (_comment_pda, mut batch_ix) = create_add_comment_ix(commenter, program_id, review_pda, first_index, "");
+        batch_ix.accounts.remove(3);
+
+        let comment_pdas: Vec<Pubkey> = (first_index..first_index + comments.len() as u64)
+            .map(|index| Pubkey::find_program_address(&[review_pda.as_ref(), &index.to_be_bytes()], &program_id).0)
+            .collect();
+        batch_ix.accounts.extend(comment_pdas.iter().map(|comment_pda| AccountMeta::new(*comment_pda, false)));
+
+        let mut data = vec![MovieInstruction::TAG_BATCH_ADD_COMMENTS];
+        data.append(&mut comments.to_vec().try_to_vec().unwrap());
+        batch_ix.data = data;
+
+        (comment_pdas, batch_ix)
+    }
+
+    fn create_update_comment_ix(commenter: Pubkey, program_id: Pubkey, comment_pda: Pubkey, comment: &str) -> Instruction {
+        let mut data = vec![MovieInstruction::TAG_UPDATE_COMMENT];
+        data.append(&mut comment.try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(commenter, true),
+                AccountMeta::new(comment_pda, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_set_spoiler_flag_ix(payer: Pubkey, program_id: Pubkey, title: &str, has_spoilers: bool) -> Instruction {
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[payer.as_ref(), title.as_bytes()], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_SET_SPOILER_FLAG];
+        data.append(&mut (title, has_spoilers).try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(payer, true),
+                AccountMeta::new(review_pda, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_claim_rewards_ix(author: Pubkey, program_id: Pubkey) -> Instruction {
+        let (profile_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), b"profile"], &program_id);
+        let (mint, _bump_seed) = Pubkey::find_program_address(&[b"token_mint"], &program_id);
+        let (mint_auth, _bump_seed) = Pubkey::find_program_address(&[b"token_auth"], &program_id);
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(author, true),
+                AccountMeta::new(profile_pda, false),
+                AccountMeta::new(mint, false),
+                AccountMeta::new_readonly(mint_auth, false),
+                AccountMeta::new(get_associated_token_address(&author, &mint), false),
+                AccountMeta::new(config_pda, false),
+                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            ],
+            data: vec![MovieInstruction::TAG_CLAIM_REWARDS],
+        }
+    }
+
+    fn create_pin_comment_ix(payer: Pubkey, program_id: Pubkey, title: &str, index: Option<u64>) -> Instruction {
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[payer.as_ref(), title.as_bytes()], &program_id);
+        let (counter_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), b"comment"], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_PIN_COMMENT];
+        data.append(&mut (title, index).try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(payer, true),
+                AccountMeta::new(review_pda, false),
+                AccountMeta::new_readonly(counter_pda, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_set_watch_status_ix(payer: Pubkey, program_id: Pubkey, title: &str, status: u8) -> Instruction {
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[payer.as_ref(), title.as_bytes()], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_SET_WATCH_STATUS];
+        data.append(&mut (title, status).try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(payer, true),
+                AccountMeta::new(review_pda, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_set_genre_ix(payer: Pubkey, program_id: Pubkey, title: &str, genre: &str) -> Instruction {
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[payer.as_ref(), title.as_bytes()], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_SET_GENRE];
+        data.append(&mut (title, genre).try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(payer, true),
+                AccountMeta::new(review_pda, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            ],
+            data,
+        }
+    }
+
+    fn create_transfer_review_ix(
+        owner: Pubkey,
+        program_id: Pubkey,
+        review_pda: Pubkey,
+        title: &str,
+        new_owner: Pubkey,
+    ) -> Instruction {
+        let mut data = vec![MovieInstruction::TAG_TRANSFER_REVIEW];
+        data.append(&mut (title, new_owner).try_to_vec().unwrap());
+
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(owner, true),
+                AccountMeta::new(review_pda, false),
+            ],
+            data,
+        }
+    }
+
+    async fn get_review(banks_client: &mut BanksClient, review_pda: Pubkey) -> MovieAccountState {
+        let account = banks_client.get_account(review_pda).await.unwrap().unwrap();
+        try_from_slice_unchecked::<MovieAccountState>(&account.data).unwrap()
+    }
+
+    async fn get_counter(banks_client: &mut BanksClient, counter_pda: Pubkey) -> MovieCommentCounter {
+        let account = banks_client.get_account(counter_pda).await.unwrap().unwrap();
+        try_from_slice_unchecked::<MovieCommentCounter>(&account.data).unwrap()
+    }
+
+    async fn get_comment(banks_client: &mut BanksClient, comment_pda: Pubkey) -> MovieComment {
+        let account = banks_client.get_account(comment_pda).await.unwrap().unwrap();
+        try_from_slice_unchecked::<MovieComment>(&account.data).unwrap()
+    }
+
+    async fn get_config(banks_client: &mut BanksClient, config_pda: Pubkey) -> ProgramConfig {
+        let account = banks_client.get_account(config_pda).await.unwrap().unwrap();
+        try_from_slice_unchecked::<ProgramConfig>(&account.data).unwrap()
+    }
+
+    async fn get_profile(banks_client: &mut BanksClient, author: Pubkey, program_id: Pubkey) -> AuthorProfile {
+        let (profile_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), b"profile"], &program_id);
+        let account = banks_client.get_account(profile_pda).await.unwrap().unwrap();
+        try_from_slice_unchecked::<AuthorProfile>(&account.data).unwrap()
+    }
+
+    async fn token_balance(banks_client: &mut BanksClient, token_account: Pubkey) -> u64 {
+        let account = banks_client.get_account(token_account).await.unwrap().unwrap();
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    // Moves the bank clock forward, e.g. past AuthorProfile::REWARD_COOLDOWN. The
+    // program reads time only through `Clock::get()`, which program-test answers from
+    // the bank's Clock sysvar, so overriding that sysvar is all a test needs; there
+    // is no test-only clock in the program itself. Needs a context from
+    // `start_with_context()`, since `start()` doesn't expose `set_sysvar`.
+    async fn advance_clock(context: &mut ProgramTestContext, seconds: i64) {
+        let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+        clock.unix_timestamp += seconds;
+        context.set_sysvar(&clock);
+    }
+
+    async fn process_instructions(
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: Hash,
+        instructions: &[Instruction],
+    ) -> Result<(), BanksClientError> {
+        let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+        transaction.sign(&[payer], recent_blockhash);
+        banks_client.process_transaction(transaction).await
+    }
+
+    fn review_error(instruction_index: u8, error: ReviewError) -> TransactionError {
+        TransactionError::InstructionError(instruction_index, InstructionError::Custom(error as u32))
+    }
+
+    fn create_add_review_v2_ix(
+        payer: Pubkey,
+        program_id: Pubkey,
+        title: &str,
+        rating: u8,
+        description: &str,
+        genre: &str,
+    ) -> (Pubkey, Pubkey, Instruction) {
+        // V2 takes the same accounts as V1, only the data differs
+        let (review_pda, counter_pda, mut add_review_ix) = create_add_review_ix(payer, program_id, title, rating, description);
+        let mut data = vec![MovieInstruction::TAG_ADD_REVIEW_V2];
+        data.append(&mut (title, rating, description, genre).try_to_vec().unwrap());
+        add_review_ix.data = data;
+        (review_pda, counter_pda, add_review_ix)
+    }
+
+    const TEST_SLASH_AMOUNT: u64 = 8 * LAMPORTS_PER_SOL;
+
+    // Initializes the reward mint and config (with the payer as admin) and creates
+    // the payer's token account for the mint
+    async fn setup_mint(
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: Hash,
+        program_id: Pubkey,
+    ) -> Pubkey {
+        let (mint, _mint_auth, init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        let (_config_pda, init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, TEST_SLASH_AMOUNT, None, None);
+        let init_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint);
+        let mut transaction = Transaction::new_with_payer(&[init_config_ix, init_mint_ix, init_ata_ix], Some(&payer.pubkey()));
+        transaction.sign(&[payer], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+        mint
+    }
+
+    #[tokio::test]
+    async fn test_initialize_mint_instruction() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+
+        // Call helper function
+        let (_config_pda, init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, 0, None, None);
+        let (_mint, _mint_auth, init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+
+        // Create transaction object with instructions, accounts, and input data
+        let mut transaction = Transaction::new_with_payer(
+            &[init_config_ix, init_mint_ix,],
+            Some(&payer.pubkey())
+        );
+        transaction.sign(&[&payer], recent_blockhash);
+
+        // Process transaction and compare the result
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+    }
+
+    #[tokio::test]
+    async fn test_initialize_mint_rejects_wrong_accounts() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+
+        // Account positions: mint auth, token program, rent sysvar
+        for index in [2, 4, 5] {
+            let (_mint, _mint_auth, mut init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+            init_mint_ix.accounts[index].pubkey = Pubkey::new_unique();
+            assert_eq!(
+                process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_mint_ix]).await.unwrap_err().unwrap(),
+                review_error(0, ReviewError::IncorrectAccountError)
+            );
+        }
+
+        // None of the rejected attempts left a mint behind
+        let (_config_pda, init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, 0, None, None);
+        let (mint, _mint_auth, init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        assert_eq!(banks_client.get_account(mint).await.unwrap(), None);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_config_ix, init_mint_ix]).await,
+            Ok(_)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_initialize_mint_requires_config_admin() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+
+        // Without a config there is no admin to compare against
+        let (_mint, _mint_auth, init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_mint_ix]).await.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::IllegalOwner)
+        );
+
+        let (_config_pda, init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, 0, None, None);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_config_ix]).await,
+            Ok(_)
+        );
+
+        let outsider = Keypair::new();
+        let fund_outsider_ix = system_instruction::transfer(&payer.pubkey(), &outsider.pubkey(), LAMPORTS_PER_SOL);
+        let (mint, _mint_auth, outsider_ix) = create_init_mint_ix(outsider.pubkey(), program_id);
+        let mut transaction = Transaction::new_with_payer(&[fund_outsider_ix, outsider_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &outsider], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(1, ReviewError::UnauthorizedInitializer)
+        );
+        assert_eq!(banks_client.get_account(mint).await.unwrap(), None);
+
+        let (_mint, _mint_auth, admin_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        let (_comment_mint, _comment_mint_auth, admin_comment_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[admin_ix, admin_comment_ix]).await,
+            Ok(_)
+        );
+        assert!(banks_client.get_account(mint).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_initialize_config_requires_admin_pubkey() {
+        let program_id = Pubkey::new_unique();
+        let stranger = Keypair::new();
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_account(stranger.pubkey(), Account::new(LAMPORTS_PER_SOL, 0, &SYSTEM_PROGRAM_ID));
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(program_test).await;
+
+        // Getting in first doesn't make a stranger the admin, by either route
+        let (config_pda, stranger_config_ix) = create_init_config_ix(stranger.pubkey(), program_id, 0, None, None);
+        let transaction = Transaction::new_signed_with_payer(&[stranger_config_ix], Some(&stranger.pubkey()), &[&stranger], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::UnauthorizedInitializer)
+        );
+        let stranger_program_ix = create_init_program_ix(stranger.pubkey(), program_id, None);
+        let transaction = Transaction::new_signed_with_payer(&[stranger_program_ix], Some(&stranger.pubkey()), &[&stranger], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::UnauthorizedInitializer)
+        );
+        assert_eq!(banks_client.get_account(config_pda).await.unwrap(), None);
+
+        let (_config_pda, admin_config_ix) = create_init_config_ix(payer.pubkey(), program_id, 0, None, None);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[admin_config_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_config(&mut banks_client, config_pda).await.admin, ADMIN_PUBKEY);
+    }
+
+    #[tokio::test]
+    async fn test_add_movie_review_instruction() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+
+        // Call helper function
+        let (mint, mint_auth, init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        let (config_pda, init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, 0, None, None);
+        let (profile_pda, _bump_seed) = Pubkey::find_program_address(&[payer.pubkey().as_ref(), b"profile"], &program_id);
+
+        // Create review PDA
+        let title: String = "Captain America".to_owned();
+        const RATING: u8 = 3;
+        let review: String = "Liked the move".to_owned();
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[payer.pubkey().as_ref(), title.as_bytes()], &program_id);
+        let aggregate_pda = get_aggregate_pda(program_id, &title);
+        let index_pda = get_index_pda(program_id, &title);
+        let (global_seq_pda, _bump_seed) = Pubkey::find_program_address(&[b"global_seq"], &program_id);
+
+        // Create comment PDA
+        let (comment_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), b"comment"], &program_id);
+
+        // Concat data to single buffer
+        let mut data_vec = vec![MovieInstruction::TAG_ADD_REVIEW];
+        data_vec.append(
+            &mut (TryInto::<u32>::try_into(title.len()).unwrap().to_le_bytes())
+              .try_into()
+              .unwrap()
+        );
+
+        data_vec.append(&mut title.into_bytes());
+        data_vec.push(RATING);
+        data_vec.append(
+            &mut (TryInto::<u32>::try_into(review.len())
+                  .unwrap()
+                  .to_le_bytes())
+            .try_into()
+            .unwrap()
+        );
+        data_vec.append(&mut review.into_bytes());
+
+        // Create transaction object with instructions, accounts, and input data
+        let mut transaction = Transaction::new_with_payer(
+            &[
+                init_config_ix,
+                init_mint_ix,
+                Instruction {
+                    program_id: program_id,
+                    accounts: vec![
+                        AccountMeta::new_readonly(payer.pubkey(), true),
+                        AccountMeta::new(review_pda, false),
+                        AccountMeta::new(comment_pda, false),
+                        AccountMeta::new(mint, false),
+                        AccountMeta::new_readonly(mint_auth, false),
+                        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+                        AccountMeta::new(config_pda, false),
+                        AccountMeta::new(profile_pda, false),
+                        AccountMeta::new(aggregate_pda, false),
+                        AccountMeta::new(index_pda, false),
+                        AccountMeta::new(global_seq_pda, false),
+                    ],
+                    data: data_vec,
+                }
+            ],
+            Some(&payer.pubkey())
+        );
+        transaction.sign(&[&payer], recent_blockhash);
+
+        // Process transaction and compare the result
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+    }
+
+    // Upper bound on what add_movie_review may consume, requested per transaction the
+    // way a client would. Under processor! the program runs natively and only its CPIs
+    // are metered, so this checks the transaction still fits the limit; it doesn't
+    // measure what `minimal-logging` saves, which would take the BPF build.
+    const ADD_REVIEW_COMPUTE_BUDGET: u32 = 60_000;
+
+    #[tokio::test]
+    async fn test_add_movie_review_within_compute_budget() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        // The review goes in its own transaction so the budget applies to it alone
+        let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Inception", 4, "Dreams within dreams"
+        );
+        let budget_ix = ComputeBudgetInstruction::set_compute_unit_limit(ADD_REVIEW_COMPUTE_BUDGET);
+        let mut transaction = Transaction::new_with_payer(&[budget_ix, add_review_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+    }
+
+    #[tokio::test]
+    async fn test_add_movie_review_rejects_long_description() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        // 850 bytes fits the review account but not the description cap
+        let description = "a".repeat(850);
+        let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Heat", 5, &description
+        );
+        let mut transaction = Transaction::new_with_payer(&[add_review_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer], recent_blockhash);
+
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::DescriptionTooLong as u32))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_comment_mints_from_comment_mint() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        let mint = setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let init_comment_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &comment_mint);
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Alien", 5, "In space no one can hear you scream"
+        );
+        let (_comment_pda, add_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "Agreed");
+        let claim_ix = create_claim_rewards_ix(payer.pubkey(), program_id);
+
+        // Comment rewards are minted on the spot, review rewards once claimed
+        let mut transaction = Transaction::new_with_payer(
+            &[init_comment_mint_ix, init_comment_ata_ix, add_review_ix, add_comment_ix, claim_ix],
+            Some(&payer.pubkey())
+        );
+        transaction.sign(&[&payer], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+
+        let review_ata = get_associated_token_address(&payer.pubkey(), &mint);
+        let comment_ata = get_associated_token_address(&payer.pubkey(), &comment_mint);
+        assert_eq!(token_balance(&mut banks_client, review_ata).await, 20 * LAMPORTS_PER_SOL);
+        assert_eq!(token_balance(&mut banks_client, comment_ata).await, 5 * LAMPORTS_PER_SOL);
+    }
+
+    #[tokio::test]
+    async fn test_add_comment_never_reuses_an_index() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let init_comment_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &comment_mint);
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Jaws", 4, "Bigger boat"
+        );
+        let (_comment_pda, add_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "First");
+        assert_matches!(
+            process_instructions(
+                &mut banks_client, &payer, recent_blockhash,
+                &[init_comment_mint_ix, init_comment_ata_ix, add_review_ix, add_comment_ix]
+            ).await,
+            Ok(_)
+        );
+
+        // Index 0 is taken, and the counter has moved past it
+        let (_comment_pda, add_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "Again");
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_comment_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidPDA)
+        );
+
+        // Funding the next slot ahead of time leaves an account in the way
+        let (comment_pda, add_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 1, "Second");
+        let squat_ix = system_instruction::transfer(&payer.pubkey(), &comment_pda, LAMPORTS_PER_SOL);
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[squat_ix, add_comment_ix]).await.unwrap_err().unwrap(),
+            review_error(1, ReviewError::CommentSlotOccupied)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transfer_review() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Vertigo", 5, "Hitchcock at his best"
+        );
+        let new_owner = Keypair::new();
+        let transfer_ix = create_transfer_review_ix(payer.pubkey(), program_id, review_pda, "Vertigo", new_owner.pubkey());
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix, transfer_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_review(&mut banks_client, review_pda).await.review, new_owner.pubkey());
+
+        // The previous author no longer holds the review
+        let transfer_back_ix = create_transfer_review_ix(payer.pubkey(), program_id, review_pda, "Vertigo", payer.pubkey());
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[transfer_back_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::IncorrectAccountError)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_movie_review_reads_rent_without_sysvar_account() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (review_pda, counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Rocky", 4, "Gonna fly now"
+        );
+        assert!(add_review_ix.accounts.iter().all(|meta| meta.pubkey != SYSVAR_RENT_ID));
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+
+        // Both accounts were funded from the syscall's rent parameters
+        let rent = banks_client.get_rent().await.unwrap();
+        let review_account = banks_client.get_account(review_pda).await.unwrap().unwrap();
+        let counter_account = banks_client.get_account(counter_pda).await.unwrap().unwrap();
+        assert_eq!(review_account.lamports, rent.minimum_balance(review_account.data.len()));
+        assert_eq!(counter_account.lamports, rent.minimum_balance(MovieCommentCounter::SIZE));
+    }
+
+    #[tokio::test]
+    async fn test_add_movie_review_v1_and_v2_coexist() {
+        let program_id = Pubkey::new_unique();
+        let mut context = start_with_context_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        let recent_blockhash = context.last_blockhash;
+        setup_mint(&mut context.banks_client, &context.payer, recent_blockhash, program_id).await;
+
+        let (v1_pda, _counter_pda, add_v1_ix) = create_add_review_ix(
+            context.payer.pubkey(), program_id, "Psycho", 4, "Never trust a motel"
+        );
+        let (v2_pda, _counter_pda, add_v2_ix) = create_add_review_v2_ix(
+            context.payer.pubkey(), program_id, "Halloween", 5, "Classic slasher", "horror"
+        );
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[add_v1_ix]).await,
+            Ok(_)
+        );
+        // Keep the second reward out of the first's cooldown
+        advance_clock(&mut context, AuthorProfile::REWARD_COOLDOWN).await;
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[add_v2_ix]).await,
+            Ok(_)
+        );
+        let (mut banks_client, payer) = (context.banks_client, context.payer);
+
+        let v1 = get_review(&mut banks_client, v1_pda).await;
+        assert_eq!(v1.discriminator, MovieAccountState::DISCRIMINATOR);
+        assert_eq!(v1.genre, "");
+        assert_eq!(v1.created_at, 0);
+
+        let v2 = get_review(&mut banks_client, v2_pda).await;
+        assert_eq!(v2.discriminator, MovieAccountState::DISCRIMINATOR_V2);
+        assert_eq!(v2.genre, "horror");
+        assert!(v2.created_at > 0);
+        assert_eq!(v2.created_at, v2.updated_at);
+
+        // Each version earned a first reviewer's 20 token reward
+        assert_eq!(get_profile(&mut banks_client, payer.pubkey(), program_id).await.pending_rewards, 40 * LAMPORTS_PER_SOL);
+
+        let (_v2_pda, _counter_pda, bad_genre_ix) = create_add_review_v2_ix(
+            payer.pubkey(), program_id, "Cats", 1, "Why", "musical"
+        );
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[bad_genre_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidGenre)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stored_bumps_match_derived_bumps() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let init_comment_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &comment_mint);
+        let (review_pda, counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Casablanca", 5, "Here's looking at you"
+        );
+        let (comment_pda, add_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "Kid");
+        assert_matches!(
+            process_instructions(
+                &mut banks_client, &payer, recent_blockhash,
+                &[init_comment_mint_ix, init_comment_ata_ix, add_review_ix, add_comment_ix]
+            ).await,
+            Ok(_)
+        );
+
+        let (_, review_bump) = Pubkey::find_program_address(&[payer.pubkey().as_ref(), b"Casablanca"], &program_id);
+        let (_, counter_bump) = Pubkey::find_program_address(&[review_pda.as_ref(), b"comment"], &program_id);
+        let (_, comment_bump) = Pubkey::find_program_address(&[review_pda.as_ref(), 0u64.to_be_bytes().as_ref()], &program_id);
+        assert_eq!(get_review(&mut banks_client, review_pda).await.bump, review_bump);
+        assert_eq!(get_counter(&mut banks_client, counter_pda).await.bump, counter_bump);
+        assert_eq!(get_comment(&mut banks_client, comment_pda).await.bump, comment_bump);
+
+        // The mint PDAs' bumps live in the config, which rewarding instructions check against
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let config = get_config(&mut banks_client, config_pda).await;
+        assert_eq!(config.mint_bump, Pubkey::find_program_address(&[b"token_mint"], &program_id).1);
+        assert_eq!(config.mint_auth_bump, Pubkey::find_program_address(&[b"token_auth"], &program_id).1);
+        assert_eq!(config.comment_mint_bump, Pubkey::find_program_address(&[b"comment_token_mint"], &program_id).1);
+        assert_eq!(config.comment_mint_auth_bump, Pubkey::find_program_address(&[b"comment_token_auth"], &program_id).1);
+
+        // Updates re-derive the review PDA from the stored bump
+        let update_ix = create_update_review_ix(payer.pubkey(), program_id, "Casablanca", 4, "Round up the usual suspects");
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[update_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_review(&mut banks_client, review_pda).await.rating, 4);
+    }
+
+    #[tokio::test]
+    async fn test_check_review_exists_logs_result() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        // Native program-test prints sol_log_data to stdout instead of the log
+        // collector, so check the plain log line emitted next to it
+        async fn simulate_check(banks_client: &mut BanksClient, payer: &Keypair, recent_blockhash: Hash, ix: Instruction) -> Vec<String> {
+            let transaction = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], recent_blockhash);
+            let simulation = banks_client.simulate_transaction(transaction).await.unwrap();
+            assert_matches!(simulation.result, Some(Ok(())));
+            simulation.simulation_details.unwrap().logs
+                .into_iter()
+                .filter(|log| log.starts_with("Program log: Review exists"))
+                .collect()
+        }
+
+        let (_review_pda, check_ix) = create_check_review_exists_ix(program_id, payer.pubkey(), "Alien");
+        let logs = simulate_check(&mut banks_client, &payer, recent_blockhash, check_ix.clone()).await;
+        assert_eq!(logs, vec!["Program log: Review exists: false"]);
+
+        let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Alien", 5, "In space no one can hear you scream"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+
+        let logs = simulate_check(&mut banks_client, &payer, recent_blockhash, check_ix).await;
+        assert_eq!(logs, vec!["Program log: Review exists: true"]);
+
+        // The account must still be the PDA for the given author and title
+        let (_review_pda, mut wrong_ix) = create_check_review_exists_ix(program_id, payer.pubkey(), "Aliens");
+        wrong_ix.accounts[0] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[wrong_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidPDA)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_slash_review_burns_author_tokens() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        let mint = setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+        let payer_ata = get_associated_token_address(&payer.pubkey(), &mint);
+
+        // setup_mint configures a TEST_SLASH_AMOUNT of 8 tokens
+        let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Spam", 5, "Buy my tokens"
+        );
+        let claim_ix = create_claim_rewards_ix(payer.pubkey(), program_id);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix, claim_ix]).await,
+            Ok(_)
+        );
+
+        let first_slash_ix = create_slash_review_ix(payer.pubkey(), program_id, payer.pubkey(), "Spam");
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[first_slash_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(token_balance(&mut banks_client, payer_ata).await, 12 * LAMPORTS_PER_SOL);
+
+        // Only the config admin may slash
+        let outsider = Keypair::new();
+        let outsider_slash_ix = create_slash_review_ix(outsider.pubkey(), program_id, payer.pubkey(), "Spam");
+        let mut transaction = Transaction::new_with_payer(&[outsider_slash_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &outsider], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::IncorrectAccountError)
+        );
+
+        // 12 -> 4, after which an 8 token slash no longer fits the balance. Each repeat
+        // needs a fresh blockhash so it isn't deduplicated as the same transaction
+        let latest_blockhash = banks_client.get_new_latest_blockhash(&recent_blockhash).await.unwrap();
+        let second_slash_ix = create_slash_review_ix(payer.pubkey(), program_id, payer.pubkey(), "Spam");
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, latest_blockhash, &[second_slash_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(token_balance(&mut banks_client, payer_ata).await, 4 * LAMPORTS_PER_SOL);
+
+        let newer_blockhash = banks_client.get_new_latest_blockhash(&latest_blockhash).await.unwrap();
+        let third_slash_ix = create_slash_review_ix(payer.pubkey(), program_id, payer.pubkey(), "Spam");
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, newer_blockhash, &[third_slash_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InsufficientTokensToSlash)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_comment_pdas_use_big_endian_counter_seeds() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let init_comment_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &comment_mint);
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Heat", 5, "The diner scene"
+        );
+        let (_comment_pda, first_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "First");
+        let (_comment_pda, second_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 1, "Second");
+        assert_matches!(
+            process_instructions(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &[init_comment_mint_ix, init_comment_ata_ix, add_review_ix, first_comment_ix, second_comment_ix]
+            ).await,
+            Ok(_)
+        );
+
+        // Re-derive without the helper so the seed encoding is pinned here
+        for (index, comment) in [(0u64, "First"), (1u64, "Second")] {
+            let (comment_pda, _bump_seed) = Pubkey::find_program_address(
+                &[review_pda.as_ref(), &index.to_be_bytes()],
+                &program_id
+            );
+            assert_eq!(get_comment(&mut banks_client, comment_pda).await.comment, comment);
+        }
+
+        // A little-endian seed points somewhere else entirely
+        let (little_endian_pda, _bump_seed) = Pubkey::find_program_address(
+            &[review_pda.as_ref(), &1u64.to_le_bytes()],
+            &program_id
+        );
+        assert_eq!(banks_client.get_account(little_endian_pda).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_update_rejects_unknown_review_version() {
+        let program_id = Pubkey::new_unique();
+        let author = Keypair::new();
+        // A review written by some future layout
+        let (review_pda, review_account) = create_review_account(program_id, author.pubkey(), "Tenet", MovieAccountState::VERSION + 1);
+
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_account(review_pda, review_account);
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(program_test).await;
+
+        let update_ix = create_update_review_ix(author.pubkey(), program_id, "Tenet", 4, "Still inverted");
+        let mut transaction = Transaction::new_with_payer(&[update_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &author], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::UnsupportedVersion)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ensure_counter_repairs_orphaned_review() {
+        let program_id = Pubkey::new_unique();
+        let author = Pubkey::new_unique();
+        let (orphan_pda, orphan_account) = create_review_account(program_id, author, "Metropolis", MovieAccountState::VERSION);
+
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_account(orphan_pda, orphan_account);
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(program_test).await;
+
+        // Any payer can create the missing counter
+        let (counter_pda, ensure_ix) = create_ensure_counter_ix(payer.pubkey(), program_id, author, "Metropolis");
+        assert_eq!(banks_client.get_account(counter_pda).await.unwrap(), None);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[ensure_ix]).await,
+            Ok(_)
+        );
+        let counter = get_counter(&mut banks_client, counter_pda).await;
+        assert!(counter.is_initialized);
+        assert_eq!(counter.counter, 0);
+
+        // Reviews that already have a counter are left untouched
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+        let (_review_pda, counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Nosferatu", 4, "Shadows on the stairs"
+        );
+        let (_counter_pda, ensure_ix) = create_ensure_counter_ix(payer.pubkey(), program_id, payer.pubkey(), "Nosferatu");
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix, ensure_ix]).await,
+            Ok(_)
+        );
+        assert!(get_counter(&mut banks_client, counter_pda).await.is_initialized);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_empty_fields() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (_review_pda, _counter_pda, empty_title_ix) = create_add_review_ix(payer.pubkey(), program_id, "", 5, "No title");
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[empty_title_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::EmptyField)
+        );
+
+        let (_review_pda, _counter_pda, empty_description_ix) = create_add_review_ix(payer.pubkey(), program_id, "Up", 5, "");
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[empty_description_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::EmptyField)
+        );
+
+        // Updates can't blank out a description either
+        let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(payer.pubkey(), program_id, "Up", 5, "Balloons");
+        let update_ix = create_update_review_ix(payer.pubkey(), program_id, "Up", 5, "");
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix, update_ix]).await.unwrap_err().unwrap(),
+            review_error(1, ReviewError::EmptyField)
+        );
+
+        // Comments follow the same rule
+        let (comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let init_comment_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &comment_mint);
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(payer.pubkey(), program_id, "Up", 5, "Balloons");
+        let (_comment_pda, empty_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "");
+        assert_eq!(
+            process_instructions(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &[init_comment_mint_ix, init_comment_ata_ix, add_review_ix, empty_comment_ix]
+            ).await.unwrap_err().unwrap(),
+            review_error(3, ReviewError::EmptyField)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mint_reward_enforces_supply_cap() {
+        let program_id = Pubkey::new_unique();
+        let (config_pda, config_bump) = Pubkey::find_program_address(&[b"config"], &program_id);
+
+        // A config with room for exactly one more first reviewer's reward
+        let config_data = ProgramConfig {
+            discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+            version: ProgramConfig::VERSION,
+            is_initialized: true,
+            admin: ADMIN_PUBKEY,
+            slash_amount: 0,
+            bump: config_bump,
+            total_minted: ProgramConfig::MAX_SUPPLY_TOKENS * LAMPORTS_PER_SOL - 20 * LAMPORTS_PER_SOL,
+            max_rating: ProgramConfig::DEFAULT_MAX_RATING,
+            tip_fee_bps: ProgramConfig::DEFAULT_TIP_FEE_BPS,
+            max_reviews_per_author: AuthorProfile::MAX_REVIEWS_PER_AUTHOR,
+            decimals: ProgramConfig::DEFAULT_DECIMALS,
+            report_threshold: ProgramConfig::DEFAULT_REPORT_THRESHOLD,
+            max_comments_per_review: MovieComment::MAX_COMMENTS_PER_REVIEW,
+            pending_admin: Pubkey::default(),
+            verbose_logs: true,
+            review_fee: 0,
+            allow_self_comment: true,
+            min_description_len: 0,
+            mint_bump: 0,
+            mint_auth_bump: 0,
+            comment_mint_bump: 0,
+            comment_mint_auth_bump: 0,
+        }.try_to_vec().unwrap();
+
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_account(config_pda, Account {
+            lamports: Rent::default().minimum_balance(config_data.len()),
+            data: config_data,
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        });
+        let mut context = start_with_context_as_admin(program_test).await;
+        let (payer, recent_blockhash) = (context.payer.pubkey(), context.last_blockhash);
+
+        let (mint, _mint_auth, init_mint_ix) = create_init_mint_ix(payer, program_id);
+        let init_ata_ix = create_associated_token_account(&payer, &payer, &mint);
+        let (_review_pda, _counter_pda, first_review_ix) = create_add_review_ix(
+            payer, program_id, "Ran", 5, "Kurosawa's Lear"
+        );
+        let claim_ix = create_claim_rewards_ix(payer, program_id);
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[init_mint_ix, init_ata_ix, first_review_ix, claim_ix]).await,
+            Ok(_)
+        );
+
+        assert_eq!(get_config(&mut context.banks_client, config_pda).await.total_minted, ProgramConfig::MAX_SUPPLY_TOKENS * LAMPORTS_PER_SOL);
+
+        // Past the cooldown, so the second review earns a reward that can't be minted
+        advance_clock(&mut context, AuthorProfile::REWARD_COOLDOWN).await;
+        let (_review_pda, _counter_pda, second_review_ix) = create_add_review_ix(
+            payer, program_id, "Ikiru", 5, "To live"
+        );
+        let claim_ix = create_claim_rewards_ix(payer, program_id);
+        assert_eq!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[second_review_ix, claim_ix]).await.unwrap_err().unwrap(),
+            review_error(1, ReviewError::SupplyCapExceeded)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_author_profile_counts_reviews() {
+        let program_id = Pubkey::new_unique();
+        let mut context = start_with_context_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        let (payer, recent_blockhash) = (context.payer.pubkey(), context.last_blockhash);
+        setup_mint(&mut context.banks_client, &context.payer, recent_blockhash, program_id).await;
+
+        let (_review_pda, _counter_pda, first_review_ix) = create_add_review_ix(
+            payer, program_id, "Amelie", 5, "Paris, gnomes"
+        );
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[first_review_ix]).await,
+            Ok(_)
+        );
+        let profile = get_profile(&mut context.banks_client, payer, program_id).await;
+        assert_eq!(profile.author, payer);
+        assert_eq!(profile.review_count, 1);
+        assert_eq!(profile.total_rewards, 20 * LAMPORTS_PER_SOL);
+
+        // The second review reuses the existing profile
+        advance_clock(&mut context, AuthorProfile::REWARD_COOLDOWN).await;
+        let (_review_pda, _counter_pda, second_review_ix) = create_add_review_v2_ix(
+            payer, program_id, "Delicatessen", 4, "Butcher upstairs", "comedy"
+        );
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[second_review_ix]).await,
+            Ok(_)
+        );
+        let profile = get_profile(&mut context.banks_client, payer, program_id).await;
+        assert_eq!(profile.review_count, 2);
+        assert_eq!(profile.total_rewards, 40 * LAMPORTS_PER_SOL);
+    }
+
+    #[tokio::test]
+    async fn test_update_comment_does_not_mint_again() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let init_comment_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &comment_mint);
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Solaris", 4, "Slow and haunting"
+        );
+        let (comment_pda, add_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "Too slow");
+        assert_matches!(
+            process_instructions(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &[init_comment_mint_ix, init_comment_ata_ix, add_review_ix, add_comment_ix]
+            ).await,
+            Ok(_)
+        );
+        let comment_ata = get_associated_token_address(&payer.pubkey(), &comment_mint);
+        assert_eq!(token_balance(&mut banks_client, comment_ata).await, 5 * LAMPORTS_PER_SOL);
+        assert!(get_comment(&mut banks_client, comment_pda).await.rewarded);
+
+        // Growing and then shrinking the text resizes the account without paying out
+        let longer_ix = create_update_comment_ix(payer.pubkey(), program_id, comment_pda, "Too slow, but it stayed with me for weeks");
+        let shorter_ix = create_update_comment_ix(payer.pubkey(), program_id, comment_pda, "Stayed with me");
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[longer_ix, shorter_ix]).await,
+            Ok(_)
+        );
+        let comment = get_comment(&mut banks_client, comment_pda).await;
+        assert_eq!(comment.comment, "Stayed with me");
+        assert!(comment.rewarded);
+        assert_eq!(token_balance(&mut banks_client, comment_ata).await, 5 * LAMPORTS_PER_SOL);
+
+        let comment_account = banks_client.get_account(comment_pda).await.unwrap().unwrap();
+        assert_eq!(comment_account.data.len(), MovieComment::get_account_size("Stayed with me"));
+        assert_eq!(comment_account.lamports, Rent::default().minimum_balance(comment_account.data.len()));
+
+        // Nobody else may edit it
+        let outsider = Keypair::new();
+        let outsider_ix = create_update_comment_ix(outsider.pubkey(), program_id, comment_pda, "Hijacked");
+        let mut transaction = Transaction::new_with_payer(&[outsider_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &outsider], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::IncorrectAccountError)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_spoiler_flag() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Psycho", 5, "The shower scene"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+        assert!(!get_review(&mut banks_client, review_pda).await.has_spoilers);
+
+        let flag_ix = create_set_spoiler_flag_ix(payer.pubkey(), program_id, "Psycho", true);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[flag_ix]).await,
+            Ok(_)
+        );
+        assert!(get_review(&mut banks_client, review_pda).await.has_spoilers);
+
+        let unflag_ix = create_set_spoiler_flag_ix(payer.pubkey(), program_id, "Psycho", false);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[unflag_ix]).await,
+            Ok(_)
+        );
+        assert!(!get_review(&mut banks_client, review_pda).await.has_spoilers);
+
+        // Someone other than the author derives a different PDA
+        let outsider = Keypair::new();
+        let mut outsider_ix = create_set_spoiler_flag_ix(outsider.pubkey(), program_id, "Psycho", true);
+        outsider_ix.accounts[1] = AccountMeta::new(review_pda, false);
+        let mut transaction = Transaction::new_with_payer(&[outsider_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &outsider], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidPDA)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_comment_reward_path() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        // The commenter is not the review author and pays for their own comment account
+        let commenter = Keypair::new();
+        let fund_commenter_ix = system_instruction::transfer(&payer.pubkey(), &commenter.pubkey(), LAMPORTS_PER_SOL);
+        let (comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let init_commenter_ata_ix = create_associated_token_account(&payer.pubkey(), &commenter.pubkey(), &comment_mint);
+        let (review_pda, counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Stalker", 5, "The Zone"
+        );
+        assert_matches!(
+            process_instructions(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &[fund_commenter_ix, init_comment_mint_ix, init_commenter_ata_ix, add_review_ix]
+            ).await,
+            Ok(_)
+        );
+
+        let (comment_pda, add_comment_ix) = create_add_comment_ix(commenter.pubkey(), program_id, review_pda, 0, "Go to the room");
+        let mut transaction = Transaction::new_with_payer(&[add_comment_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &commenter], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+
+        let comment_account = banks_client.get_account(comment_pda).await.unwrap().unwrap();
+        let comment = try_from_slice_unchecked::<MovieComment>(&comment_account.data).unwrap();
+        assert_eq!(comment.comment, "Go to the room");
+        assert_eq!(comment.commenter, commenter.pubkey());
+        assert_eq!(comment.review, review_pda);
+
+        let counter_account = banks_client.get_account(counter_pda).await.unwrap().unwrap();
+        let counter = try_from_slice_unchecked::<MovieCommentCounter>(&counter_account.data).unwrap();
+        assert_eq!(counter.counter, 1);
+
+        let commenter_ata = get_associated_token_address(&commenter.pubkey(), &comment_mint);
+        let ata_account = banks_client.get_account(commenter_ata).await.unwrap().unwrap();
+        assert_eq!(spl_token::state::Account::unpack(&ata_account.data).unwrap().amount, 5 * 10u64.pow(9));
+
+        // The commenter has to sign for their own comment
+        let (_comment_pda, mut unsigned_ix) = create_add_comment_ix(commenter.pubkey(), program_id, review_pda, 1, "Unsigned");
+        unsigned_ix.accounts[0].is_signer = false;
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[unsigned_ix]).await.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_configurable_rating_scale() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+
+        let (mint, _mint_auth, init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        let (config_pda, init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, 0, Some(10), None);
+        let init_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_config_ix, init_mint_ix, init_ata_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_config(&mut banks_client, config_pda).await.max_rating, 10);
+
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Oldboy", 8, "The hallway fight"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_review(&mut banks_client, review_pda).await.rating, 8);
+
+        let (_review_pda, _counter_pda, too_high_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Mother", 11, "Off the scale"
+        );
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[too_high_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidRating)
+        );
+
+        // Updates use the same scale
+        let update_ix = create_update_review_ix(payer.pubkey(), program_id, "Oldboy", 10, "The hallway fight, again");
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[update_ix]).await,
+            Ok(_)
+        );
+        let too_high_update_ix = create_update_review_ix(payer.pubkey(), program_id, "Oldboy", 11, "Off the scale");
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[too_high_update_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidRating)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_rejects_uninitialized_review() {
+        let program_id = Pubkey::new_unique();
+        let author = Keypair::new();
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.pubkey().as_ref(), b"Blank"], &program_id);
+
+        // Program owned but never written, as if creation had been interrupted
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_account(review_pda, Account {
+            lamports: Rent::default().minimum_balance(1000),
+            data: vec![0; 1000],
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        });
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(program_test).await;
+
+        let update_ix = create_update_review_ix(author.pubkey(), program_id, "Blank", 4, "Filled in");
+        let mut transaction = Transaction::new_with_payer(&[update_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &author], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::UninitializedAccount)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_add_comments() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let init_comment_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &comment_mint);
+        let (review_pda, counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Brazil", 5, "Ducts everywhere"
+        );
+        let (_comment_pda, add_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "First!");
+        // Picks up after the single comment at index 0
+        let (comment_pdas, batch_ix) = create_batch_add_comments_ix(
+            payer.pubkey(), program_id, review_pda, 1, &["Imported one", "Imported two", "Imported three"]
+        );
+        assert_matches!(
+            process_instructions(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &[init_comment_mint_ix, init_comment_ata_ix, add_review_ix, add_comment_ix, batch_ix]
+            ).await,
+            Ok(_)
+        );
+
+        assert_eq!(get_counter(&mut banks_client, counter_pda).await.counter, 4);
+        for (index, (comment_pda, text)) in comment_pdas.iter().zip(["Imported one", "Imported two", "Imported three"]).enumerate() {
+            let comment = get_comment(&mut banks_client, *comment_pda).await;
+            assert_eq!(comment.comment, text);
+            assert_eq!(comment.count, index as u64 + 1);
+            assert!(comment.rewarded);
+        }
+        let comment_ata = get_associated_token_address(&payer.pubkey(), &comment_mint);
+        assert_eq!(token_balance(&mut banks_client, comment_ata).await, 20 * LAMPORTS_PER_SOL);
+
+        let oversized = ["a"; MovieComment::MAX_BATCH + 1];
+        let (_comment_pdas, oversized_ix) = create_batch_add_comments_ix(payer.pubkey(), program_id, review_pda, 4, &oversized);
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[oversized_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::BatchTooLarge)
+        );
+
+        // Out of order PDAs are rejected
+        let (_comment_pdas, mut swapped_ix) = create_batch_add_comments_ix(payer.pubkey(), program_id, review_pda, 4, &["x", "y"]);
+        let last = swapped_ix.accounts.len() - 1;
+        swapped_ix.accounts.swap(last - 1, last);
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[swapped_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidPDA)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_freeze_account_blocks_rewards() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+
+        let (mint, mint_auth, mut init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        init_mint_ix.data = vec![MovieInstruction::TAG_INITIALIZE_MINT];
+        init_mint_ix.data.append(&mut true.try_to_vec().unwrap());
+        let (_config_pda, init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, 0, None, None);
+        let init_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint);
+        let freeze_ix = create_freeze_account_ix(payer.pubkey(), program_id, payer.pubkey());
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_config_ix, init_mint_ix, init_ata_ix, freeze_ix]).await,
+            Ok(_)
+        );
+
+        let mint_account = banks_client.get_account(mint).await.unwrap().unwrap();
+        let mint_state = spl_token::state::Mint::unpack(&mint_account.data).unwrap();
+        assert_eq!(mint_state.freeze_authority, COption::Some(mint_auth));
+
+        let ata_account = banks_client.get_account(get_associated_token_address(&payer.pubkey(), &mint)).await.unwrap().unwrap();
+        assert!(spl_token::state::Account::unpack(&ata_account.data).unwrap().is_frozen());
+
+        // The review reward can no longer be claimed into the frozen account
+        let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Chinatown", 5, "Forget it, Jake"
+        );
+        let claim_ix = create_claim_rewards_ix(payer.pubkey(), program_id);
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix, claim_ix]).await.unwrap_err().unwrap(),
+            TransactionError::InstructionError(1, InstructionError::Custom(spl_token::error::TokenError::AccountFrozen as u32))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrate_review_from_old_layout() {
+        let program_id = Pubkey::new_unique();
+        let author = Keypair::new();
+        let (review_pda, old_account) = create_old_review_account(program_id, author.pubkey(), "Vertigo");
+        let (other_pda, other_account) = create_old_review_account(program_id, author.pubkey(), "Rope");
+
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_account(review_pda, old_account);
+        program_test.add_account(other_pda, other_account);
+        program_test.add_account(author.pubkey(), Account::new(LAMPORTS_PER_SOL, 0, &SYSTEM_PROGRAM_ID));
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(program_test).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        // Strangers can't migrate someone else's review
+        let stranger = Keypair::new();
+        let stranger_ix = create_migrate_review_ix(stranger.pubkey(), program_id, author.pubkey(), "Vertigo");
+        let mut transaction = Transaction::new_with_payer(&[stranger_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &stranger], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::IncorrectAccountError)
+        );
+
+        // The author migrates one review, the admin (payer) the other
+        let migrate_ix = create_migrate_review_ix(author.pubkey(), program_id, author.pubkey(), "Vertigo");
+        let mut transaction = Transaction::new_with_payer(&[migrate_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &author], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+
+        let admin_ix = create_migrate_review_ix(payer.pubkey(), program_id, author.pubkey(), "Rope");
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[admin_ix]).await,
+            Ok(_)
+        );
+
+        let review_account = banks_client.get_account(review_pda).await.unwrap().unwrap();
+        assert_eq!(review_account.data.len(), MovieAccountState::ACCOUNT_LEN);
+        assert!(review_account.lamports >= Rent::default().minimum_balance(MovieAccountState::ACCOUNT_LEN));
+
+        let review = get_review(&mut banks_client, review_pda).await;
+        let (_pda, bump_seed) = Pubkey::find_program_address(&[author.pubkey().as_ref(), b"Vertigo"], &program_id);
+        assert_eq!(
+            review,
+            MovieAccountState::new(author.pubkey(), "Vertigo".to_string(), 4, "Written before versioning".to_string(), bump_seed)
+        );
+        assert_eq!(get_review(&mut banks_client, other_pda).await.version, MovieAccountState::VERSION);
+
+        // Migrated reviews go through the normal update path
+        let update_ix = create_update_review_ix(author.pubkey(), program_id, "Vertigo", 5, "Better every time");
+        let mut transaction = Transaction::new_with_payer(&[update_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &author], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+        assert_eq!(get_review(&mut banks_client, review_pda).await.description, "Better every time");
+
+        // A review already in the current layout can't be migrated again
+        let again_ix = create_migrate_review_ix(payer.pubkey(), program_id, author.pubkey(), "Rope");
+        let new_blockhash = banks_client.get_new_latest_blockhash(&recent_blockhash).await.unwrap();
+        assert!(process_instructions(&mut banks_client, &payer, new_blockhash, &[again_ix]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_review_reward_cooldown() {
+        let program_id = Pubkey::new_unique();
+        let mut context = start_with_context_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        let (payer, recent_blockhash) = (context.payer.pubkey(), context.last_blockhash);
+        let mint = setup_mint(&mut context.banks_client, &context.payer, recent_blockhash, program_id).await;
+        let user_ata = get_associated_token_address(&payer, &mint);
+
+        let (_review_pda, _counter_pda, first_review_ix) = create_add_review_ix(
+            payer, program_id, "Heat", 5, "Coffee with De Niro"
+        );
+        let (second_pda, _counter_pda, second_review_ix) = create_add_review_ix(
+            payer, program_id, "Collateral", 4, "Cab ride"
+        );
+        let claim_ix = create_claim_rewards_ix(payer, program_id);
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[first_review_ix, second_review_ix, claim_ix]).await,
+            Ok(_)
+        );
+
+        // The second review was still created, just without a reward
+        assert!(get_review(&mut context.banks_client, second_pda).await.is_initialized);
+        assert_eq!(token_balance(&mut context.banks_client, user_ata).await, 20 * LAMPORTS_PER_SOL);
+        let profile = get_profile(&mut context.banks_client, payer, program_id).await;
+        assert_eq!(profile.review_count, 2);
+        assert_eq!(profile.total_rewards, 20 * LAMPORTS_PER_SOL);
+
+        // Once the cooldown has passed the next review is rewarded again
+        advance_clock(&mut context, AuthorProfile::REWARD_COOLDOWN).await;
+        let (_review_pda, _counter_pda, third_review_ix) = create_add_review_ix(
+            payer, program_id, "Thief", 4, "Safecracking"
+        );
+        let claim_ix = create_claim_rewards_ix(payer, program_id);
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[third_review_ix, claim_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(token_balance(&mut context.banks_client, user_ata).await, 40 * LAMPORTS_PER_SOL);
+        let profile = get_profile(&mut context.banks_client, payer, program_id).await;
+        assert_eq!(profile.review_count, 3);
+        assert_eq!(profile.total_rewards, 40 * LAMPORTS_PER_SOL);
+    }
+
+    #[tokio::test]
+    async fn test_rewards_require_existing_ata() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+
+        // Mint and config, but no associated token account for the payer
+        let (_mint, _mint_auth, init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        let (_config_pda, init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, TEST_SLASH_AMOUNT, None, None);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_config_ix, init_mint_ix]).await,
+            Ok(_)
+        );
+
+        // The review itself goes through, the reward just waits in the profile
+        let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Brazil", 4, "Ducts everywhere"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+
+        let claim_ix = create_claim_rewards_ix(payer.pubkey(), program_id);
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[claim_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::AssociatedTokenAccountMissing)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_movie_aggregate_averages_ratings() {
+        let program_id = Pubkey::new_unique();
+        let other_author = Keypair::new();
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_account(other_author.pubkey(), Account::new(LAMPORTS_PER_SOL, 0, &SYSTEM_PROGRAM_ID));
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(program_test).await;
+        let mint = setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        // Two authors review the same movie, spelled slightly differently
+        let (_review_pda, _counter_pda, first_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Jaws", 5, "Bigger boat"
+        );
+        let (_review_pda, _counter_pda, second_review_ix) = create_add_review_ix(
+            other_author.pubkey(), program_id, " jaws", 2, "Fake shark"
+        );
+        let init_ata_ix = create_associated_token_account(&payer.pubkey(), &other_author.pubkey(), &mint);
+        let mut transaction = Transaction::new_with_payer(
+            &[first_review_ix, init_ata_ix, second_review_ix],
+            Some(&payer.pubkey())
+        );
+        transaction.sign(&[&payer, &other_author], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+
+        let aggregate_pda = get_aggregate_pda(program_id, "Jaws");
+        assert_eq!(aggregate_pda, get_aggregate_pda(program_id, " jaws"));
+        let account = banks_client.get_account(aggregate_pda).await.unwrap().unwrap();
+        let aggregate = try_from_slice_unchecked::<MovieAggregate>(&account.data).unwrap();
+        assert_eq!(aggregate.title_hash, MovieAggregate::title_hash("JAWS"));
+        assert_eq!(aggregate.review_count, 2);
+        assert_eq!(aggregate.rating_sum, 7);
+        assert_eq!(aggregate.rating_sum as f64 / aggregate.review_count as f64, 3.5);
+        assert_eq!(aggregate.rating_counts, [0, 1, 0, 0, 1]);
+
+        // A mismatched aggregate account is rejected
+        let (_review_pda, _counter_pda, mut bad_aggregate_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Jaws 2", 3, "Just when you thought"
+        );
+        bad_aggregate_ix.accounts[9].pubkey = aggregate_pda;
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[bad_aggregate_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidPDA)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tip_reviewer_splits_with_treasury() {
+        let program_id = Pubkey::new_unique();
+        let tipper = Keypair::new();
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_account(tipper.pubkey(), Account::new(10 * LAMPORTS_PER_SOL, 0, &SYSTEM_PROGRAM_ID));
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(program_test).await;
+        let (treasury_pda, _bump_seed) = Pubkey::find_program_address(&[b"treasury"], &program_id);
+
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Fargo", 5, "Oh yah"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+
+        // The tipper pays the fees, so the author's balance moves by exactly their share
+        let rent_floor = banks_client.get_rent().await.unwrap().minimum_balance(0);
+        let mut author_expected = banks_client.get_balance(payer.pubkey()).await.unwrap();
+        let mut treasury_expected = rent_floor;
+        for (amount, fee) in [(1_000_000, 50_000), (1_019, 50), (20, 1)] {
+            let tip_ix = create_tip_reviewer_ix(tipper.pubkey(), program_id, review_pda, payer.pubkey(), amount);
+            let mut transaction = Transaction::new_with_payer(&[tip_ix], Some(&tipper.pubkey()));
+            transaction.sign(&[&tipper], recent_blockhash);
+            assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+
+            author_expected += amount - fee;
+            treasury_expected += fee;
+            assert_eq!(banks_client.get_balance(payer.pubkey()).await.unwrap(), author_expected);
+            assert_eq!(banks_client.get_balance(treasury_pda).await.unwrap(), treasury_expected);
+        }
+
+        let dust_ix = create_tip_reviewer_ix(tipper.pubkey(), program_id, review_pda, payer.pubkey(), 19);
+        let mut transaction = Transaction::new_with_payer(&[dust_ix], Some(&tipper.pubkey()));
+        transaction.sign(&[&tipper], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidTipAmount)
+        );
+
+        // Tips can't be redirected away from the author
+        let misdirected_ix = create_tip_reviewer_ix(tipper.pubkey(), program_id, review_pda, tipper.pubkey(), 1_000);
+        let mut transaction = Transaction::new_with_payer(&[misdirected_ix], Some(&tipper.pubkey()));
+        transaction.sign(&[&tipper], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::IncorrectAccountError)
+        );
+    }
+
+    #[test]
+    fn test_comment_reward_halves_every_ten_comments() {
+        assert_eq!(comment_reward(0), 5);
+        assert_eq!(comment_reward(9), 5);
+        assert_eq!(comment_reward(10), 2);
+        assert_eq!(comment_reward(20), 1);
+        assert_eq!(comment_reward(100), 1);
+        assert_eq!(comment_reward(u64::MAX), 1);
+    }
+
+    // Pins the addresses clients derive for a fixed program and author. A change to
+    // any seed scheme, such as normalizing titles, moves these and must be deliberate.
+    #[test]
+    fn test_pda_derivation_is_stable() {
+        let program_id = Pubkey::new_from_array([7; 32]);
+        let author = Pubkey::new_from_array([42; 32]);
+        let expected = |address: &str| address.parse::<Pubkey>().unwrap();
+
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), b"Inception"], &program_id);
+        assert_eq!(review_pda, expected("26Jh2si5Bop6JstS9x1c4A54xoaszdZx4SpuyruNdaEY"));
+
+        let (counter_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), b"comment"], &program_id);
+        assert_eq!(counter_pda, expected("JDJ1eX5DTiNcmTy96WnQysvvpUn7EoFYSU8bYwHQHwEg"));
+
+        let (comment_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), &0u64.to_be_bytes()], &program_id);
+        assert_eq!(comment_pda, expected("36SJeywWWisoLo1skbLDc5tXk2mSevg3wHTDCiE69LE7"));
+
+        let (mint_pda, _bump_seed) = Pubkey::find_program_address(&[b"token_mint"], &program_id);
+        assert_eq!(mint_pda, expected("BLbn9fXHvbFNZu7xPGmwoXDcPihqqvmTUaQbsWjt2vRh"));
+
+        let (mint_auth_pda, _bump_seed) = Pubkey::find_program_address(&[b"token_auth"], &program_id);
+        assert_eq!(mint_auth_pda, expected("HRDHZvDw8NqpsDnGm6fz38oNvKj1p3wuR5Rsjnb3uVHN"));
+    }
+
+    #[tokio::test]
+    async fn test_add_comment_without_counter() {
+        let program_id = Pubkey::new_unique();
+        let author = Pubkey::new_unique();
+        let (orphan_pda, orphan_account) = create_review_account(program_id, author, "Solaris", MovieAccountState::VERSION);
+
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_account(orphan_pda, orphan_account);
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(program_test).await;
+
+        // The counter PDA was never created, so it is an empty system account
+        let (_comment_pda, add_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, orphan_pda, 0, "Ocean of memory");
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_comment_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::CounterNotInitialized)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_genre_changes_only_genre() {
+        let program_id = Pubkey::new_unique();
+        let author = Keypair::new();
+
+        // A drama review whose account is sized exactly to its contents
+        let (review_pda, mut review_account) = create_review_account(program_id, author.pubkey(), "Heat", MovieAccountState::VERSION);
+        let mut review = try_from_slice_unchecked::<MovieAccountState>(&review_account.data).unwrap();
+        review.discriminator = MovieAccountState::DISCRIMINATOR_V2.to_string();
+        review.genre = "drama".to_string();
+        review_account.data = review.try_to_vec().unwrap();
+        review_account.lamports = Rent::default().minimum_balance(review_account.data.len());
+
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_account(review_pda, review_account);
+        program_test.add_account(author.pubkey(), Account::new(LAMPORTS_PER_SOL, 0, &SYSTEM_PROGRAM_ID));
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(program_test).await;
+
+        let set_genre_ix = create_set_genre_ix(author.pubkey(), program_id, "Heat", "action");
+        let mut transaction = Transaction::new_with_payer(&[set_genre_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &author], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+
+        let updated = get_review(&mut banks_client, review_pda).await;
+        assert_eq!(updated.genre, "action");
+        assert_eq!(updated.rating, review.rating);
+        assert_eq!(updated.description, review.description);
+        assert_eq!(updated.title, review.title);
+
+        // A longer genre grows the account and keeps it rent exempt
+        let set_genre_ix = create_set_genre_ix(author.pubkey(), program_id, "Heat", "documentary");
+        let mut transaction = Transaction::new_with_payer(&[set_genre_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &author], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+
+        let account = banks_client.get_account(review_pda).await.unwrap().unwrap();
+        let updated = try_from_slice_unchecked::<MovieAccountState>(&account.data).unwrap();
+        assert_eq!(updated.genre, "documentary");
+        assert_eq!(account.data.len(), updated.serialized_len());
+        assert_eq!(account.lamports, Rent::default().minimum_balance(account.data.len()));
+
+        let bad_genre_ix = create_set_genre_ix(author.pubkey(), program_id, "Heat", "musical");
+        let mut transaction = Transaction::new_with_payer(&[bad_genre_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &author], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidGenre)
+        );
+
+        // Nobody but the author can recategorize
+        let mut stranger_ix = create_set_genre_ix(payer.pubkey(), program_id, "Heat", "comedy");
+        stranger_ix.accounts[1].pubkey = review_pda;
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[stranger_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidPDA)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_log_comment_by_index() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let init_comment_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &comment_mint);
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Stalker", 5, "The Zone"
+        );
+        let (_comment_pda, add_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "What do you wish for");
+        assert_matches!(
+            process_instructions(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &[init_comment_mint_ix, init_comment_ata_ix, add_review_ix, add_comment_ix]
+            ).await,
+            Ok(_)
+        );
+
+        // As with CheckReviewExists, the msg! line stands in for sol_log_data here
+        let log_ix = create_log_comment_ix(program_id, payer.pubkey(), "Stalker", 0);
+        let transaction = Transaction::new_signed_with_payer(&[log_ix], Some(&payer.pubkey()), &[&payer], recent_blockhash);
+        let simulation = banks_client.simulate_transaction(transaction).await.unwrap();
+        assert_matches!(simulation.result, Some(Ok(())));
+        let logs: Vec<String> = simulation.simulation_details.unwrap().logs
+            .into_iter()
+            .filter(|log| log.starts_with("Program log: Comment 0"))
+            .collect();
+        assert_eq!(logs, vec![format!("Program log: Comment 0 by {}: What do you wish for", payer.pubkey())]);
+
+        // Only indices below the counter are valid
+        let out_of_range_ix = create_log_comment_ix(program_id, payer.pubkey(), "Stalker", 1);
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[out_of_range_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidPDA)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_react_to_comment_counts_each_kind() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let init_comment_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &comment_mint);
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Playtime", 5, "Glass and steel"
+        );
+        let (comment_pda, add_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "Hulot at his best");
+        let reactor = Keypair::new();
+        let fund_reactor_ix = system_instruction::transfer(&payer.pubkey(), &reactor.pubkey(), LAMPORTS_PER_SOL);
+        assert_matches!(
+            process_instructions(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &[init_comment_mint_ix, init_comment_ata_ix, add_review_ix, add_comment_ix, fund_reactor_ix]
+            ).await,
+            Ok(_)
+        );
+
+        // Like from the payer, laugh from someone else
+        let (_comment_pda, like_ix) = create_react_ix(payer.pubkey(), program_id, payer.pubkey(), "Playtime", 0, 0);
+        let (_comment_pda, laugh_ix) = create_react_ix(reactor.pubkey(), program_id, payer.pubkey(), "Playtime", 0, 2);
+        let mut transaction = Transaction::new_with_payer(&[like_ix, laugh_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &reactor], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+
+        let comment = get_comment(&mut banks_client, comment_pda).await;
+        assert_eq!(comment.reactions, [1, 0, 1, 0]);
+        assert_eq!(comment.comment, "Hulot at his best");
+        let (reaction_pda, reaction_bump) = Pubkey::find_program_address(&[comment_pda.as_ref(), reactor.pubkey().as_ref(), b"react"], &program_id);
+        let account = banks_client.get_account(reaction_pda).await.unwrap().unwrap();
+        let marker = try_from_slice_unchecked::<ReactionMarker>(&account.data).unwrap();
+        assert_eq!(marker.discriminator, ReactionMarker::DISCRIMINATOR);
+        assert_eq!((marker.bump, marker.reaction_kind), (reaction_bump, 2));
+
+        // One reaction per reactor, whatever the kind
+        let (_comment_pda, love_ix) = create_react_ix(payer.pubkey(), program_id, payer.pubkey(), "Playtime", 0, 1);
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[love_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::AlreadyReacted)
+        );
+
+        let (_comment_pda, unknown_ix) = create_react_ix(reactor.pubkey(), program_id, payer.pubkey(), "Playtime", 0, 4);
+        let mut transaction = Transaction::new_with_payer(&[unknown_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &reactor], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidReactionKind)
+        );
+        assert_eq!(get_comment(&mut banks_client, comment_pda).await.reactions, [1, 0, 1, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_review_limit_per_author() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+
+        let (mint, _mint_auth, init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        let (config_pda, init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, 0, None, Some(2));
+        let init_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_config_ix, init_mint_ix, init_ata_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_config(&mut banks_client, config_pda).await.max_reviews_per_author, 2);
+
+        for title in ["Ran", "Kagemusha"] {
+            let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+                payer.pubkey(), program_id, title, 5, "Kurosawa in colour"
+            );
+            assert_matches!(
+                process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+                Ok(_)
+            );
+        }
+
+        let (review_pda, _counter_pda, over_limit_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Dreams", 5, "One too many"
+        );
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[over_limit_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::ReviewLimitReached)
+        );
+        assert_eq!(banks_client.get_account(review_pda).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_log_config_reports_admin_and_supply() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let log_config_ix = Instruction {
+            program_id,
+            accounts: vec![AccountMeta::new_readonly(config_pda, false)],
+            data: vec![MovieInstruction::TAG_LOG_CONFIG],
+        };
+
+        let transaction = Transaction::new_signed_with_payer(std::slice::from_ref(&log_config_ix), Some(&payer.pubkey()), &[&payer], recent_blockhash);
+        let simulation = banks_client.simulate_transaction(transaction).await.unwrap();
+        assert_eq!(
+            simulation.result,
+            Some(Err(review_error(0, ReviewError::UninitializedAccount)))
+        );
+
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+        let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Umberto D.", 5, "And his dog"
+        );
+        let claim_ix = create_claim_rewards_ix(payer.pubkey(), program_id);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix, claim_ix]).await,
+            Ok(_)
+        );
+
+        let transaction = Transaction::new_signed_with_payer(&[log_config_ix], Some(&payer.pubkey()), &[&payer], recent_blockhash);
+        let simulation = banks_client.simulate_transaction(transaction).await.unwrap();
+        assert_matches!(simulation.result, Some(Ok(())));
+        let logs = simulation.simulation_details.unwrap().logs;
+        assert!(logs.contains(&format!("Program log: Config admin: {}", payer.pubkey())));
+        assert!(logs.contains(&format!(
+            "Program log: Total minted: {} of {}", FIRST_REVIEWER_MULTIPLIER * REVIEW_REWARD * LAMPORTS_PER_SOL, ProgramConfig::MAX_SUPPLY_TOKENS * LAMPORTS_PER_SOL
+        )));
+        assert!(logs.contains(&format!("Program log: Max rating: {}", ProgramConfig::DEFAULT_MAX_RATING)));
+    }
+
+    #[tokio::test]
+    async fn test_display_title_kept_apart_from_seed() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        // The seed is the client's ASCII form; the display title follows the create_ata flag
+        let (review_pda, _counter_pda, mut add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "amelie", 5, "Garden gnome travels"
+        );
+        add_review_ix.data.append(&mut (false, Some("Le Fabuleux Destin d'Amélie Poulain 🍮")).try_to_vec().unwrap());
+        let (other_pda, _counter_pda, plain_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Delicatessen", 4, "Butcher upstairs"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix, plain_ix]).await,
+            Ok(_)
+        );
+
+        let review = get_review(&mut banks_client, review_pda).await;
+        assert_eq!(review.title, "amelie");
+        assert_eq!(review.display_title, "Le Fabuleux Destin d'Amélie Poulain 🍮");
+
+        // Without one, the seed title doubles as the display title
+        assert_eq!(get_review(&mut banks_client, other_pda).await.display_title, "Delicatessen");
+    }
+
+    #[tokio::test]
+    async fn test_update_size_check_matches_add() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        // A long display title leaves room for exactly 745 description bytes in the
+        // 1024 byte account; the old update arithmetic skipped most fields and let more through
+        let title = "The Discreet Charm of the Bourge";
+        let display_title = "The Discreet Charm of the Bourgeoisie (Le Charme discret de la bourgeoisie), Buñuel, 72";
+        let (review_pda, _counter_pda, mut add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, title, 5, "Dinner, interrupted"
+        );
+        add_review_ix.data.append(&mut (false, Some(display_title)).try_to_vec().unwrap());
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+
+        let longest = "d".repeat(MovieAccountState::ACCOUNT_LEN - MovieAccountState::get_account_size(title, display_title, "", ""));
+        assert_eq!(longest.len(), 745);
+        let update_ix = create_update_review_ix(payer.pubkey(), program_id, title, 4, &longest);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[update_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_review(&mut banks_client, review_pda).await.description, longest);
+
+        let too_long_ix = create_update_review_ix(payer.pubkey(), program_id, title, 4, &format!("{}d", longest));
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[too_long_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidDataLength)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_description_and_rating_separately() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Yi Yi", 4, "A family, three hours"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+
+        // Same accounts as UpdateMovieReview, with a narrower payload
+        let mut description_ix = create_update_review_ix(payer.pubkey(), program_id, "Yi Yi", 0, "");
+        description_ix.data = vec![MovieInstruction::TAG_UPDATE_DESCRIPTION];
+        description_ix.data.append(&mut ("Yi Yi", "A family, three hours, no wasted minute").try_to_vec().unwrap());
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[description_ix]).await,
+            Ok(_)
+        );
+        let review = get_review(&mut banks_client, review_pda).await;
+        assert_eq!(review.rating, 4);
+        assert_eq!(review.description, "A family, three hours, no wasted minute");
+
+        let mut rating_ix = create_update_review_ix(payer.pubkey(), program_id, "Yi Yi", 0, "");
+        rating_ix.data = vec![MovieInstruction::TAG_UPDATE_RATING];
+        rating_ix.data.append(&mut ("Yi Yi", 5u8).try_to_vec().unwrap());
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[rating_ix]).await,
+            Ok(_)
+        );
+        let review = get_review(&mut banks_client, review_pda).await;
+        assert_eq!(review.rating, 5);
+        assert_eq!(review.description, "A family, three hours, no wasted minute");
+
+        // The rating is still checked against the configured scale
+        let mut too_high_ix = create_update_review_ix(payer.pubkey(), program_id, "Yi Yi", 0, "");
+        too_high_ix.data = vec![MovieInstruction::TAG_UPDATE_RATING];
+        too_high_ix.data.append(&mut ("Yi Yi", 6u8).try_to_vec().unwrap());
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[too_high_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidRating)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_comment_returns_its_index() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let init_comment_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &comment_mint);
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Ikiru", 5, "The swing scene"
+        );
+        let (_comment_pda, first_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "Life is brief");
+        assert_matches!(
+            process_instructions(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &[init_comment_mint_ix, init_comment_ata_ix, add_review_ix, first_comment_ix]
+            ).await,
+            Ok(_)
+        );
+
+        let (_comment_pda, second_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 1, "Fall in love, maidens");
+        let transaction = Transaction::new_signed_with_payer(&[second_comment_ix], Some(&payer.pubkey()), &[&payer], recent_blockhash);
+        let simulation = banks_client.simulate_transaction(transaction).await.unwrap();
+        assert_matches!(simulation.result, Some(Ok(())));
+        let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+        assert_eq!(return_data.program_id, program_id);
+        // The runtime trims trailing zero bytes, so clients pad back to 8
+        let mut index = return_data.data;
+        index.resize(8, 0);
+        assert_eq!(u64::from_le_bytes(index.try_into().unwrap()), 1);
+    }
+
+    #[tokio::test]
+    async fn test_create_token_metadata() {
+        let program_id = Pubkey::new_unique();
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_program("token_metadata_stub", TOKEN_METADATA_PROGRAM_ID, processor!(token_metadata_stub));
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(program_test).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let mut wrong_pda_ix = create_token_metadata_ix(payer.pubkey(), program_id, "Movie Review Token");
+        wrong_pda_ix.accounts[4].pubkey = Pubkey::new_unique();
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[wrong_pda_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidPDA)
+        );
+
+        let mut wrong_program_ix = create_token_metadata_ix(payer.pubkey(), program_id, "Movie Review Token");
+        wrong_program_ix.accounts[5].pubkey = Pubkey::new_unique();
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[wrong_program_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::IncorrectAccountError)
+        );
+
+        // Only the config admin may name the token
+        let outsider = Keypair::new();
+        let outsider_ix = create_token_metadata_ix(outsider.pubkey(), program_id, "Movie Review Token");
+        let mut transaction = Transaction::new_with_payer(&[outsider_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &outsider], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::IncorrectAccountError)
+        );
+
+        let metadata_ix = create_token_metadata_ix(payer.pubkey(), program_id, "Movie Review Token");
+        let transaction = Transaction::new_signed_with_payer(&[metadata_ix], Some(&payer.pubkey()), &[&payer], recent_blockhash);
+        let simulation = banks_client.simulate_transaction(transaction).await.unwrap();
+        assert_matches!(simulation.result, Some(Ok(())));
+        assert!(simulation.simulation_details.unwrap().logs.contains(&"Program log: Metadata name: Movie Review Token".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_reward_cooldown_boundary() {
+        let program_id = Pubkey::new_unique();
+        let mut context = start_with_context_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        let (payer, recent_blockhash) = (context.payer.pubkey(), context.last_blockhash);
+        let mint = setup_mint(&mut context.banks_client, &context.payer, recent_blockhash, program_id).await;
+        let user_ata = get_associated_token_address(&payer, &mint);
+
+        let (_review_pda, _counter_pda, first_review_ix) = create_add_review_ix(
+            payer, program_id, "Alphaville", 4, "Lemmy Caution"
+        );
+        let claim_ix = create_claim_rewards_ix(payer, program_id);
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[first_review_ix, claim_ix]).await,
+            Ok(_)
+        );
+
+        // One second short of the cooldown: created, not rewarded
+        advance_clock(&mut context, AuthorProfile::REWARD_COOLDOWN - 1).await;
+        let (_review_pda, _counter_pda, early_review_ix) = create_add_review_ix(
+            payer, program_id, "Pierrot le Fou", 4, "Blue face"
+        );
+        let claim_ix = create_claim_rewards_ix(payer, program_id);
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[early_review_ix, claim_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(token_balance(&mut context.banks_client, user_ata).await, FIRST_REVIEWER_MULTIPLIER * REVIEW_REWARD * LAMPORTS_PER_SOL);
+
+        // The cooldown runs from the last rewarded review, so one more second is enough
+        advance_clock(&mut context, 1).await;
+        let (_review_pda, _counter_pda, on_time_review_ix) = create_add_review_ix(
+            payer, program_id, "Contempt", 5, "Capri"
+        );
+        let claim_ix = create_claim_rewards_ix(payer, program_id);
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[on_time_review_ix, claim_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(token_balance(&mut context.banks_client, user_ata).await, 2 * FIRST_REVIEWER_MULTIPLIER * REVIEW_REWARD * LAMPORTS_PER_SOL);
+    }
+
+    #[test]
+    fn test_close_account_moves_lamports_and_zeroes_data() {
+        let program_id = Pubkey::new_unique();
+        let system_program = SYSTEM_PROGRAM_ID;
+        let (account_key, recipient_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mut account_lamports, mut recipient_lamports) = (2_000_000, 500);
+        let mut account_data = vec![7u8; 64];
+        let mut recipient_data = vec![];
+        let account = AccountInfo::new(
+            &account_key, false, true, &mut account_lamports, &mut account_data, &program_id, false, 0,
+        );
+        let recipient = AccountInfo::new(
+            &recipient_key, true, true, &mut recipient_lamports, &mut recipient_data, &system_program, false, 0,
+        );
+
+        close_account(&account, &recipient).unwrap();
+        assert_eq!(account.lamports(), 0);
+        assert_eq!(recipient.lamports(), 2_000_500);
+        assert!(account.data.borrow().iter().all(|&b| b == 0));
+
+        // Closing into itself would burn the lamports
+        assert_eq!(
+            close_account(&recipient, &recipient),
+            Err(ReviewError::IncorrectAccountError.into())
+        );
+        assert_eq!(recipient.lamports(), 2_000_500);
+    }
+
+    #[tokio::test]
+    async fn test_update_moves_rating_between_buckets() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        async fn get_aggregate(banks_client: &mut BanksClient, aggregate_pda: Pubkey) -> MovieAggregate {
+            let account = banks_client.get_account(aggregate_pda).await.unwrap().unwrap();
+            try_from_slice_unchecked::<MovieAggregate>(&account.data).unwrap()
+        }
+
+        let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Heat", 5, "The diner scene"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+        let aggregate_pda = get_aggregate_pda(program_id, "Heat");
+        assert_eq!(get_aggregate(&mut banks_client, aggregate_pda).await.rating_counts, [0, 0, 0, 0, 1]);
+
+        let update_ix = create_update_review_ix(payer.pubkey(), program_id, "Heat", 3, "The diner scene, and not much else");
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[update_ix]).await,
+            Ok(_)
+        );
+        let aggregate = get_aggregate(&mut banks_client, aggregate_pda).await;
+        assert_eq!(aggregate.rating_counts, [0, 0, 1, 0, 0]);
+        assert_eq!(aggregate.review_count, 1);
+        assert_eq!(aggregate.rating_sum, 3);
+
+        // A rating update needs the movie's own aggregate
+        let mut wrong_aggregate_ix = create_update_review_ix(payer.pubkey(), program_id, "Heat", 4, "Reconsidered");
+        wrong_aggregate_ix.accounts[3].pubkey = get_aggregate_pda(program_id, "Thief");
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[wrong_aggregate_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidPDA)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_review_and_comment_reject_wrong_system_program() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (review_pda, _counter_pda, mut spoofed_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Brazil", 5, "Ducts"
+        );
+        spoofed_review_ix.accounts[5].pubkey = Pubkey::new_unique();
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[spoofed_review_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::IncorrectAccountError)
+        );
+
+        let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Brazil", 5, "Ducts"
+        );
+        let (_comment_pda, mut spoofed_comment_ix) = create_add_comment_ix(
+            payer.pubkey(), program_id, review_pda, 0, "Tuttle did nothing wrong"
+        );
+        spoofed_comment_ix.accounts[7].pubkey = Pubkey::new_unique();
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix, spoofed_comment_ix]).await.unwrap_err().unwrap(),
+            review_error(1, ReviewError::IncorrectAccountError)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_index_bucket_counts_reviews_by_first_letter() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (_review_pda, _counter_pda, inception_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Inception", 4, "Spinning top"
+        );
+        let (_review_pda, _counter_pda, ikiru_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "ikiru", 5, "The swing"
+        );
+        let (_review_pda, _counter_pda, heat_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Heat", 5, "The diner scene"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[inception_ix, ikiru_ix, heat_ix]).await,
+            Ok(_)
+        );
+
+        let index_pda = get_index_pda(program_id, "Inception");
+        assert_eq!(index_pda, get_index_pda(program_id, "ikiru"));
+        let account = banks_client.get_account(index_pda).await.unwrap().unwrap();
+        let bucket = try_from_slice_unchecked::<IndexBucket>(&account.data).unwrap();
+        assert_eq!(bucket.key, b'i');
+        assert_eq!(bucket.review_count, 2);
+
+        let account = banks_client.get_account(get_index_pda(program_id, "Heat")).await.unwrap().unwrap();
+        assert_eq!(try_from_slice_unchecked::<IndexBucket>(&account.data).unwrap().review_count, 1);
+
+        // Filing a review under another letter's bucket is rejected
+        let (_review_pda, _counter_pda, mut wrong_index_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Insomnia", 3, "Alaska"
+        );
+        wrong_index_ix.accounts[10].pubkey = get_index_pda(program_id, "Heat");
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[wrong_index_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidPDA)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_initialize_mint_with_decimals_scales_rewards() {
+        for decimals in [0u8, 6] {
+            let program_id = Pubkey::new_unique();
+            let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+                "pda_local",
+                program_id,
+                processor!(process_instruction)
+            )).await;
+
+            let (mint, _mint_auth, mut init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+            init_mint_ix.data = vec![MovieInstruction::TAG_INITIALIZE_MINT, 0, decimals];
+            let (config_pda, init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, 0, None, None);
+            let init_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint);
+            let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+                payer.pubkey(), program_id, "Stalker", 5, "The Zone"
+            );
+            let claim_ix = create_claim_rewards_ix(payer.pubkey(), program_id);
+            assert_matches!(
+                process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_config_ix, init_mint_ix, init_ata_ix, add_review_ix, claim_ix]).await,
+                Ok(_)
+            );
+
+            let mint_account = banks_client.get_account(mint).await.unwrap().unwrap();
+            assert_eq!(spl_token::state::Mint::unpack(&mint_account.data).unwrap().decimals, decimals);
+            let config = get_config(&mut banks_client, config_pda).await;
+            assert_eq!(config.decimals, decimals);
+            let user_ata = get_associated_token_address(&payer.pubkey(), &mint);
+            assert_eq!(token_balance(&mut banks_client, user_ata).await, 20 * 10u64.pow(decimals as u32));
+            assert_eq!(config.total_minted, 20 * 10u64.pow(decimals as u32));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_initialize_mint_rejects_too_many_decimals() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+
+        let (_mint, _mint_auth, mut init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        init_mint_ix.data = vec![MovieInstruction::TAG_INITIALIZE_MINT, 0, ProgramConfig::MAX_DECIMALS + 1];
+        let (_config_pda, init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, 0, None, None);
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_config_ix, init_mint_ix]).await.unwrap_err().unwrap(),
+            review_error(1, ReviewError::InvalidDecimals)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_comment_rejects_missing_review() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (_comment_pda, comment_ix) = create_add_comment_ix(
+            payer.pubkey(), program_id, Pubkey::new_unique(), 0, "Free tokens"
+        );
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[comment_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::ReviewDoesNotExist)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_log_treasury_balance() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        let (treasury_pda, _bump_seed) = Pubkey::find_program_address(&[b"treasury"], &program_id);
+        let log_treasury_ix = Instruction {
+            program_id,
+            accounts: vec![AccountMeta::new_readonly(treasury_pda, false)],
+            data: vec![MovieInstruction::TAG_LOG_TREASURY_BALANCE],
+        };
+
+        async fn simulate_log(banks_client: &mut BanksClient, payer: &Keypair, recent_blockhash: Hash, ix: &Instruction) -> Vec<String> {
+            let transaction = Transaction::new_signed_with_payer(std::slice::from_ref(ix), Some(&payer.pubkey()), &[payer], recent_blockhash);
+            let simulation = banks_client.simulate_transaction(transaction).await.unwrap();
+            assert_matches!(simulation.result, Some(Ok(())));
+            simulation.simulation_details.unwrap().logs
+        }
+
+        // Before any tip there is no treasury, which is reported rather than an error
+        let logs = simulate_log(&mut banks_client, &payer, recent_blockhash, &log_treasury_ix).await;
+        assert!(logs.contains(&"Program log: Treasury does not exist yet; the first tip creates it".to_string()));
+        assert!(logs.contains(&"Program log: Treasury balance: 0 lamports, 0 from fees".to_string()));
+
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Nashville", 4, "Twenty-four leads"
+        );
+        let tipper = Keypair::new();
+        let fund_tipper_ix = system_instruction::transfer(&payer.pubkey(), &tipper.pubkey(), LAMPORTS_PER_SOL);
+        let tip_ix = create_tip_reviewer_ix(tipper.pubkey(), program_id, review_pda, payer.pubkey(), 1_000_000);
+        let mut transaction = Transaction::new_with_payer(&[add_review_ix, fund_tipper_ix, tip_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &tipper], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+
+        let lamports = banks_client.get_balance(treasury_pda).await.unwrap();
+        let logs = simulate_log(&mut banks_client, &payer, recent_blockhash, &log_treasury_ix).await;
+        assert!(logs.contains(&format!("Program log: Treasury balance: {} lamports, 50000 from fees", lamports)));
+
+        // Only the treasury PDA is accepted
+        let mut wrong_account_ix = log_treasury_ix.clone();
+        wrong_account_ix.accounts[0].pubkey = review_pda;
+        let transaction = Transaction::new_signed_with_payer(&[wrong_account_ix], Some(&payer.pubkey()), &[&payer], recent_blockhash);
+        assert_eq!(
+            banks_client.simulate_transaction(transaction).await.unwrap().result,
+            Some(Err(review_error(0, ReviewError::InvalidPDA)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_watch_status() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        let mint = setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+        let user_ata = get_associated_token_address(&payer.pubkey(), &mint);
+
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Persona", 5, "Two faces"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_review(&mut banks_client, review_pda).await.status, MovieAccountState::STATUS_REVIEWED);
+
+        for status in [
+            MovieAccountState::STATUS_WANT_TO_WATCH,
+            MovieAccountState::STATUS_WATCHING,
+            MovieAccountState::STATUS_REVIEWED,
+        ] {
+            let status_ix = create_set_watch_status_ix(payer.pubkey(), program_id, "Persona", status);
+            let new_blockhash = banks_client.get_new_latest_blockhash(&recent_blockhash).await.unwrap();
+            assert_matches!(
+                process_instructions(&mut banks_client, &payer, new_blockhash, &[status_ix]).await,
+                Ok(_)
+            );
+            assert_eq!(get_review(&mut banks_client, review_pda).await.status, status);
+        }
+
+        let unknown_ix = create_set_watch_status_ix(payer.pubkey(), program_id, "Persona", 3);
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[unknown_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidStatus)
+        );
+
+        // Regular reviews still need a rating
+        let (_review_pda, _counter_pda, unrated_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Solaris", 0, "Not seen yet"
+        );
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, std::slice::from_ref(&unrated_review_ix)).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidRating)
+        );
+
+        // Followed by the create_ata flag, no display title, then the status byte
+        let mut watchlist_ix = unrated_review_ix;
+        watchlist_ix.data.extend_from_slice(&[0, 0, MovieAccountState::STATUS_WANT_TO_WATCH]);
+        let balance_before = token_balance(&mut banks_client, user_ata).await;
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[watchlist_ix]).await,
+            Ok(_)
+        );
+        let (solaris_pda, _bump_seed) = Pubkey::find_program_address(&[payer.pubkey().as_ref(), b"Solaris"], &program_id);
+        let entry = get_review(&mut banks_client, solaris_pda).await;
+        assert_eq!(entry.status, MovieAccountState::STATUS_WANT_TO_WATCH);
+        assert_eq!(entry.rating, 0);
+        assert_eq!(token_balance(&mut banks_client, user_ata).await, balance_before);
+        assert!(banks_client.get_account(get_aggregate_pda(program_id, "Solaris")).await.unwrap().is_none());
+
+        // Marking it reviewed needs a rating first
+        let reviewed_ix = create_set_watch_status_ix(payer.pubkey(), program_id, "Solaris", MovieAccountState::STATUS_REVIEWED);
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, std::slice::from_ref(&reviewed_ix)).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidRating)
+        );
+        let rate_ix = create_update_review_ix(payer.pubkey(), program_id, "Solaris", 4, "Seen it now");
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[rate_ix, reviewed_ix]).await,
+            Ok(_)
+        );
+        let entry = get_review(&mut banks_client, solaris_pda).await;
+        assert_eq!((entry.status, entry.rating), (MovieAccountState::STATUS_REVIEWED, 4));
+    }
+
+    #[tokio::test]
+    async fn test_add_movie_review_twice_reports_existing_review() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Playtime", 5, "Tativille"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, std::slice::from_ref(&add_review_ix)).await,
+            Ok(_)
+        );
+
+        // The same transaction again needs a fresh blockhash so it isn't deduplicated
+        let new_blockhash = banks_client.get_new_latest_blockhash(&recent_blockhash).await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(&[add_review_ix], Some(&payer.pubkey()), &[&payer], new_blockhash);
+        let simulation = banks_client.simulate_transaction(transaction).await.unwrap();
+        assert_eq!(simulation.result, Some(Err(review_error(0, ReviewError::ReviewAlreadyExists))));
+        assert!(simulation.simulation_details.unwrap().logs.contains(
+            &"Program log: A review of \"Playtime\" by this author already exists; update it instead".to_string()
+        ));
+    }
+
+    fn create_import_review_ix(admin: Pubkey, program_id: Pubkey, author: Pubkey, title: &str, created_at: i64) -> (Pubkey, Instruction) {
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[author.as_ref(), title.as_bytes()], &program_id);
+        let (counter_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), b"comment"], &program_id);
+        let (global_seq_pda, _bump_seed) = Pubkey::find_program_address(&[b"global_seq"], &program_id);
+
+        let mut data = vec![MovieInstruction::TAG_IMPORT_REVIEW];
+        data.append(&mut (author, title, 4u8, "From the old site", created_at).try_to_vec().unwrap());
+
+        (
+            review_pda,
+            Instruction {
+                program_id,
+                accounts: vec![
+                    AccountMeta::new(admin, true),
+                    AccountMeta::new_readonly(config_pda, false),
+                    AccountMeta::new(review_pda, false),
+                    AccountMeta::new(counter_pda, false),
+                    AccountMeta::new(get_aggregate_pda(program_id, title), false),
+                    AccountMeta::new(get_index_pda(program_id, title), false),
+                    AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                    AccountMeta::new(global_seq_pda, false),
+                ],
+                data,
+            }
+        )
+    }
+
+    #[tokio::test]
+    async fn test_import_review_keeps_original_timestamp() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        let mint = setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let author = Pubkey::new_unique();
+
+        // 2015-06-01T00:00:00Z
+        const CREATED_AT: i64 = 1_433_116_800;
+        let (review_pda, import_ix) = create_import_review_ix(payer.pubkey(), program_id, author, "Mad Max: Fury Road", CREATED_AT);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[import_ix]).await,
+            Ok(_)
+        );
+
+        let review = get_review(&mut banks_client, review_pda).await;
+        assert_eq!(review.review, author);
+        assert_eq!(review.rating, 4);
+        assert_eq!(review.created_at, CREATED_AT);
+        assert_eq!(review.updated_at, CREATED_AT);
+        assert_eq!(review.discriminator, MovieAccountState::DISCRIMINATOR_V2);
+        assert_eq!(review.global_seq, 1);
+
+        // Counted like any review, but nothing is minted
+        let account = banks_client.get_account(get_aggregate_pda(program_id, "Mad Max: Fury Road")).await.unwrap().unwrap();
+        assert_eq!(try_from_slice_unchecked::<MovieAggregate>(&account.data).unwrap().review_count, 1);
+        assert_eq!(get_config(&mut banks_client, config_pda).await.total_minted, 0);
+        let author_ata = get_associated_token_address(&author, &mint);
+        assert!(banks_client.get_account(author_ata).await.unwrap().is_none());
+
+        let (_review_pda, negative_ix) = create_import_review_ix(payer.pubkey(), program_id, author, "Metropolis", -1);
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[negative_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidTimestamp)
+        );
+        let (_review_pda, future_ix) = create_import_review_ix(payer.pubkey(), program_id, author, "Metropolis", i64::MAX);
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[future_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidTimestamp)
+        );
+
+        // Only the config admin can import
+        let stranger = Keypair::new();
+        let fund_ix = system_instruction::transfer(&payer.pubkey(), &stranger.pubkey(), LAMPORTS_PER_SOL);
+        let (_review_pda, stranger_ix) = create_import_review_ix(stranger.pubkey(), program_id, author, "Metropolis", CREATED_AT);
+        let mut transaction = Transaction::new_with_payer(&[fund_ix, stranger_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &stranger], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(1, ReviewError::IncorrectAccountError)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_first_reviewer_of_a_movie_earns_double() {
+        let program_id = Pubkey::new_unique();
+        let other_author = Keypair::new();
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_account(other_author.pubkey(), Account::new(LAMPORTS_PER_SOL, 0, &SYSTEM_PROGRAM_ID));
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(program_test).await;
+        let mint = setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        // Both spellings share one aggregate, so only the first review gets the bonus
+        let (_review_pda, _counter_pda, first_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Alien", 5, "In space no one can hear you scream"
+        );
+        let init_ata_ix = create_associated_token_account(&payer.pubkey(), &other_author.pubkey(), &mint);
+        let (_review_pda, _counter_pda, second_review_ix) = create_add_review_ix(
+            other_author.pubkey(), program_id, "alien", 4, "Jonesy lives"
+        );
+        let first_claim_ix = create_claim_rewards_ix(payer.pubkey(), program_id);
+        let second_claim_ix = create_claim_rewards_ix(other_author.pubkey(), program_id);
+        let mut transaction = Transaction::new_with_payer(
+            &[first_review_ix, init_ata_ix, second_review_ix, first_claim_ix, second_claim_ix],
+            Some(&payer.pubkey())
+        );
+        transaction.sign(&[&payer, &other_author], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+
+        let first_ata = get_associated_token_address(&payer.pubkey(), &mint);
+        let second_ata = get_associated_token_address(&other_author.pubkey(), &mint);
+        assert_eq!(token_balance(&mut banks_client, first_ata).await, 20 * LAMPORTS_PER_SOL);
+        assert_eq!(token_balance(&mut banks_client, second_ata).await, 10 * LAMPORTS_PER_SOL);
+    }
+
+    #[tokio::test]
+    async fn test_reward_mint_checks_decimals() {
+        let program_id = Pubkey::new_unique();
+        let mut context = start_with_context_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        let (payer, recent_blockhash) = (context.payer.pubkey(), context.last_blockhash);
+        setup_mint(&mut context.banks_client, &context.payer, recent_blockhash, program_id).await;
+
+        // Leave the mint at the default decimals but have the config disagree
+        let (config_pda, _config_bump) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let mut config_account = context.banks_client.get_account(config_pda).await.unwrap().unwrap();
+        let mut config = try_from_slice_unchecked::<ProgramConfig>(&config_account.data).unwrap();
+        config.decimals = 6;
+        config_account.data = config.try_to_vec().unwrap();
+        context.set_account(&config_pda, &config_account.into());
+
+        let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer, program_id, "Sicario", 4, "Juarez"
+        );
+        let claim_ix = create_claim_rewards_ix(payer, program_id);
+        assert_eq!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[add_review_ix, claim_ix]).await.unwrap_err().unwrap(),
+            TransactionError::InstructionError(1, InstructionError::Custom(spl_token::error::TokenError::MintDecimalsMismatch as u32))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unique_commenters_counts_each_commenter_once() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let init_comment_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &comment_mint);
+        let (review_pda, counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Vertigo", 5, "Spirals"
+        );
+        let (_comment_pda, first_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "The tower scene");
+        let (_comment_pda, second_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 1, "And the dream");
+        assert_matches!(
+            process_instructions(
+                &mut banks_client, &payer, recent_blockhash,
+                &[init_comment_mint_ix, init_comment_ata_ix, add_review_ix, first_comment_ix, second_comment_ix]
+            ).await,
+            Ok(_)
+        );
+
+        let counter = get_counter(&mut banks_client, counter_pda).await;
+        assert_eq!(counter.counter, 2);
+        assert_eq!(counter.unique_commenters, 1);
+
+        let marker_pda = get_commenter_marker_pda(program_id, review_pda, payer.pubkey());
+        let marker_account = banks_client.get_account(marker_pda).await.unwrap().unwrap();
+        assert_eq!(marker_account.owner, program_id);
+        let marker = try_from_slice_unchecked::<CommenterMarker>(&marker_account.data).unwrap();
+        assert_eq!(marker.discriminator, CommenterMarker::DISCRIMINATOR);
+    }
+
+    #[tokio::test]
+    async fn test_update_rejects_counter_passed_as_review() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (_review_pda, counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Repulsion", 4, "Cracks in the walls"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+
+        // Program owned, so only the discriminator tells it apart from a review
+        let mut update_ix = create_update_review_ix(payer.pubkey(), program_id, "Repulsion", 5, "Even better");
+        update_ix.accounts[1] = AccountMeta::new(counter_pda, false);
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[update_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::WrongDiscriminator)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_version_needs_no_accounts() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+
+        let get_version_ix = Instruction {
+            program_id,
+            accounts: vec![],
+            data: vec![MovieInstruction::TAG_GET_VERSION],
+        };
+        let transaction = Transaction::new_signed_with_payer(&[get_version_ix], Some(&payer.pubkey()), &[&payer], recent_blockhash);
+        let simulation = banks_client.simulate_transaction(transaction).await.unwrap();
+        assert_matches!(simulation.result, Some(Ok(())));
+
+        let details = simulation.simulation_details.unwrap();
+        assert!(details.logs.contains(&format!("Program log: Program version: {}", env!("CARGO_PKG_VERSION"))));
+        assert_eq!(details.return_data.unwrap().data, env!("CARGO_PKG_VERSION").as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_update_rejects_tampered_bump() {
+        let program_id = Pubkey::new_unique();
+        let mut context = start_with_context_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        let (payer, recent_blockhash) = (context.payer.pubkey(), context.last_blockhash);
+        setup_mint(&mut context.banks_client, &context.payer, recent_blockhash, program_id).await;
+
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer, program_id, "Chinatown", 5, "Forget it, Jake"
+        );
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+
+        // Any other bump derives a different address, or none at all
+        let mut review_account = context.banks_client.get_account(review_pda).await.unwrap().unwrap();
+        let mut review = try_from_slice_unchecked::<MovieAccountState>(&review_account.data).unwrap();
+        review.bump = review.bump.wrapping_sub(1);
+        review.serialize(&mut &mut review_account.data[..]).unwrap();
+        context.set_account(&review_pda, &review_account.into());
+
+        let update_ix = create_update_review_ix(payer, program_id, "Chinatown", 4, "Second viewing");
+        assert_eq!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[update_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidPDA)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reports_from_distinct_reporters_hide_review() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+
+        let (mint, _mint_auth, init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        let (_config_pda, mut init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, 0, None, None);
+        init_config_ix.data = vec![MovieInstruction::TAG_INITIALIZE_CONFIG];
+        init_config_ix.data.append(&mut (0u64, None::<u8>, None::<u16>, None::<u64>, Some(3u64), None::<u64>, None::<u64>, None::<bool>, None::<u64>).try_to_vec().unwrap());
+        let init_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint);
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Salo", 1, "Unwatchable"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_config_ix, init_mint_ix, init_ata_ix, add_review_ix]).await,
+            Ok(_)
+        );
+
+        let reporters = [Keypair::new(), Keypair::new(), Keypair::new()];
+        for (reported, reporter) in reporters.iter().enumerate() {
+            let fund_ix = system_instruction::transfer(&payer.pubkey(), &reporter.pubkey(), LAMPORTS_PER_SOL);
+            let report_ix = create_report_review_ix(reporter.pubkey(), program_id, payer.pubkey(), "Salo");
+            let mut transaction = Transaction::new_with_payer(&[fund_ix, report_ix], Some(&payer.pubkey()));
+            transaction.sign(&[&payer, reporter], recent_blockhash);
+            assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+
+            // Hidden only once the third distinct report lands
+            let review = get_review(&mut banks_client, review_pda).await;
+            assert_eq!(review.report_count, reported as u64 + 1);
+            assert_eq!(review.hidden, reported == 2);
+        }
+
+        // A second report from the same reporter isn't counted
+        let repeat_ix = create_report_review_ix(reporters[0].pubkey(), program_id, payer.pubkey(), "Salo");
+        let mut transaction = Transaction::new_with_payer(&[repeat_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &reporters[0]], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::AlreadyReported)
+        );
+        assert_eq!(get_review(&mut banks_client, review_pda).await.report_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_comment_limit_per_review() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+
+        let (mint, _mint_auth, init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        let (_config_pda, mut init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, 0, None, None);
+        init_config_ix.data = vec![MovieInstruction::TAG_INITIALIZE_CONFIG];
+        init_config_ix.data.append(&mut (0u64, None::<u8>, None::<u16>, None::<u64>, None::<u64>, Some(2u64), None::<u64>, None::<bool>, None::<u64>).try_to_vec().unwrap());
+        let init_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint);
+        let (comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let init_comment_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &comment_mint);
+        let (review_pda, counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Tampopo", 5, "Ramen western"
+        );
+        let (_comment_pda, first_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "Hungry now");
+        let (_comment_pda, second_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 1, "The egg yolk");
+        assert_matches!(
+            process_instructions(
+                &mut banks_client, &payer, recent_blockhash,
+                &[init_config_ix, init_mint_ix, init_ata_ix, init_comment_mint_ix, init_comment_ata_ix, add_review_ix, first_comment_ix, second_comment_ix]
+            ).await,
+            Ok(_)
+        );
+        assert_eq!(get_counter(&mut banks_client, counter_pda).await.counter, 2);
+
+        let (_comment_pda, third_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 2, "One too many");
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[third_comment_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::CommentLimitReached)
+        );
+    }
+
+    #[test]
+    fn test_client_account_lists_match_test_instructions() {
+        let program_id = Pubkey::new_unique();
+        let author = Pubkey::new_unique();
+
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(author, program_id, "Ikiru", 5, "To live");
+        assert_eq!(client::review_pda(&program_id, &author, "Ikiru"), review_pda);
+        assert_eq!(client::add_review_accounts(&program_id, &author, "Ikiru"), add_review_ix.accounts);
+
+        let update_ix = create_update_review_ix(author, program_id, "Ikiru", 4, "Still living");
+        assert_eq!(client::update_review_accounts(&program_id, &author, "Ikiru"), update_ix.accounts);
+
+        let admin = Pubkey::new_unique();
+        let (_review_pda, import_ix) = create_import_review_ix(admin, program_id, author, "Ran", 0);
+        assert_eq!(client::import_review_accounts(&program_id, &admin, &author, "Ran"), import_ix.accounts);
+
+        let commenter = Pubkey::new_unique();
+        let (_comment_pda, add_comment_ix) = create_add_comment_ix(commenter, program_id, review_pda, 3, "The swing");
+        assert_eq!(client::add_comment_accounts(&program_id, &commenter, &review_pda, 3), add_comment_ix.accounts);
+    }
+
+    #[tokio::test]
+    async fn test_referrer_earns_bonus_with_review() {
+        let program_id = Pubkey::new_unique();
+        let referrer = Keypair::new();
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_account(referrer.pubkey(), Account::new(LAMPORTS_PER_SOL, 0, &SYSTEM_PROGRAM_ID));
+        let mut context = start_with_context_as_admin(program_test).await;
+        let (payer, recent_blockhash) = (context.payer.pubkey(), context.last_blockhash);
+        let mint = setup_mint(&mut context.banks_client, &context.payer, recent_blockhash, program_id).await;
+        let payer_ata = get_associated_token_address(&payer, &mint);
+        let referrer_ata = get_associated_token_address(&referrer.pubkey(), &mint);
+
+        // First review of the movie, so 20 tokens for the author and 2 for the referrer
+        let init_referrer_ata_ix = create_associated_token_account(&payer, &referrer.pubkey(), &mint);
+        let (_review_pda, _counter_pda, mut referred_review_ix) = create_add_review_ix(
+            payer, program_id, "Rashomon", 5, "Four versions"
+        );
+        referred_review_ix.accounts.push(AccountMeta::new(referrer_ata, false));
+        let claim_ix = create_claim_rewards_ix(payer, program_id);
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[init_referrer_ata_ix, referred_review_ix, claim_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(token_balance(&mut context.banks_client, payer_ata).await, 20 * LAMPORTS_PER_SOL);
+        assert_eq!(token_balance(&mut context.banks_client, referrer_ata).await, 2 * LAMPORTS_PER_SOL);
+
+        // The author can't refer themselves
+        advance_clock(&mut context, AuthorProfile::REWARD_COOLDOWN).await;
+        let (_review_pda, _counter_pda, mut self_referred_ix) = create_add_review_ix(
+            payer, program_id, "Ran", 5, "Kurosawa's Lear"
+        );
+        self_referred_ix.accounts.push(AccountMeta::new(payer_ata, false));
+        assert_eq!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[self_referred_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::IncorrectAccountError)
+        );
+
+        // The default pubkey means no referrer, so only the author is paid
+        let (_review_pda, _counter_pda, mut unreferred_review_ix) = create_add_review_ix(
+            payer, program_id, "Ran", 5, "Kurosawa's Lear"
+        );
+        unreferred_review_ix.accounts.push(AccountMeta::new_readonly(Pubkey::default(), false));
+        let claim_ix = create_claim_rewards_ix(payer, program_id);
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[unreferred_review_ix, claim_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(token_balance(&mut context.banks_client, payer_ata).await, 40 * LAMPORTS_PER_SOL);
+        assert_eq!(token_balance(&mut context.banks_client, referrer_ata).await, 2 * LAMPORTS_PER_SOL);
+    }
+
+    #[tokio::test]
+    async fn test_update_marks_review_edited() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Cure", 4, "Red X"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+        assert!(!get_review(&mut banks_client, review_pda).await.edited);
+
+        // Even an update that changes nothing counts as an edit
+        let update_ix = create_update_review_ix(payer.pubkey(), program_id, "Cure", 4, "Red X");
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[update_ix]).await,
+            Ok(_)
+        );
+        assert!(get_review(&mut banks_client, review_pda).await.edited);
+    }
+
+    #[tokio::test]
+    async fn test_initialize_program_creates_config_mint_and_treasury() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let (mint, _bump_seed) = Pubkey::find_program_address(&[b"token_mint"], &program_id);
+        let (mint_auth, _bump_seed) = Pubkey::find_program_address(&[b"token_auth"], &program_id);
+        let (treasury_pda, _bump_seed) = Pubkey::find_program_address(&[b"treasury"], &program_id);
+
+        // A bad decimals value fails the mint step after the config was created,
+        // and takes the config down with it
+        let init_program_ix = create_init_program_ix(payer.pubkey(), program_id, Some(ProgramConfig::MAX_DECIMALS + 1));
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_program_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidDecimals)
+        );
+        assert!(banks_client.get_account(config_pda).await.unwrap().is_none());
+
+        let init_program_ix = create_init_program_ix(payer.pubkey(), program_id, None);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_program_ix]).await,
+            Ok(_)
+        );
+
+        let config_data = get_config(&mut banks_client, config_pda).await;
+        assert_eq!(config_data.admin, payer.pubkey());
+        assert_eq!(config_data.slash_amount, TEST_SLASH_AMOUNT);
+
+        // The mint authority is a signing-only PDA; it exists as the mint's authority
+        let mint_account = banks_client.get_account(mint).await.unwrap().unwrap();
+        assert_eq!(mint_account.owner, TOKEN_PROGRAM_ID);
+        let mint_state = spl_token::state::Mint::unpack(&mint_account.data).unwrap();
+        assert_eq!(mint_state.mint_authority, COption::Some(mint_auth));
+        assert_eq!(mint_state.decimals, ProgramConfig::DEFAULT_DECIMALS);
+
+        let (comment_mint, _bump_seed) = Pubkey::find_program_address(&[b"comment_token_mint"], &program_id);
+        let (comment_mint_auth, _bump_seed) = Pubkey::find_program_address(&[b"comment_token_auth"], &program_id);
+        let comment_mint_account = banks_client.get_account(comment_mint).await.unwrap().unwrap();
+        assert_eq!(comment_mint_account.owner, TOKEN_PROGRAM_ID);
+        let comment_mint_state = spl_token::state::Mint::unpack(&comment_mint_account.data).unwrap();
+        assert_eq!(comment_mint_state.mint_authority, COption::Some(comment_mint_auth));
+        assert_eq!(comment_mint_state.decimals, ProgramConfig::DEFAULT_DECIMALS);
+
+        let treasury_account = banks_client.get_account(treasury_pda).await.unwrap().unwrap();
+        assert_eq!(treasury_account.owner, program_id);
+        assert!(treasury_account.data.is_empty());
+
+        let (global_seq_pda, _bump_seed) = Pubkey::find_program_address(&[b"global_seq"], &program_id);
+        let global_seq_account = banks_client.get_account(global_seq_pda).await.unwrap().unwrap();
+        assert_eq!(try_from_slice_unchecked::<GlobalSequence>(&global_seq_account.data).unwrap().last_seq, 0);
+
+        // Reviews earn rewards straight after
+        let init_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint);
+        let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Ikiru", 5, "Gondola song"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_ata_ix, add_review_ix]).await,
+            Ok(_)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_review_title_seed_length_boundary() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let title = "t".repeat(MAX_SEED_LEN);
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, &title, 4, "Fits exactly"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_review(&mut banks_client, review_pda).await.title, title);
+
+        // The client can't derive an address for a 33 byte seed either, so point the
+        // instruction somewhere and change only the title in the payload
+        let (_review_pda, _counter_pda, mut add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "placeholder", 4, "One byte over"
+        );
+        add_review_ix.data = vec![MovieInstruction::TAG_ADD_REVIEW];
+        add_review_ix.data.append(&mut ("t".repeat(MAX_SEED_LEN + 1), 4u8, "One byte over").try_to_vec().unwrap());
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::SeedTooLong)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_admin_transfer_takes_effect_on_accept() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+
+        let new_admin = Keypair::new();
+        let transfer_ix = create_transfer_admin_ix(payer.pubkey(), program_id, new_admin.pubkey());
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[transfer_ix]).await,
+            Ok(_)
+        );
+        let config_data = get_config(&mut banks_client, config_pda).await;
+        assert_eq!(config_data.admin, payer.pubkey());
+        assert_eq!(config_data.pending_admin, new_admin.pubkey());
+
+        // Nobody but the proposed key can accept
+        let outsider = Keypair::new();
+        let outsider_accept_ix = create_accept_admin_ix(outsider.pubkey(), program_id);
+        let mut transaction = Transaction::new_with_payer(&[outsider_accept_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &outsider], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::IncorrectAccountError)
+        );
+
+        let accept_ix = create_accept_admin_ix(new_admin.pubkey(), program_id);
+        let mut transaction = Transaction::new_with_payer(&[accept_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &new_admin], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+        let config_data = get_config(&mut banks_client, config_pda).await;
+        assert_eq!(config_data.admin, new_admin.pubkey());
+        assert_eq!(config_data.pending_admin, Pubkey::default());
+
+        // The old admin has lost the seat, and there's nothing left to accept
+        let latest_blockhash = banks_client.get_new_latest_blockhash(&recent_blockhash).await.unwrap();
+        let transfer_back_ix = create_transfer_admin_ix(payer.pubkey(), program_id, payer.pubkey());
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, latest_blockhash, &[transfer_back_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::IncorrectAccountError)
+        );
+        let accept_ix = create_accept_admin_ix(new_admin.pubkey(), program_id);
+        let mut transaction = Transaction::new_with_payer(&[accept_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &new_admin], latest_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::NoPendingAdmin)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transfer_admin_rejects_non_admin() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+
+        let outsider = Keypair::new();
+        let transfer_ix = create_transfer_admin_ix(outsider.pubkey(), program_id, outsider.pubkey());
+        let mut transaction = Transaction::new_with_payer(&[transfer_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &outsider], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::IncorrectAccountError)
+        );
+        assert_eq!(get_config(&mut banks_client, config_pda).await.pending_admin, Pubkey::default());
+    }
+
+    #[tokio::test]
+    async fn test_verbose_logs_toggle_keeps_instructions_working() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+        assert!(get_config(&mut banks_client, config_pda).await.verbose_logs);
+
+        let set_logs_ix = create_set_verbose_logs_ix(payer.pubkey(), program_id, false);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[set_logs_ix]).await,
+            Ok(_)
+        );
+        assert!(!get_config(&mut banks_client, config_pda).await.verbose_logs);
+
+        // The review still goes through, just without the config-gated progress logs
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Solaris", 4, "Ocean of memory"
+        );
+        let transaction = Transaction::new_signed_with_payer(std::slice::from_ref(&add_review_ix), Some(&payer.pubkey()), &[&payer], recent_blockhash);
+        let simulation = banks_client.simulate_transaction(transaction).await.unwrap();
+        assert_matches!(simulation.result, Some(Ok(())));
+        let logs = simulation.simulation_details.unwrap().logs;
+        assert!(!logs.iter().any(|log| log == "Program log: first reviewer bonus applied"));
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_review(&mut banks_client, review_pda).await.title, "Solaris");
+
+        // Only the admin may switch them back on
+        let outsider = Keypair::new();
+        let outsider_ix = create_set_verbose_logs_ix(outsider.pubkey(), program_id, true);
+        let mut transaction = Transaction::new_with_payer(&[outsider_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &outsider], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::IncorrectAccountError)
+        );
+
+        let set_logs_ix = create_set_verbose_logs_ix(payer.pubkey(), program_id, true);
+        let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Mirror", 5, "Wind in the field"
+        );
+        let transaction = Transaction::new_signed_with_payer(&[set_logs_ix, add_review_ix], Some(&payer.pubkey()), &[&payer], recent_blockhash);
+        let simulation = banks_client.simulate_transaction(transaction).await.unwrap();
+        assert_matches!(simulation.result, Some(Ok(())));
+        let logs = simulation.simulation_details.unwrap().logs;
+        assert!(logs.iter().any(|log| log == "Program log: first reviewer bonus applied"));
+    }
+
+    #[tokio::test]
+    async fn test_add_review_rolls_back_when_counter_creation_fails() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        let mint = setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        // The author can afford the review account and nothing more, so the review is
+        // created and the comment counter right after it is not
+        let author = Keypair::new();
+        let review_rent = banks_client.get_rent().await.unwrap().minimum_balance(MovieAccountState::ACCOUNT_LEN);
+        let fund_author_ix = system_instruction::transfer(&payer.pubkey(), &author.pubkey(), review_rent);
+        let init_ata_ix = create_associated_token_account(&payer.pubkey(), &author.pubkey(), &mint);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[fund_author_ix, init_ata_ix]).await,
+            Ok(_)
+        );
+
+        let (review_pda, counter_pda, add_review_ix) = create_add_review_ix(
+            author.pubkey(), program_id, "Playtime", 5, "Glass and steel"
+        );
+        let mut transaction = Transaction::new_with_payer(&[add_review_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &author], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(SystemError::ResultWithNegativeLamports as u32))
+        );
+
+        // Nothing the instruction did before the failure survives it
+        assert_eq!(banks_client.get_account(review_pda).await.unwrap(), None);
+        assert_eq!(banks_client.get_account(counter_pda).await.unwrap(), None);
+        assert_eq!(banks_client.get_balance(author.pubkey()).await.unwrap(), review_rent);
+        assert_eq!(token_balance(&mut banks_client, get_associated_token_address(&author.pubkey(), &mint)).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_pin_and_unpin_comment() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let init_comment_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &comment_mint);
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Sans Soleil", 5, "Emus on Ile de France"
+        );
+        let (_comment_pda, first_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "Three children in Iceland");
+        let (_comment_pda, second_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 1, "The cat temple");
+        assert_matches!(
+            process_instructions(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &[init_comment_mint_ix, init_comment_ata_ix, add_review_ix, first_comment_ix, second_comment_ix]
+            ).await,
+            Ok(_)
+        );
+        assert_eq!(get_review(&mut banks_client, review_pda).await.pinned_comment, None);
+
+        let pin_ix = create_pin_comment_ix(payer.pubkey(), program_id, "Sans Soleil", Some(1));
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[pin_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_review(&mut banks_client, review_pda).await.pinned_comment, Some(1));
+
+        // Only the two existing comments can be pinned
+        let out_of_range_ix = create_pin_comment_ix(payer.pubkey(), program_id, "Sans Soleil", Some(2));
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[out_of_range_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::CommentIndexOutOfRange)
+        );
+
+        let unpin_ix = create_pin_comment_ix(payer.pubkey(), program_id, "Sans Soleil", None);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[unpin_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_review(&mut banks_client, review_pda).await.pinned_comment, None);
+    }
+
+    #[tokio::test]
+    async fn test_pin_comment_requires_review_author() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "La Jetee", 5, "A pier at Orly"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+
+        // An outsider signing for the author's review fails the PDA re-derivation
+        let outsider = Keypair::new();
+        let mut pin_ix = create_pin_comment_ix(outsider.pubkey(), program_id, "La Jetee", None);
+        pin_ix.accounts[1].pubkey = review_pda;
+        let mut transaction = Transaction::new_with_payer(&[pin_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &outsider], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::InvalidPDA)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_review_fee_goes_to_treasury() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        let (treasury_pda, _bump_seed) = Pubkey::find_program_address(&[b"treasury"], &program_id);
+        let review_fee = LAMPORTS_PER_SOL / 100;
+
+        let (_config_pda, mut init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, 0, None, None);
+        init_config_ix.data = vec![MovieInstruction::TAG_INITIALIZE_CONFIG];
+        init_config_ix.data.append(&mut (0u64, None::<u8>, None::<u16>, None::<u64>, None::<u64>, None::<u64>, Some(review_fee), None::<bool>, None::<u64>).try_to_vec().unwrap());
+        let (mint, _mint_auth, init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        let init_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_config_ix, init_mint_ix, init_ata_ix]).await,
+            Ok(_)
+        );
+
+        // With a fee configured, the treasury is required
+        let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Persona", 5, "Two faces"
+        );
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+        );
+
+        // The default pubkey fills the referrer slot. The first fee also creates the
+        // treasury, so it holds the fee above its rent-exempt floor
+        let rent_floor = banks_client.get_rent().await.unwrap().minimum_balance(0);
+        let (_review_pda, _counter_pda, mut add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Persona", 5, "Two faces"
+        );
+        add_review_ix.accounts.push(AccountMeta::new_readonly(Pubkey::default(), false));
+        add_review_ix.accounts.push(AccountMeta::new(treasury_pda, false));
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(banks_client.get_balance(treasury_pda).await.unwrap(), rent_floor + review_fee);
+
+        let (_review_pda, _counter_pda, mut add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Wild Strawberries", 4, "A long drive"
+        );
+        add_review_ix.accounts.push(AccountMeta::new_readonly(Pubkey::default(), false));
+        add_review_ix.accounts.push(AccountMeta::new(treasury_pda, false));
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(banks_client.get_balance(treasury_pda).await.unwrap(), rent_floor + 2 * review_fee);
+
+        // An author who can pay the rent but not the fee is turned away
+        let author = Keypair::new();
+        let fund_author_ix = system_instruction::transfer(&payer.pubkey(), &author.pubkey(), review_fee);
+        let init_author_ata_ix = create_associated_token_account(&payer.pubkey(), &author.pubkey(), &mint);
+        let (_review_pda, _counter_pda, mut add_review_ix) = create_add_review_ix(
+            author.pubkey(), program_id, "Persona", 5, "Two faces"
+        );
+        add_review_ix.accounts.push(AccountMeta::new_readonly(Pubkey::default(), false));
+        add_review_ix.accounts.push(AccountMeta::new(treasury_pda, false));
+        let mut transaction = Transaction::new_with_payer(&[fund_author_ix, init_author_ata_ix, add_review_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &author], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(2, ReviewError::InsufficientFunds)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_length_limits_count_utf8_bytes() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        // Each emoji is four bytes, so 8 make a 32 byte seed and 10 don't fit
+        let title = "🎬".repeat(8);
+        assert_eq!(title.len(), MAX_SEED_LEN);
+        let description = "🍿".repeat(MovieAccountState::MAX_DESCRIPTION_LEN / 4);
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, &title, 5, &description
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_review(&mut banks_client, review_pda).await.description, description);
+
+        let (_review_pda, _counter_pda, mut add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "placeholder", 5, "Ten emoji"
+        );
+        add_review_ix.data = vec![MovieInstruction::TAG_ADD_REVIEW];
+        add_review_ix.data.append(&mut ("🎬".repeat(10), 5u8, "Ten emoji").try_to_vec().unwrap());
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::SeedTooLong)
+        );
+
+        // 201 emoji are 804 bytes, over the limit at well under 800 characters
+        let (_review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Tampopo", 5, &"🍜".repeat(MovieAccountState::MAX_DESCRIPTION_LEN / 4 + 1)
+        );
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::DescriptionTooLong)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_claim_rewards_mints_accumulated_balance_once() {
+        let program_id = Pubkey::new_unique();
+        let other_author = Keypair::new();
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_account(other_author.pubkey(), Account::new(LAMPORTS_PER_SOL, 0, &SYSTEM_PROGRAM_ID));
+        let mut context = start_with_context_as_admin(program_test).await;
+        let (payer, recent_blockhash) = (context.payer.pubkey(), context.last_blockhash);
+        let mint = setup_mint(&mut context.banks_client, &context.payer, recent_blockhash, program_id).await;
+        let (config_pda, _bump_seed) = Pubkey::find_program_address(&[b"config"], &program_id);
+        let payer_ata = get_associated_token_address(&payer, &mint);
+
+        // Someone else reviews both movies first, so the payer earns the plain 10 tokens each
+        let init_ata_ix = create_associated_token_account(&payer, &other_author.pubkey(), &mint);
+        let (_review_pda, _counter_pda, first_other_ix) = create_add_review_ix(
+            other_author.pubkey(), program_id, "Solaris", 4, "Ocean planet"
+        );
+        let (_review_pda, _counter_pda, second_other_ix) = create_add_review_ix(
+            other_author.pubkey(), program_id, "Mirror", 5, "Burning barn"
+        );
+        let mut transaction = Transaction::new_with_payer(
+            &[init_ata_ix, first_other_ix, second_other_ix],
+            Some(&payer)
+        );
+        transaction.sign(&[&context.payer, &other_author], recent_blockhash);
+        assert_matches!(context.banks_client.process_transaction(transaction).await, Ok(_));
+
+        let (_review_pda, _counter_pda, first_review_ix) = create_add_review_ix(
+            payer, program_id, "Solaris", 3, "Too long"
+        );
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[first_review_ix]).await,
+            Ok(_)
+        );
+        advance_clock(&mut context, AuthorProfile::REWARD_COOLDOWN).await;
+        let (_review_pda, _counter_pda, second_review_ix) = create_add_review_ix(
+            payer, program_id, "Mirror", 4, "Dreamlike"
+        );
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[second_review_ix]).await,
+            Ok(_)
+        );
+
+        // Nothing has been minted to the payer yet
+        assert_eq!(token_balance(&mut context.banks_client, payer_ata).await, 0);
+        assert_eq!(get_profile(&mut context.banks_client, payer, program_id).await.pending_rewards, 20 * LAMPORTS_PER_SOL);
+        let minted_before = get_config(&mut context.banks_client, config_pda).await.total_minted;
+
+        let claim_ix = create_claim_rewards_ix(payer, program_id);
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[claim_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(token_balance(&mut context.banks_client, payer_ata).await, 20 * LAMPORTS_PER_SOL);
+        assert_eq!(get_profile(&mut context.banks_client, payer, program_id).await.pending_rewards, 0);
+        assert_eq!(get_config(&mut context.banks_client, config_pda).await.total_minted, minted_before + 20 * LAMPORTS_PER_SOL);
+
+        // A second claim has nothing left to mint
+        let claim_ix = create_claim_rewards_ix(payer, program_id);
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, context.last_blockhash, &[claim_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(token_balance(&mut context.banks_client, payer_ata).await, 20 * LAMPORTS_PER_SOL);
+        assert_eq!(get_config(&mut context.banks_client, config_pda).await.total_minted, minted_before + 20 * LAMPORTS_PER_SOL);
+    }
+
+    #[tokio::test]
+    async fn test_mint_auth_passed_as_signer_is_rejected() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Nosferatu", 4, "Shadow on the stairs"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+
+        // Nobody holds a key for the real PDA, so a keypair stands in at its position
+        let fake_auth = Keypair::new();
+        let (_review_pda, _counter_pda, mut signed_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Faust", 4, "Wager with the devil"
+        );
+        let (_comment_pda, mut signed_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "Max Schreck");
+        let mut signed_claim_ix = create_claim_rewards_ix(payer.pubkey(), program_id);
+        for (ix, mint_auth_index) in [(&mut signed_review_ix, 4), (&mut signed_comment_ix, 5), (&mut signed_claim_ix, 3)] {
+            ix.accounts[mint_auth_index] = AccountMeta::new_readonly(fake_auth.pubkey(), true);
+            let mut transaction = Transaction::new_with_payer(std::slice::from_ref(ix), Some(&payer.pubkey()));
+            transaction.sign(&[&payer, &fake_auth], recent_blockhash);
+            assert_eq!(
+                banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+                review_error(0, ReviewError::UnexpectedSigner)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_review_rejects_updates_once_expired() {
+        let program_id = Pubkey::new_unique();
+        let mut context = start_with_context_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        let (payer, recent_blockhash) = (context.payer.pubkey(), context.last_blockhash);
+        setup_mint(&mut context.banks_client, &context.payer, recent_blockhash, program_id).await;
+
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer, program_id, "Tokyo Story", 5, "Visiting the children"
+        );
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_review(&mut context.banks_client, review_pda).await.expires_at, 0);
+
+        // Only the admin sets an expiry
+        let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+        let outsider = Keypair::new();
+        let outsider_ix = create_set_review_expiry_ix(outsider.pubkey(), program_id, payer, "Tokyo Story", now + 100);
+        let mut transaction = Transaction::new_with_payer(&[outsider_ix], Some(&payer));
+        transaction.sign(&[&context.payer, &outsider], recent_blockhash);
+        assert_eq!(
+            context.banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::IncorrectAccountError)
+        );
+
+        let expiry_ix = create_set_review_expiry_ix(payer, program_id, payer, "Tokyo Story", now + 100);
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[expiry_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_review(&mut context.banks_client, review_pda).await.expires_at, now + 100);
+
+        // Not expired yet, so the author can still update it
+        let update_ix = create_update_review_ix(payer, program_id, "Tokyo Story", 4, "Quietly devastating");
+        assert_matches!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[update_ix]).await,
+            Ok(_)
+        );
+
+        advance_clock(&mut context, 100).await;
+        let update_ix = create_update_review_ix(payer, program_id, "Tokyo Story", 3, "Too slow on a rewatch");
+        assert_eq!(
+            process_instructions(&mut context.banks_client, &context.payer, recent_blockhash, &[update_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::ReviewExpired)
+        );
+        let review = get_review(&mut context.banks_client, review_pda).await;
+        assert_eq!((review.rating, review.description.as_str()), (4, "Quietly devastating"));
+    }
+
+    #[tokio::test]
+    async fn test_reviews_of_a_movie_are_linked_from_its_aggregate() {
+        let program_id = Pubkey::new_unique();
+        let other_author = Keypair::new();
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_account(other_author.pubkey(), Account::new(LAMPORTS_PER_SOL, 0, &SYSTEM_PROGRAM_ID));
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(program_test).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
 
-    Ok(())
-}
+        // Spelled differently, but both land on the same aggregate
+        let (first_pda, _counter_pda, first_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Playtime", 5, "Glass everywhere"
+        );
+        let (second_pda, _counter_pda, second_review_ix) = create_add_review_ix(
+            other_author.pubkey(), program_id, "playtime", 4, "Monsieur Hulot again"
+        );
+        let (_review_pda, _counter_pda, other_movie_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Mon Oncle", 4, "The villa"
+        );
+        let mut transaction = Transaction::new_with_payer(
+            &[first_review_ix, second_review_ix, other_movie_ix],
+            Some(&payer.pubkey())
+        );
+        transaction.sign(&[&payer, &other_author], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
 
-pub fn initialize_token_mint(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo]
-) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
+        // Walk the list from the aggregate's head, newest review first
+        let aggregate_account = banks_client.get_account(get_aggregate_pda(program_id, "Playtime")).await.unwrap().unwrap();
+        let mut next = try_from_slice_unchecked::<MovieAggregate>(&aggregate_account.data).unwrap().head_review;
+        let mut walked = vec![];
+        while let Some(review_pda) = next {
+            walked.push(review_pda);
+            next = get_review(&mut banks_client, review_pda).await.next_review;
+        }
+        assert_eq!(walked, vec![second_pda, first_pda]);
+    }
 
-    // The order of the following accounts is not arbitrary!
-    // They are sent by the client in this order
+    #[tokio::test]
+    async fn test_self_comments_rejected_when_disabled() {
+        let program_id = Pubkey::new_unique();
+        let commenter = Keypair::new();
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_account(commenter.pubkey(), Account::new(LAMPORTS_PER_SOL, 0, &SYSTEM_PROGRAM_ID));
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(program_test).await;
 
-    // Sender of transaction
-    let initializer = next_account_info(account_info_iter)?;
-    // Token mint PDA - derived on the clinet
-    let token_mint = next_account_info(account_info_iter)?;
-    // Token mint authority (should be me)
-    let mint_auth = next_account_info(account_info_iter)?;
-    // System program to create a new account
-    let system_program = next_account_info(account_info_iter)?;
-    // Solana Token program address
-    let token_program = next_account_info(account_info_iter)?;
-    // System account to calculate the rent
-    let sysvar_rent = next_account_info(account_info_iter)?;
+        let (mint, _mint_auth, init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        let (_config_pda, mut init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, 0, None, None);
+        init_config_ix.data = vec![MovieInstruction::TAG_INITIALIZE_CONFIG];
+        init_config_ix.data.append(&mut (0u64, None::<u8>, None::<u16>, None::<u64>, None::<u64>, None::<u64>, None::<u64>, Some(false), None::<u64>).try_to_vec().unwrap());
+        let init_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint);
+        let (comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Amarcord", 5, "I remember"
+        );
+        assert_matches!(
+            process_instructions(
+                &mut banks_client, &payer, recent_blockhash, &[init_config_ix, init_mint_ix, init_ata_ix, init_comment_mint_ix, add_review_ix]
+            ).await,
+            Ok(_)
+        );
 
-    // Derive the mint PDA again to validate
-    let (mint_pda, mint_bump) = Pubkey::find_program_address(&[b"token_mint"], program_id);
+        let (_comment_pda, self_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "My own review is great");
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[self_comment_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::SelfCommentNotAllowed)
+        );
 
-    // Derive the mint authority to validate
-    let (mint_auth_pda, _mint_auth_bump) = Pubkey::find_program_address(&[b"token_auth"], program_id);
+        // Anyone else can still comment, and is rewarded for it
+        let init_commenter_ata_ix = create_associated_token_account(&payer.pubkey(), &commenter.pubkey(), &comment_mint);
+        let (_comment_pda, other_comment_ix) = create_add_comment_ix(commenter.pubkey(), program_id, review_pda, 0, "The peacock in the snow");
+        let mut transaction = Transaction::new_with_payer(&[init_commenter_ata_ix, other_comment_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &commenter], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+        let commenter_ata = get_associated_token_address(&commenter.pubkey(), &comment_mint);
+        assert!(token_balance(&mut banks_client, commenter_ata).await > 0);
+    }
 
-    msg!("Token mint: {:?}", mint_pda);
-    msg!("Mint authority: {:?}", mint_auth_pda);
+    #[tokio::test]
+    async fn test_export_review_snapshot_decodes_to_stored_review() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
 
-    // Validate the important accounts passed in
-    if mint_pda != *token_mint.key {
-        msg!("Incorrect token mint account");
-        return Err(ReviewError::IncorrectAccountError.into());
-    }
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_v2_ix(
+            payer.pubkey(), program_id, "Suspiria", 4, "Red everywhere", "horror"
+        );
+        let spoiler_ix = create_set_spoiler_flag_ix(payer.pubkey(), program_id, "Suspiria", true);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix, spoiler_ix]).await,
+            Ok(_)
+        );
+        let stored = get_review(&mut banks_client, review_pda).await;
 
-    if *token_program.key != TOKEN_PROGRAM_ID {
-        msg!("Incorrect token program");
-        return Err(ReviewError::IncorrectAccountError.into());
-    }
+        // Native program-test prints sol_log_data to stdout, so read the same bytes
+        // back from the return data
+        let export_ix = create_export_review_ix(program_id, payer.pubkey(), "Suspiria");
+        let transaction = Transaction::new_signed_with_payer(std::slice::from_ref(&export_ix), Some(&payer.pubkey()), &[&payer], recent_blockhash);
+        let simulation = banks_client.simulate_transaction(transaction).await.unwrap();
+        assert_matches!(simulation.result, Some(Ok(())));
+        let details = simulation.simulation_details.unwrap();
+        assert!(details.logs.contains(&format!("Program log: Review snapshot: {} bytes", stored.serialized_len())));
+        let return_data = details.return_data.unwrap();
+        assert_eq!(return_data.program_id, program_id);
+        // The runtime trims trailing zero bytes, so pad back to the logged length
+        let mut snapshot = return_data.data;
+        snapshot.resize(stored.serialized_len(), 0);
+        assert_eq!(MovieAccountState::try_from_slice(&snapshot).unwrap(), stored);
 
-    if *mint_auth.key != mint_auth_pda {
-        msg!("Incorrect mint auth account");
-        return Err(ReviewError::IncorrectAccountError.into());
+        // Running it for real leaves the account untouched
+        let before = banks_client.get_account(review_pda).await.unwrap().unwrap();
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[export_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(banks_client.get_account(review_pda).await.unwrap().unwrap(), before);
     }
 
-    if *system_program.key != SYSTEM_PROGRAM_ID {
-        msg!("Incorrect system program");
-        return Err(ReviewError::IncorrectAccountError.into());
-    }
+    #[tokio::test]
+    async fn test_min_description_len_applies_to_new_and_updated_reviews() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
 
-    if *sysvar_rent.key != RENT_PROGRAM_ID {
-        msg!("Incorrect rent program");
-        return Err(ReviewError::IncorrectAccountError.into());
-    }
+        let (mint, _mint_auth, init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        let (_config_pda, mut init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, 0, None, None);
+        init_config_ix.data = vec![MovieInstruction::TAG_INITIALIZE_CONFIG];
+        init_config_ix.data.append(&mut (0u64, None::<u8>, None::<u16>, None::<u64>, None::<u64>, None::<u64>, None::<u64>, None::<bool>, Some(10u64)).try_to_vec().unwrap());
+        let init_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_config_ix, init_mint_ix, init_ata_ix]).await,
+            Ok(_)
+        );
 
-    let rent = Rent::get()?;
-    // The size of a mint account is 82! Remember this!
-    let rent_lamports = rent.minimum_balance(82);
+        let (_review_pda, _counter_pda, short_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Sátántangó", 5, "Long."
+        );
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[short_review_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::DescriptionTooShort)
+        );
 
-    // Create the token mint PDA
-    invoke_signed(
-        &system_instruction::create_account(
-            initializer.key,
-            token_mint.key,
-            rent_lamports,
-            82, // size of token mint account
-            token_program.key
-        ),
-        // Accounts we're reading from or writing to
-        &[
-          initializer.clone(),
-          token_mint.clone(),
-          system_program.clone(),
-        ],
-        // Seeds for our token mint account
-        &[&[b"token_mint", &[mint_bump]]]
-    )?;
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Sátántangó", 5, "Seven hours of rain."
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
+        );
 
-    msg!("Created token mint account");
+        // Updates are held to the same minimum
+        let short_update_ix = create_update_review_ix(payer.pubkey(), program_id, "Sátántangó", 4, "Slow.");
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[short_update_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::DescriptionTooShort)
+        );
+        let update_ix = create_update_review_ix(payer.pubkey(), program_id, "Sátántangó", 4, "Exactly10!");
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[update_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_review(&mut banks_client, review_pda).await.description, "Exactly10!");
+    }
 
-    // Initialize mint account
-    invoke_signed(
-        &initialize_mint(
-            token_program.key,
-            token_mint.key,
-            mint_auth.key,
-            Option::None, // Freeze authority - we don't want anyone to be able to freeze
-            9, // Number of decimals
-        )?,
-        // Which accounts we're reading from or writing to
-        &[token_mint.clone(), sysvar_rent.clone(), mint_auth.clone()],
-        // Seeds for our token mint PDA
-        &[&[b"token_mint", &[mint_bump]]]
-    )?;
+    #[tokio::test]
+    async fn test_reviews_get_increasing_global_seq() {
+        let program_id = Pubkey::new_unique();
+        let other_author = Keypair::new();
+        let mut program_test = ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        );
+        program_test.add_account(other_author.pubkey(), Account::new(LAMPORTS_PER_SOL, 0, &SYSTEM_PROGRAM_ID));
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(program_test).await;
+        let mint = setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
 
-    msg!("Initialized token mint");
+        // Same movie, same transaction, so updated_at can't tell them apart
+        let (first_pda, _counter_pda, first_review_ix) = create_add_review_v2_ix(
+            payer.pubkey(), program_id, "Chungking Express", 5, "California Dreamin'", "romance"
+        );
+        let init_ata_ix = create_associated_token_account(&payer.pubkey(), &other_author.pubkey(), &mint);
+        let (second_pda, _counter_pda, second_review_ix) = create_add_review_v2_ix(
+            other_author.pubkey(), program_id, "Chungking Express", 4, "Pineapple tins", "romance"
+        );
+        let mut transaction = Transaction::new_with_payer(
+            &[first_review_ix, init_ata_ix, second_review_ix],
+            Some(&payer.pubkey())
+        );
+        transaction.sign(&[&payer, &other_author], recent_blockhash);
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
 
-    Ok(())
-}
+        let first = get_review(&mut banks_client, first_pda).await;
+        let second = get_review(&mut banks_client, second_pda).await;
+        assert_eq!(first.updated_at, second.updated_at);
+        assert_eq!((first.global_seq, second.global_seq), (1, 2));
 
-#[cfg(test)]
-mod tests {
-    use {
-        super::*,
-        assert_matches::*,
-        solana_program::{
-            instruction::{AccountMeta, Instruction},
-            system_program::ID as SYSTEM_PROGRAM_ID,
-        },
-        solana_program_test::*,
-        solana_sdk::{
-            signature::Signer,
-            transaction::Transaction,
-            sysvar::rent::ID as SYSVAR_RENT_ID,
-        },
-        spl_associated_token_account::{
-            get_associated_token_address,
-            instruction::create_associated_token_account
-        },
-        spl_token::ID as TOKEN_PROGRAM_ID
-    };
+        let (global_seq_pda, _bump_seed) = Pubkey::find_program_address(&[b"global_seq"], &program_id);
+        let global_seq_account = banks_client.get_account(global_seq_pda).await.unwrap().unwrap();
+        assert_eq!(try_from_slice_unchecked::<GlobalSequence>(&global_seq_account.data).unwrap().last_seq, 2);
+    }
 
-    fn create_init_mint_ix(payer: Pubkey, program_id: Pubkey) -> (Pubkey, Pubkey, Instruction) {
-        // Derive PDA for token mint authority
-        let (mint, _bump_seed) = Pubkey::find_program_address(&[b"token_mint"], &program_id);
-        let (mint_auth, _bump_seed) = Pubkey::find_program_address(&[b"token_auth"], &program_id);
+    #[tokio::test]
+    async fn test_entrypoint_logs_review_error_variant_name() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(crate::entrypoint::process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
 
-        let init_mint_ix = Instruction {
-            program_id: program_id,
-            accounts: vec![
-                AccountMeta::new_readonly(payer, true),
-                AccountMeta::new(mint, false),
-                AccountMeta::new(mint_auth, false),
-                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
-                AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
-                AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
-            ],
-            data: vec![3]
-        };
-        (mint, mint_auth, init_mint_ix)
+        let (_review_pda, _counter_pda, zero_rating_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Tenet", 0, "Unrated"
+        );
+        let transaction = Transaction::new_signed_with_payer(&[zero_rating_ix], Some(&payer.pubkey()), &[&payer], recent_blockhash);
+        let simulation = banks_client.simulate_transaction(transaction).await.unwrap();
+        assert_eq!(simulation.result.unwrap().unwrap_err(), review_error(0, ReviewError::InvalidRating));
+        let logs = simulation.simulation_details.unwrap().logs;
+        assert!(
+            logs.contains(&"Program log: Error: InvalidRating: Rating is zero or above the configured maximum".to_string()),
+            "{:?}", logs
+        );
     }
 
     #[tokio::test]
-    async fn test_initialize_mint_instruction() {
+    async fn test_create_ata_rejects_wrong_token_program_before_cpi() {
         let program_id = Pubkey::new_unique();
-        let (mut banks_client, payer, recent_blockhash) = ProgramTest::new(
+        let author = Keypair::new();
+        let mut program_test = ProgramTest::new(
             "pda_local",
             program_id,
             processor!(process_instruction)
-        )
-        .start()
-        .await;
+        );
+        program_test.add_account(author.pubkey(), Account::new(LAMPORTS_PER_SOL, 0, &SYSTEM_PROGRAM_ID));
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(program_test).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
 
-        // Call helper function
-        let (_mint, _mint_auth, init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        let (_comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Sicario", 4, "The border crossing"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_comment_mint_ix, add_review_ix]).await,
+            Ok(_)
+        );
 
-        // Create transaction object with instructions, accounts, and input data
-        let mut transaction = Transaction::new_with_payer(
-            &[init_mint_ix,],
-            Some(&payer.pubkey())
+        // The author has no comment ATA yet, so create_ata would otherwise reach the CPI
+        let (_comment_pda, mut add_comment_ix) = create_add_comment_ix(
+            author.pubkey(), program_id, review_pda, 0, "Alejandro knows"
         );
-        transaction.sign(&[&payer], recent_blockhash);
+        add_comment_ix.data.push(1);
+        add_comment_ix.accounts[8] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+        add_comment_ix.accounts.push(AccountMeta::new_readonly(spl_associated_token_account::ID, false));
 
-        // Process transaction and compare the result
-        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+        let mut transaction = Transaction::new_with_payer(&[add_comment_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &author], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::IncorrectAccountError)
+        );
     }
 
     #[tokio::test]
-    async fn test_add_movie_review_instruction() {
+    async fn test_watchlist_entry_counts_as_a_review_once_rated() {
         let program_id = Pubkey::new_unique();
-        let (mut banks_client, payer, recent_blockhash) = ProgramTest::new(
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
             "pda_local",
             program_id,
             processor!(process_instruction)
-        )
-        .start()
-        .await;
+        )).await;
 
-        // Call helper function
-        let (mint, mint_auth, init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        // A limit of one review, which the watchlist entry uses up
+        let (mint, _mint_auth, init_mint_ix) = create_init_mint_ix(payer.pubkey(), program_id);
+        let (_config_pda, init_config_ix) = create_init_config_ix(payer.pubkey(), program_id, 0, None, Some(1));
+        let init_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[init_config_ix, init_mint_ix, init_ata_ix]).await,
+            Ok(_)
+        );
 
-        // Create review PDA
-        let title: String = "Captain America".to_owned();
-        const RATING: u8 = 3;
-        let review: String = "Liked the move".to_owned();
-        let (review_pda, _bump_seed) = Pubkey::find_program_address(&[payer.pubkey().as_ref(), title.as_bytes()], &program_id);
+        let (entry_pda, _counter_pda, mut watchlist_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Stalker", 0, "Not seen yet"
+        );
+        watchlist_ix.data.extend_from_slice(&[0, 0, MovieAccountState::STATUS_WANT_TO_WATCH]);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[watchlist_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_profile(&mut banks_client, payer.pubkey(), program_id).await.review_count, 1);
 
-        // Create comment PDA
-        let (comment_pda, _bump_seed) = Pubkey::find_program_address(&[review_pda.as_ref(), b"comment"], &program_id);
+        let (_review_pda, _counter_pda, second_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Mirror", 5, "The barn fire"
+        );
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[second_review_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::ReviewLimitReached)
+        );
 
-        // Create user associated token account of token mint
-        let init_ata_ix: Instruction = create_associated_token_account(
-            &payer.pubkey(),
-            &payer.pubkey(),
-            &mint
+        // The first rating creates the aggregate, here the movie's first
+        let aggregate_pda = get_aggregate_pda(program_id, "Stalker");
+        assert!(banks_client.get_account(aggregate_pda).await.unwrap().is_none());
+        let rate_ix = create_update_review_ix(payer.pubkey(), program_id, "Stalker", 4, "Into the Zone");
+        let reviewed_ix = create_set_watch_status_ix(payer.pubkey(), program_id, "Stalker", MovieAccountState::STATUS_REVIEWED);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[rate_ix, reviewed_ix]).await,
+            Ok(_)
         );
 
-        let user_ata: Pubkey = get_associated_token_address(&payer.pubkey(), &mint);
+        let aggregate_account = banks_client.get_account(aggregate_pda).await.unwrap().unwrap();
+        let aggregate = try_from_slice_unchecked::<MovieAggregate>(&aggregate_account.data).unwrap();
+        assert_eq!((aggregate.review_count, aggregate.rating_sum), (1, 4));
+        assert_eq!(aggregate.rating_counts[MovieAggregate::rating_bucket(4).unwrap()], 1);
+        assert_eq!(aggregate.head_review, Some(entry_pda));
+        assert_eq!(get_profile(&mut banks_client, payer.pubkey(), program_id).await.review_count, 1);
 
-        // Concat data to single buffer
-        let mut data_vec = vec![0];
-        data_vec.append(
-            &mut (TryInto::<u32>::try_into(title.len()).unwrap().to_le_bytes())
-              .try_into()
-              .unwrap()
+        // From then on it is moved like any rated review, not counted again
+        let rerate_ix = create_update_review_ix(payer.pubkey(), program_id, "Stalker", 5, "Into the Zone, again");
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[rerate_ix]).await,
+            Ok(_)
         );
+        let aggregate_account = banks_client.get_account(aggregate_pda).await.unwrap().unwrap();
+        let aggregate = try_from_slice_unchecked::<MovieAggregate>(&aggregate_account.data).unwrap();
+        assert_eq!((aggregate.review_count, aggregate.rating_sum), (1, 5));
+        assert_eq!(aggregate.rating_counts[MovieAggregate::rating_bucket(4).unwrap()], 0);
+        assert_eq!(aggregate.rating_counts[MovieAggregate::rating_bucket(5).unwrap()], 1);
+    }
+    #[tokio::test]
+    async fn test_update_mints_bonus_for_upvotes() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        let mint = setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+        let (mint_auth, _bump_seed) = Pubkey::find_program_address(&[b"token_auth"], &program_id);
+        let payer_ata = get_associated_token_address(&payer.pubkey(), &mint);
 
-        data_vec.append(&mut title.into_bytes());
-        data_vec.push(RATING);
-        data_vec.append(
-            &mut (TryInto::<u32>::try_into(review.len())
-                  .unwrap()
-                  .to_le_bytes())
-            .try_into()
-            .unwrap()
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Amelie", 5, "Crème brûlée"
+        );
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[add_review_ix]).await,
+            Ok(_)
         );
-        data_vec.append(&mut review.into_bytes());
 
-        // Create transaction object with instructions, accounts, and input data
-        let mut transaction = Transaction::new_with_payer(
-            &[
-                init_mint_ix,
-                init_ata_ix,
-                Instruction {
-                    program_id: program_id,
-                    accounts: vec![
-                        AccountMeta::new_readonly(payer.pubkey(), true),
-                        AccountMeta::new(review_pda, false),
-                        AccountMeta::new(comment_pda, false),
-                        AccountMeta::new(mint, false),
-                        AccountMeta::new_readonly(mint_auth, false),
-                        AccountMeta::new(user_ata, false),
-                        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
-                        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
-                    ],
-                    data: data_vec,
-                }
-            ],
-            Some(&payer.pubkey())
+        let voters = [Keypair::new(), Keypair::new(), Keypair::new()];
+        for voter in &voters {
+            let fund_ix = system_instruction::transfer(&payer.pubkey(), &voter.pubkey(), LAMPORTS_PER_SOL);
+            let upvote_ix = create_upvote_review_ix(voter.pubkey(), program_id, payer.pubkey(), "Amelie");
+            let mut transaction = Transaction::new_with_payer(&[fund_ix, upvote_ix], Some(&payer.pubkey()));
+            transaction.sign(&[&payer, voter], recent_blockhash);
+            assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+        }
+        assert_eq!(get_review(&mut banks_client, review_pda).await.upvotes, 3);
+
+        // Neither a repeat vote nor the author's own counts
+        let repeat_ix = create_upvote_review_ix(voters[0].pubkey(), program_id, payer.pubkey(), "Amelie");
+        let mut transaction = Transaction::new_with_payer(&[repeat_ix], Some(&payer.pubkey()));
+        transaction.sign(&[&payer, &voters[0]], recent_blockhash);
+        assert_eq!(
+            banks_client.process_transaction(transaction).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::AlreadyUpvoted)
+        );
+        let self_ix = create_upvote_review_ix(payer.pubkey(), program_id, payer.pubkey(), "Amelie");
+        assert_eq!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[self_ix]).await.unwrap_err().unwrap(),
+            review_error(0, ReviewError::SelfUpvoteNotAllowed)
         );
-        transaction.sign(&[&payer], recent_blockhash);
 
-        // Process transaction and compare the result
-        assert_matches!(banks_client.process_transaction(transaction).await, Ok(_));
+        let bonus_accounts = [
+            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(mint_auth, false),
+            AccountMeta::new(payer_ata, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ];
+        let balance_before = token_balance(&mut banks_client, payer_ata).await;
+        let mut update_ix = create_update_review_ix(payer.pubkey(), program_id, "Amelie", 5, "Crème brûlée, cracked");
+        update_ix.accounts.extend_from_slice(&bonus_accounts);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[update_ix]).await,
+            Ok(_)
+        );
+        let bonus = 3 * UPVOTE_REWARD * LAMPORTS_PER_SOL;
+        assert_eq!(token_balance(&mut banks_client, payer_ata).await, balance_before + bonus);
+        assert_eq!(get_review(&mut banks_client, review_pda).await.rewarded_upvotes, 3);
+
+        // The same upvotes aren't paid out again
+        let mut update_ix = create_update_review_ix(payer.pubkey(), program_id, "Amelie", 4, "Crème brûlée, burnt");
+        update_ix.accounts.extend_from_slice(&bonus_accounts);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[update_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(token_balance(&mut banks_client, payer_ata).await, balance_before + bonus);
     }
-}
+    #[tokio::test]
+    async fn test_prefunded_reaction_pda_does_not_block_reacting() {
+        let program_id = Pubkey::new_unique();
+        let (mut banks_client, payer, recent_blockhash) = start_as_admin(ProgramTest::new(
+            "pda_local",
+            program_id,
+            processor!(process_instruction)
+        )).await;
+        setup_mint(&mut banks_client, &payer, recent_blockhash, program_id).await;
+
+        let (comment_mint, _comment_mint_auth, init_comment_mint_ix) = create_init_comment_mint_ix(payer.pubkey(), program_id);
+        let init_comment_ata_ix = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &comment_mint);
+        let (review_pda, _counter_pda, add_review_ix) = create_add_review_ix(
+            payer.pubkey(), program_id, "Mon Oncle", 5, "The villa"
+        );
+        let (comment_pda, add_comment_ix) = create_add_comment_ix(payer.pubkey(), program_id, review_pda, 0, "That fish fountain");
+        assert_matches!(
+            process_instructions(
+                &mut banks_client, &payer, recent_blockhash,
+                &[init_comment_mint_ix, init_comment_ata_ix, add_review_ix, add_comment_ix]
+            ).await,
+            Ok(_)
+        );
 
+        // Someone else sends a lamport to the payer's reaction PDA before they react
+        let (reaction_pda, _bump_seed) = Pubkey::find_program_address(&[comment_pda.as_ref(), payer.pubkey().as_ref(), b"react"], &program_id);
+        let grief_ix = system_instruction::transfer(&payer.pubkey(), &reaction_pda, 1);
+        let (_comment_pda, like_ix) = create_react_ix(payer.pubkey(), program_id, payer.pubkey(), "Mon Oncle", 0, 0);
+        assert_matches!(
+            process_instructions(&mut banks_client, &payer, recent_blockhash, &[grief_ix, like_ix]).await,
+            Ok(_)
+        );
+        assert_eq!(get_comment(&mut banks_client, comment_pda).await.reactions, [1, 0, 0, 0]);
+
+        let account = banks_client.get_account(reaction_pda).await.unwrap().unwrap();
+        assert_eq!(account.owner, program_id);
+        assert_eq!(account.lamports, Rent::default().minimum_balance(ReactionMarker::SIZE));
+        assert_eq!(try_from_slice_unchecked::<ReactionMarker>(&account.data).unwrap().reaction_kind, 0);
+    }
+}
@@ -1,41 +1,265 @@
 use borsh::{BorshSerialize, BorshDeserialize};
 use solana_program::{
+    hash::hash,
+    program_error::ProgramError,
     program_pack::{IsInitialized, Sealed},
     pubkey::Pubkey,
 };
+use std::convert::TryFrom;
+use std::io::{self, Write};
+use crate::error::ReviewError;
+use crate::math::mul_u64;
 
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
 pub struct MovieAccountState {
     pub discriminator: String,
+    pub version: u8,
     pub is_initialized: bool,
     pub review: Pubkey,
     pub rating: u8,
     pub title: String,
     pub description: String,
+    // Set by AddMovieReviewV2 only; V1 reviews leave these empty/zero
+    pub genre: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub bump: u8,
+    // Lets frontends blur the description until the reader opts in
+    pub has_spoilers: bool,
+    // Title as the author wrote it; `title` is the PDA seed and may be a normalized
+    // form of it. Empty on reviews written before this field, which show `title`
+    pub display_title: String,
+    // One of the STATUS_* values; reviews from before this field read as reviewed
+    pub status: u8,
+    // Distinct reporters, each recorded by a ReportMarker
+    pub report_count: u64,
+    // Set once report_count reaches the config's report_threshold; frontends
+    // suppress hidden reviews
+    pub hidden: bool,
+    // Set by any rating or description update, for an "(edited)" badge that
+    // doesn't depend on comparing timestamps
+    pub edited: bool,
+    // Index of the comment the author pinned with PinComment
+    pub pinned_comment: Option<u64>,
+    // Unix time from which the review is archived and can't be updated, set by the
+    // admin with SetReviewExpiry; 0 means it never expires
+    pub expires_at: i64,
+    // The review of the same movie created before this one, by any author. Walking
+    // it from MovieAggregate::head_review lists the movie's reviews, newest first
+    pub next_review: Option<Pubkey>,
+    // Creation order across all reviews, from the `[b"global_seq"]` PDA, so clients
+    // can break ties on updated_at. Starts at 1; 0 on reviews from before the field
+    pub global_seq: u64,
+    // Distinct upvoters, each recorded by an UpvoteMarker
+    pub upvotes: u32,
+    // What `upvotes` was at the last helpful-votes bonus, so each upvote pays out
+    // at most once
+    pub rewarded_upvotes: u32,
 }
 
-// Struct for recording how many comments total
+// Review layout written before the version byte and the fields after `description`
+// existed. Only MigrateReview reads it.
 #[derive(BorshSerialize, BorshDeserialize)]
+pub struct OldMovieAccountState {
+    pub discriminator: String,
+    pub is_initialized: bool,
+    pub review: Pubkey,
+    pub rating: u8,
+    pub title: String,
+    pub description: String,
+}
+
+// Struct for recording how many comments total
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
 pub struct MovieCommentCounter {
     pub discriminator: String,
+    pub version: u8,
     pub is_initialized: bool,
     pub counter: u64,
+    pub bump: u8,
+    // Distinct commenters, each counted when their CommenterMarker is created.
+    // Counters grown by EnsureCounter start this at zero
+    pub unique_commenters: u64,
 }
 
 // Struct for storing individual comments
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
 pub struct MovieComment {
     pub discriminator: String,
+    pub version: u8,
     pub is_initialized: bool,
     pub review: Pubkey,
     pub commenter: Pubkey,
     pub comment: String,
     pub count: u64,
+    pub bump: u8,
+    // Set once add_comment has minted the comment reward; edits never mint again
+    pub rewarded: bool,
+    // Counts per reaction kind, indexed like/love/laugh/angry
+    pub reactions: [u32; MovieComment::REACTION_KINDS],
+}
+
+// Program-wide settings, stored at the `[b"config"]` PDA
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ProgramConfig {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub admin: Pubkey,
+    // Raw token amount SlashReview burns from an author's reward account
+    pub slash_amount: u64,
+    pub bump: u8,
+    // Raw amount minted so far across both reward mints
+    pub total_minted: u64,
+    // Ratings run from 1 to max_rating
+    pub max_rating: u8,
+    // Share of each tip routed to the `[b"treasury"]` PDA, in basis points
+    pub tip_fee_bps: u16,
+    // Reviews an author may post before add_movie_review rejects them
+    pub max_reviews_per_author: u64,
+    // Decimals of both reward mints, chosen by InitializeMint
+    pub decimals: u8,
+    // Distinct reports that hide a review; 0 never hides
+    pub report_threshold: u64,
+    // Comments a review may hold before add_comment rejects more
+    pub max_comments_per_review: u64,
+    // Proposed by TransferAdmin until it signs AcceptAdmin; the default pubkey
+    // means no transfer is pending
+    pub pending_admin: Pubkey,
+    // Progress logs after the config is loaded; SetVerboseLogs toggles it
+    pub verbose_logs: bool,
+    // Lamports add_movie_review sends to the `[b"treasury"]` PDA on top of rent;
+    // 0 charges nothing
+    pub review_fee: u64,
+    // When false, add_comment rejects comments by the review's author, so authors
+    // can't farm comment rewards on their own reviews
+    pub allow_self_comment: bool,
+    // Fewest description bytes a new or updated review may have; 0 only requires a
+    // nonempty description
+    pub min_description_len: u64,
+    // Bumps of the `[b"token_mint"]`, `[b"token_auth"]`, `[b"comment_token_mint"]`
+    // and `[b"comment_token_auth"]` PDAs, recorded when the mints are created;
+    // 0 until then
+    pub mint_bump: u8,
+    pub mint_auth_bump: u8,
+    pub comment_mint_bump: u8,
+    pub comment_mint_auth_bump: u8,
+}
+
+// Per-author totals, stored at the `[author, b"profile"]` PDA
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct AuthorProfile {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub author: Pubkey,
+    pub review_count: u64,
+    // Raw amount of review reward tokens the author has earned, claimed or not
+    pub total_rewards: u64,
+    pub bump: u8,
+    // Unix time of the author's last rewarded review
+    pub last_review_ts: i64,
+    // Raw amount earned since the last ClaimRewards, which mints and zeroes it
+    pub pending_rewards: u64,
+}
+
+// Totals across every author's review of one movie, stored at the
+// `[MovieAggregate::title_hash(title), b"aggregate"]` PDA
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct MovieAggregate {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub title_hash: [u8; 32],
+    pub review_count: u64,
+    // Sum of current ratings; average is rating_sum / review_count
+    pub rating_sum: u64,
+    pub bump: u8,
+    // Reviews per star, index 0 holding 1-star reviews. Ratings above 5 on a wider
+    // configured scale count towards review_count and rating_sum only
+    pub rating_counts: [u64; MovieAggregate::RATING_BUCKETS],
+    // Most recently created review counted here; see MovieAccountState::next_review.
+    // Watchlist entries are never counted at creation, so they stay off the list
+    pub head_review: Option<Pubkey>,
+}
+
+// Count of reviews whose title starts with one character, for A-Z browsing without
+// a full scan. Stored at the `[b"index", IndexBucket::key(title)]` PDA
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct IndexBucket {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub key: u8,
+    pub review_count: u64,
+    pub bump: u8,
+}
+
+// Hands out MovieAccountState::global_seq, stored at the `[b"global_seq"]` PDA
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct GlobalSequence {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    // The last global_seq assigned; 0 until the first review
+    pub last_seq: u64,
+    pub bump: u8,
+}
+
+// Marks that a commenter has commented on a review, stored at the
+// `[review_pda, commenter, b"commented"]` PDA
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct CommenterMarker {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub bump: u8,
+}
+
+// Marks that a reporter has reported a review, stored at the
+// `[review_pda, reporter, b"report"]` PDA
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReportMarker {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub bump: u8,
+}
+
+// Records a reactor's single reaction to a comment, stored at the
+// `[comment_pda, reactor, b"react"]` PDA
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReactionMarker {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub bump: u8,
+    // Index into MovieComment::reactions
+    pub reaction_kind: u8,
+}
+
+// Marks that a voter has upvoted a review, stored at the
+// `[review_pda, voter, b"upvote"]` PDA
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct UpvoteMarker {
+    pub discriminator: String,
+    pub version: u8,
+    pub is_initialized: bool,
+    pub bump: u8,
 }
 
 // Use Sealed if account size is not dynamic
 impl Sealed for MovieAccountState {}
 impl Sealed for MovieCommentCounter {}
+impl Sealed for ProgramConfig {}
+impl Sealed for AuthorProfile {}
+impl Sealed for MovieAggregate {}
+impl Sealed for IndexBucket {}
+impl Sealed for GlobalSequence {}
+impl Sealed for CommenterMarker {}
+impl Sealed for ReportMarker {}
+impl Sealed for UpvoteMarker {}
+impl Sealed for ReactionMarker {}
 
 impl IsInitialized for MovieAccountState {
     fn is_initialized(&self) -> bool {
@@ -55,32 +279,800 @@ impl IsInitialized for MovieComment {
     }
 }
 
+impl IsInitialized for ProgramConfig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for AuthorProfile {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for MovieAggregate {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for CommenterMarker {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ReportMarker {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for UpvoteMarker {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for ReactionMarker {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for IndexBucket {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for GlobalSequence {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
 impl MovieAccountState {
     pub const DISCRIMINATOR: &'static str = "review";
+    pub const VERSION: u8 = 1;
+    // Bytes allocated for every review, enough for the longest seed title and
+    // description with the display title defaulting to the seed title
+    pub const ACCOUNT_LEN: usize = 1024;
+    // Written by AddMovieReviewV2, which fills in genre and timestamps
+    pub const DISCRIMINATOR_V2: &'static str = "review_v2";
+    pub const DISCRIMINATORS: [&'static str; 2] = [MovieAccountState::DISCRIMINATOR, MovieAccountState::DISCRIMINATOR_V2];
+    // In UTF-8 bytes, like every length limit here, since bytes are what the account
+    // stores; a description of four-byte emoji holds a quarter as many characters
+    pub const MAX_DESCRIPTION_LEN: usize = 800;
+    pub const STATUS_REVIEWED: u8 = 0;
+    // Watchlist entries, which may be added unrated (rating 0)
+    pub const STATUS_WANT_TO_WATCH: u8 = 1;
+    pub const STATUS_WATCHING: u8 = 2;
+    pub const GENRES: [&'static str; 8] = [
+        "action", "comedy", "documentary", "drama", "horror", "romance", "sci-fi", "thriller",
+    ];
 
-    pub fn get_account_size(title: String, description: String) -> usize {
-        // 4 bytes to store the size of the subsequent dynamic data string
-        return (4 + MovieAccountState::DISCRIMINATOR.len())
+    // An initialized V1 review, displayed under its seed title
+    pub fn new(review: Pubkey, title: String, rating: u8, description: String, bump: u8) -> Self {
+        MovieAccountState {
+            discriminator: MovieAccountState::DISCRIMINATOR.to_string(),
+            version: MovieAccountState::VERSION,
+            is_initialized: true,
+            review,
+            rating,
+            display_title: title.clone(),
+            title,
+            description,
+            genre: String::new(),
+            created_at: 0,
+            updated_at: 0,
+            bump,
+            has_spoilers: false,
+            status: MovieAccountState::STATUS_REVIEWED,
+            report_count: 0,
+            hidden: false,
+            edited: false,
+            pinned_comment: None,
+            expires_at: 0,
+            next_review: None,
+            global_seq: 0,
+            upvotes: 0,
+            rewarded_upvotes: 0,
+        }
+    }
+
+    pub fn get_account_size(title: &str, display_title: &str, description: &str, genre: &str) -> usize {
+        // 4 bytes to store the size of the subsequent dynamic data string,
+        // sized for the longer of the two discriminators
+        return (4 + MovieAccountState::DISCRIMINATOR_V2.len())
+            + 1 // for version
             + 1 // for is_initialized
+            + 32 // for review pubkey
             + 1 // for rating
             + (4 + title.len()) // 4 to store subsequent dynamic data string
-            + (4 + description.len()); // 4 to store subsequent dynamic data string
+            + (4 + description.len()) // 4 to store subsequent dynamic data string
+            + (4 + genre.len()) // 4 to store subsequent dynamic data string
+            + 8 // for created_at
+            + 8 // for updated_at
+            + 1 // for bump
+            + 1 // for has_spoilers
+            + (4 + display_title.len()) // 4 to store subsequent dynamic data string
+            + 1 // for status
+            + 8 // for report_count
+            + 1 // for hidden
+            + 1 // for edited
+            + 9 // for pinned_comment, reserved at its Some size
+            + 8 // for expires_at
+            + 33 // for next_review, reserved at its Some size
+            + 8 // for global_seq
+            + 4 // for upvotes
+            + 4; // for rewarded_upvotes
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+
+    // Exact Borsh size of this review as it would be written
+    pub fn serialized_len(&self) -> usize {
+        borsh_len(self)
     }
 }
 
 impl  MovieComment {
     pub const DISCRIMINATOR: &'static str = "comment";
-    pub fn get_account_size(comment: String) -> usize {
+    pub const VERSION: u8 = 1;
+    // Keeps BatchAddComments well inside the compute limit; each comment is a create_account CPI
+    pub const MAX_BATCH: usize = 5;
+    pub const REACTION_KINDS: usize = 4;
+    // Default cap on comments per review, unless the config sets its own
+    pub const MAX_COMMENTS_PER_REVIEW: u64 = 1_000;
+
+    // An initialized comment, not yet rewarded and without reactions
+    pub fn new(review: Pubkey, commenter: Pubkey, comment: String, count: u64, bump: u8) -> Self {
+        MovieComment {
+            discriminator: MovieComment::DISCRIMINATOR.to_string(),
+            version: MovieComment::VERSION,
+            is_initialized: true,
+            review,
+            commenter,
+            comment,
+            count,
+            bump,
+            rewarded: false,
+            reactions: [0; MovieComment::REACTION_KINDS],
+        }
+    }
+
+    pub fn get_account_size(comment: &str) -> usize {
         return (4 + MovieComment::DISCRIMINATOR.len())
+            + 1 // for version
             + 1 // for is_initialized
             + 32 // for movie review pubkey
             + 32 // for commenter pubkey
             + (4 + comment.len()) // 4 to store subsequent dynamic data string
-            + 8; // for count (u64)
+            + 8 // for count (u64)
+            + 1 // for bump
+            + 1 // for rewarded
+            + 4 * MovieComment::REACTION_KINDS; // for reactions (u32 each)
+    }
+
+    // Exact Borsh size of this comment as it would be written
+    pub fn serialized_len(&self) -> usize {
+        borsh_len(self)
     }
 }
 
 impl MovieCommentCounter {
     pub const DISCRIMINATOR: &'static str = "counter";
-    pub const SIZE: usize = (4 + MovieCommentCounter::DISCRIMINATOR.len()) + 1 + 8;
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize = (4 + MovieCommentCounter::DISCRIMINATOR.len()) + 1 + 1 + 8 + 1 + 8;
+
+    // An initialized counter at zero
+    pub fn new(bump: u8) -> Self {
+        MovieCommentCounter {
+            discriminator: MovieCommentCounter::DISCRIMINATOR.to_string(),
+            version: MovieCommentCounter::VERSION,
+            is_initialized: true,
+            counter: 0,
+            bump,
+            unique_commenters: 0,
+        }
+    }
+}
+
+impl ProgramConfig {
+    pub const DISCRIMINATOR: &'static str = "config";
+    pub const VERSION: u8 = 1;
+    // In whole tokens; see max_supply
+    pub const MAX_SUPPLY_TOKENS: u64 = 1_000_000_000;
+    pub const DEFAULT_MAX_RATING: u8 = 5;
+    pub const DEFAULT_DECIMALS: u8 = 9;
+    // Keeps MAX_SUPPLY_TOKENS representable as a raw u64 amount
+    pub const MAX_DECIMALS: u8 = 9;
+    pub const DEFAULT_TIP_FEE_BPS: u16 = 500;
+    pub const MAX_TIP_FEE_BPS: u16 = 10_000;
+    pub const DEFAULT_REPORT_THRESHOLD: u64 = 5;
+    pub const SIZE: usize = (4 + ProgramConfig::DISCRIMINATOR.len())
+        + 1 // for version
+        + 1 // for is_initialized
+        + 32 // for admin pubkey
+        + 8 // for slash_amount
+        + 1 // for bump
+        + 8 // for total_minted
+        + 1 // for max_rating
+        + 2 // for tip_fee_bps
+        + 8 // for max_reviews_per_author
+        + 1 // for decimals
+        + 8 // for report_threshold
+        + 8 // for max_comments_per_review
+        + 32 // for pending_admin pubkey
+        + 1 // for verbose_logs
+        + 8 // for review_fee
+        + 1 // for allow_self_comment
+        + 8 // for min_description_len
+        + 1 // for mint_bump
+        + 1 // for mint_auth_bump
+        + 1 // for comment_mint_bump
+        + 1; // for comment_mint_auth_bump
+
+    // Raw amount for `tokens` whole reward tokens at the configured decimals
+    pub fn token_amount(&self, tokens: u64) -> Result<u64, ProgramError> {
+        mul_u64(tokens, 10u64.pow(self.decimals as u32))
+    }
+
+    // Raw supply cap shared by both reward mints
+    pub fn max_supply(&self) -> Result<u64, ProgramError> {
+        self.token_amount(ProgramConfig::MAX_SUPPLY_TOKENS)
+    }
+
+    // Splits a tip into (treasury fee, author share). The fee is floored so the
+    // author gets the remainder; tips too small to give both sides at least one
+    // lamport are rejected rather than silently sending the treasury nothing.
+    pub fn split_tip(&self, amount: u64) -> Result<(u64, u64), ReviewError> {
+        let fee = (amount as u128 * self.tip_fee_bps as u128 / ProgramConfig::MAX_TIP_FEE_BPS as u128) as u64;
+        let author_share = amount - fee;
+        if author_share == 0 || (fee == 0 && self.tip_fee_bps > 0) {
+            return Err(ReviewError::InvalidTipAmount)
+        }
+        Ok((fee, author_share))
+    }
+}
+
+impl AuthorProfile {
+    pub const DISCRIMINATOR: &'static str = "profile";
+    pub const VERSION: u8 = 1;
+    // Seconds an author must wait after a rewarded review before the next one mints
+    pub const REWARD_COOLDOWN: i64 = 60;
+    // Default cap on an author's review_count, unless the config sets its own
+    pub const MAX_REVIEWS_PER_AUTHOR: u64 = 100;
+    pub const SIZE: usize = (4 + AuthorProfile::DISCRIMINATOR.len())
+        + 1 // for version
+        + 1 // for is_initialized
+        + 32 // for author pubkey
+        + 8 // for review_count
+        + 8 // for total_rewards
+        + 1 // for bump
+        + 8 // for last_review_ts
+        + 8; // for pending_rewards
+}
+
+impl MovieAggregate {
+    pub const DISCRIMINATOR: &'static str = "aggregate";
+    pub const VERSION: u8 = 1;
+    pub const RATING_BUCKETS: usize = 5;
+    pub const SIZE: usize = (4 + MovieAggregate::DISCRIMINATOR.len())
+        + 1 // for version
+        + 1 // for is_initialized
+        + 32 // for title_hash
+        + 8 // for review_count
+        + 8 // for rating_sum
+        + 1 // for bump
+        + 8 * MovieAggregate::RATING_BUCKETS // for rating_counts
+        + 33; // for head_review, reserved at its Some size
+
+    // Index into rating_counts for `rating`, if the histogram covers it
+    pub fn rating_bucket(rating: u8) -> Option<usize> {
+        (rating as usize).checked_sub(1).filter(|&bucket| bucket < MovieAggregate::RATING_BUCKETS)
+    }
+
+    // sha256 of the trimmed, lowercased title, so "Jaws" and " jaws" share an aggregate
+    pub fn title_hash(title: &str) -> [u8; 32] {
+        hash(title.trim().to_lowercase().as_bytes()).to_bytes()
+    }
+}
+
+impl IndexBucket {
+    pub const DISCRIMINATOR: &'static str = "index";
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize = (4 + IndexBucket::DISCRIMINATOR.len())
+        + 1 // for version
+        + 1 // for is_initialized
+        + 1 // for key
+        + 8 // for review_count
+        + 1; // for bump
+    // Shared by titles that don't start with an ASCII letter
+    pub const OTHER: u8 = b'#';
+
+    // First letter of the title, normalized like MovieAggregate::title_hash
+    pub fn key(title: &str) -> u8 {
+        match title.trim().bytes().next() {
+            Some(first) if first.is_ascii_alphabetic() => first.to_ascii_lowercase(),
+            _ => IndexBucket::OTHER,
+        }
+    }
+}
+
+impl GlobalSequence {
+    pub const DISCRIMINATOR: &'static str = "global_seq";
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize = (4 + GlobalSequence::DISCRIMINATOR.len())
+        + 1 // for version
+        + 1 // for is_initialized
+        + 8 // for last_seq
+        + 1; // for bump
+}
+
+impl CommenterMarker {
+    pub const DISCRIMINATOR: &'static str = "commented";
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize = (4 + CommenterMarker::DISCRIMINATOR.len())
+        + 1 // for version
+        + 1 // for is_initialized
+        + 1; // for bump
+}
+
+impl ReportMarker {
+    pub const DISCRIMINATOR: &'static str = "report";
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize = (4 + ReportMarker::DISCRIMINATOR.len())
+        + 1 // for version
+        + 1 // for is_initialized
+        + 1; // for bump
+}
+
+impl ReactionMarker {
+    pub const DISCRIMINATOR: &'static str = "reaction";
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize = (4 + ReactionMarker::DISCRIMINATOR.len())
+        + 1 // for version
+        + 1 // for is_initialized
+        + 1 // for bump
+        + 1; // for reaction_kind
+}
+
+impl UpvoteMarker {
+    pub const DISCRIMINATOR: &'static str = "upvote";
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize = (4 + UpvoteMarker::DISCRIMINATOR.len())
+        + 1 // for version
+        + 1 // for is_initialized
+        + 1; // for bump
+}
+
+// A rating that has been checked against a scale. Reviews still store a plain u8;
+// converting through this type on ingest keeps the range check in one place.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rating(u8);
+
+impl Rating {
+    pub const MIN: u8 = 1;
+
+    // Accepts MIN through `max_rating`, the scale configured in ProgramConfig
+    pub fn new(value: u8, max_rating: u8) -> Result<Self, ReviewError> {
+        if value < Rating::MIN || value > max_rating {
+            return Err(ReviewError::InvalidRating)
+        }
+        Ok(Rating(value))
+    }
+}
+
+// Checks against the default 1-5 scale
+impl TryFrom<u8> for Rating {
+    type Error = ReviewError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Rating::new(value, ProgramConfig::DEFAULT_MAX_RATING)
+    }
+}
+
+impl From<Rating> for u8 {
+    fn from(rating: Rating) -> u8 {
+        rating.0
+    }
+}
+
+// Serializes into a byte counter rather than a buffer, so measuring doesn't allocate
+fn borsh_len<T: BorshSerialize>(value: &T) -> usize {
+    struct ByteCounter(usize);
+
+    impl Write for ByteCounter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = ByteCounter(0);
+    value.serialize(&mut counter).unwrap();
+    counter.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::borsh::try_from_slice_unchecked;
+
+    #[test]
+    fn review_round_trips_version() {
+        let review = MovieAccountState {
+            discriminator: MovieAccountState::DISCRIMINATOR_V2.to_string(),
+            genre: "thriller".to_string(),
+            created_at: 1,
+            updated_at: 2,
+            has_spoilers: true,
+            ..MovieAccountState::new(Pubkey::new_unique(), "Jaws".to_string(), 4, "Bigger boat".to_string(), 255)
+        };
+        // Review accounts are allocated with padding, so read them back the same way
+        let mut data = review.try_to_vec().unwrap();
+        data.resize(1000, 0);
+
+        let decoded = try_from_slice_unchecked::<MovieAccountState>(&data).unwrap();
+        assert_eq!(decoded, review);
+        assert_eq!(decoded.version, MovieAccountState::VERSION);
+        assert_eq!(decoded.display_title, "Jaws");
+    }
+
+    #[test]
+    fn counter_round_trips_version_at_fixed_size() {
+        let counter = MovieCommentCounter { counter: 7, unique_commenters: 3, ..MovieCommentCounter::new(254) };
+        let data = counter.try_to_vec().unwrap();
+        assert_eq!(data.len(), MovieCommentCounter::SIZE);
+
+        let decoded = MovieCommentCounter::try_from_slice(&data).unwrap();
+        assert_eq!(decoded, counter);
+        assert_eq!(decoded.version, MovieCommentCounter::VERSION);
+    }
+
+    #[test]
+    fn comment_round_trips_version_at_computed_size() {
+        let comment = MovieComment {
+            rewarded: true,
+            reactions: [2, 0, 1, 0],
+            ..MovieComment::new(Pubkey::new_unique(), Pubkey::new_unique(), "Agreed".to_string(), 0, 253)
+        };
+        let data = comment.try_to_vec().unwrap();
+        assert_eq!(data.len(), MovieComment::get_account_size(&comment.comment));
+
+        let decoded = MovieComment::try_from_slice(&data).unwrap();
+        assert_eq!(decoded, comment);
+        assert_eq!(decoded.version, MovieComment::VERSION);
+    }
+
+    #[test]
+    fn config_round_trips_version_at_fixed_size() {
+        let config = ProgramConfig {
+            discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+            version: ProgramConfig::VERSION,
+            is_initialized: true,
+            admin: Pubkey::new_unique(),
+            slash_amount: 3,
+            bump: 252,
+            total_minted: 9,
+            max_rating: 10,
+            tip_fee_bps: 250,
+            max_reviews_per_author: 20,
+            decimals: 6,
+            report_threshold: 4,
+            max_comments_per_review: 50,
+            pending_admin: Pubkey::new_unique(),
+            verbose_logs: false,
+            review_fee: 5_000,
+            allow_self_comment: false,
+            min_description_len: 10,
+            mint_bump: 254,
+            mint_auth_bump: 253,
+            comment_mint_bump: 251,
+            comment_mint_auth_bump: 250,
+        };
+        let data = config.try_to_vec().unwrap();
+        assert_eq!(data.len(), ProgramConfig::SIZE);
+
+        let decoded = ProgramConfig::try_from_slice(&data).unwrap();
+        assert_eq!(decoded.version, ProgramConfig::VERSION);
+        assert_eq!(decoded.admin, config.admin);
+        assert_eq!(decoded.slash_amount, 3);
+        assert_eq!(decoded.total_minted, 9);
+        assert_eq!(decoded.max_rating, 10);
+        assert_eq!(decoded.tip_fee_bps, 250);
+        assert_eq!(decoded.max_reviews_per_author, 20);
+        assert_eq!(decoded.decimals, 6);
+        assert_eq!(decoded.pending_admin, config.pending_admin);
+        assert!(!decoded.verbose_logs);
+        assert_eq!(decoded.review_fee, 5_000);
+        assert!(!decoded.allow_self_comment);
+        assert_eq!(decoded.min_description_len, 10);
+        assert_eq!(decoded.mint_bump, 254);
+        assert_eq!(decoded.mint_auth_bump, 253);
+        assert_eq!(decoded.comment_mint_bump, 251);
+        assert_eq!(decoded.comment_mint_auth_bump, 250);
+        assert_eq!(decoded.token_amount(10), Ok(10_000_000));
+        assert_eq!(decoded.max_supply(), Ok(1_000_000_000_000_000));
+    }
+
+    #[test]
+    fn split_tip_floors_fee_and_rejects_dust() {
+        let mut config = ProgramConfig {
+            discriminator: ProgramConfig::DISCRIMINATOR.to_string(),
+            version: ProgramConfig::VERSION,
+            is_initialized: true,
+            admin: Pubkey::new_unique(),
+            slash_amount: 0,
+            bump: 255,
+            total_minted: 0,
+            max_rating: ProgramConfig::DEFAULT_MAX_RATING,
+            tip_fee_bps: ProgramConfig::DEFAULT_TIP_FEE_BPS,
+            max_reviews_per_author: AuthorProfile::MAX_REVIEWS_PER_AUTHOR,
+            decimals: ProgramConfig::DEFAULT_DECIMALS,
+            report_threshold: ProgramConfig::DEFAULT_REPORT_THRESHOLD,
+            max_comments_per_review: MovieComment::MAX_COMMENTS_PER_REVIEW,
+            pending_admin: Pubkey::default(),
+            verbose_logs: true,
+            review_fee: 0,
+            allow_self_comment: true,
+            min_description_len: 0,
+            mint_bump: 0,
+            mint_auth_bump: 0,
+            comment_mint_bump: 0,
+            comment_mint_auth_bump: 0,
+        };
+        assert_eq!(config.split_tip(1_000_000).unwrap(), (50_000, 950_000));
+        assert_eq!(config.split_tip(1_019).unwrap(), (50, 969));
+        assert_eq!(config.split_tip(20).unwrap(), (1, 19));
+        assert!(matches!(config.split_tip(19), Err(ReviewError::InvalidTipAmount)));
+        assert!(matches!(config.split_tip(0), Err(ReviewError::InvalidTipAmount)));
+        assert_eq!(config.split_tip(u64::MAX).unwrap().0, u64::MAX / 20);
+
+        // Without a fee the author gets everything, down to one lamport
+        config.tip_fee_bps = 0;
+        assert_eq!(config.split_tip(1).unwrap(), (0, 1));
+
+        // A 100% fee leaves the author nothing, so no tip can be split
+        config.tip_fee_bps = ProgramConfig::MAX_TIP_FEE_BPS;
+        assert!(matches!(config.split_tip(1_000), Err(ReviewError::InvalidTipAmount)));
+    }
+
+    #[test]
+    fn profile_round_trips_at_fixed_size() {
+        let profile = AuthorProfile {
+            discriminator: AuthorProfile::DISCRIMINATOR.to_string(),
+            version: AuthorProfile::VERSION,
+            is_initialized: true,
+            author: Pubkey::new_unique(),
+            review_count: 2,
+            total_rewards: 20,
+            bump: 251,
+            last_review_ts: 1_700_000_000,
+            pending_rewards: 10,
+        };
+        let data = profile.try_to_vec().unwrap();
+        assert_eq!(data.len(), AuthorProfile::SIZE);
+
+        let decoded = AuthorProfile::try_from_slice(&data).unwrap();
+        assert_eq!(decoded.author, profile.author);
+        assert_eq!(decoded.review_count, 2);
+        assert_eq!(decoded.total_rewards, 20);
+        assert_eq!(decoded.last_review_ts, 1_700_000_000);
+        assert_eq!(decoded.pending_rewards, 10);
+    }
+
+    #[test]
+    fn aggregate_round_trips_at_fixed_size() {
+        let aggregate = MovieAggregate {
+            discriminator: MovieAggregate::DISCRIMINATOR.to_string(),
+            version: MovieAggregate::VERSION,
+            is_initialized: true,
+            title_hash: MovieAggregate::title_hash("Jaws"),
+            review_count: 2,
+            rating_sum: 9,
+            bump: 252,
+            rating_counts: [0, 0, 0, 1, 1],
+            head_review: Some(Pubkey::new_unique()),
+        };
+        let data = aggregate.try_to_vec().unwrap();
+        assert_eq!(data.len(), MovieAggregate::SIZE);
+
+        let decoded = MovieAggregate::try_from_slice(&data).unwrap();
+        assert_eq!(decoded.title_hash, MovieAggregate::title_hash(" JAWS "));
+        assert_eq!(decoded.review_count, 2);
+        assert_eq!(decoded.rating_sum, 9);
+        assert_eq!(decoded.rating_counts, [0, 0, 0, 1, 1]);
+        assert_eq!(decoded.head_review, aggregate.head_review);
+    }
+
+    #[test]
+    fn rating_bucket_covers_one_to_five_stars() {
+        assert_eq!(MovieAggregate::rating_bucket(0), None);
+        assert_eq!(MovieAggregate::rating_bucket(1), Some(0));
+        assert_eq!(MovieAggregate::rating_bucket(5), Some(4));
+        assert_eq!(MovieAggregate::rating_bucket(6), None);
+    }
+
+    #[test]
+    fn rating_rejects_values_outside_scale() {
+        assert!(matches!(Rating::try_from(0), Err(ReviewError::InvalidRating)));
+        assert!(matches!(Rating::try_from(6), Err(ReviewError::InvalidRating)));
+        assert_eq!(u8::from(Rating::try_from(1).unwrap()), 1);
+        assert_eq!(u8::from(Rating::try_from(5).unwrap()), 5);
+
+        // A wider configured scale accepts what the default rejects
+        assert_eq!(u8::from(Rating::new(10, 10).unwrap()), 10);
+        assert!(matches!(Rating::new(11, 10), Err(ReviewError::InvalidRating)));
+    }
+
+    #[test]
+    fn review_account_size_matches_serialized_len() {
+        let mut review = MovieAccountState {
+            discriminator: MovieAccountState::DISCRIMINATOR_V2.to_string(),
+            version: MovieAccountState::VERSION,
+            is_initialized: true,
+            review: Pubkey::new_unique(),
+            rating: 5,
+            title: "Paterson".to_string(),
+            description: "Bus driver poet".to_string(),
+            genre: "drama".to_string(),
+            created_at: 1,
+            updated_at: 1,
+            bump: 250,
+            has_spoilers: false,
+            display_title: "Paterson (2016)".to_string(),
+            status: MovieAccountState::STATUS_WATCHING,
+            report_count: 2,
+            hidden: false,
+            edited: true,
+            pinned_comment: Some(1),
+            expires_at: 0,
+            next_review: Some(Pubkey::new_unique()),
+            global_seq: 7,
+            upvotes: 3,
+            rewarded_upvotes: 1,
+        };
+        let account_size = MovieAccountState::get_account_size(
+            &review.title, &review.display_title, &review.description, &review.genre
+        );
+        assert_eq!(review.serialized_len(), account_size);
+        assert_eq!(review.serialized_len(), review.try_to_vec().unwrap().len());
+
+        // get_account_size is sized for the V2 discriminator, so V1 reviews come in under it
+        review.discriminator = MovieAccountState::DISCRIMINATOR.to_string();
+        assert_eq!(
+            review.serialized_len() + MovieAccountState::DISCRIMINATOR_V2.len() - MovieAccountState::DISCRIMINATOR.len(),
+            account_size
+        );
+    }
+
+    #[test]
+    fn comment_account_size_matches_serialized_len() {
+        let comment = MovieComment::new(Pubkey::new_unique(), Pubkey::new_unique(), "Poetry in motion".to_string(), 3, 249);
+        assert_eq!(comment.serialized_len(), MovieComment::get_account_size(&comment.comment));
+        assert_eq!(comment.serialized_len(), comment.try_to_vec().unwrap().len());
+    }
+
+    // Hand-added sizes, so the test doesn't share any arithmetic with get_account_size
+    #[test]
+    fn review_account_size_of_known_inputs() {
+        let fixed = (4 + 9) // "review_v2" discriminator
+            + 1 + 1 + 32 + 1 // version, is_initialized, review, rating
+            + 8 + 8 + 1 + 1 // created_at, updated_at, bump, has_spoilers
+            + 1 + 8 + 1 + 1 // status, report_count, hidden, edited
+            + 9 + 8 + 33 + 8 // pinned_comment, expires_at, next_review, global_seq
+            + 4 + 4; // upvotes, rewarded_upvotes
+        assert_eq!(fixed, 143);
+        // Four length prefixes for title, description, genre and display_title
+        assert_eq!(MovieAccountState::get_account_size("", "", "", ""), fixed + 4 * 4);
+        assert_eq!(MovieAccountState::get_account_size("abc", "abc", "hello", ""), 170);
+        assert_eq!(MovieAccountState::get_account_size("abc", "abc", "hello", "drama"), 175);
+    }
+
+    #[test]
+    fn comment_account_size_of_known_inputs() {
+        let fixed = (4 + 7) // "comment" discriminator
+            + 1 + 1 + 32 + 32 // version, is_initialized, review, commenter
+            + 8 + 1 + 1 // count, bump, rewarded
+            + 4 * 4; // four u32 reaction counts
+        assert_eq!(fixed, 103);
+        assert_eq!(MovieComment::get_account_size(""), fixed + 4);
+        assert_eq!(MovieComment::get_account_size("hello"), 112);
+    }
+
+    // Struct literals rather than `new`, so a field added without updating the size
+    // computation stops this test from compiling instead of slipping through
+    #[test]
+    fn fully_populated_accounts_serialize_to_their_allocated_size() {
+        let review = MovieAccountState {
+            discriminator: MovieAccountState::DISCRIMINATOR_V2.to_string(),
+            version: MovieAccountState::VERSION,
+            is_initialized: true,
+            review: Pubkey::new_unique(),
+            rating: u8::MAX,
+            title: "Stalker".to_string(),
+            description: "Three men walk into the Zone".to_string(),
+            genre: "scifi".to_string(),
+            created_at: i64::MAX,
+            updated_at: i64::MAX,
+            bump: 255,
+            has_spoilers: true,
+            display_title: "Stalker (Tarkovsky, 1979)".to_string(),
+            status: MovieAccountState::STATUS_WANT_TO_WATCH,
+            report_count: u64::MAX,
+            hidden: true,
+            edited: true,
+            pinned_comment: Some(u64::MAX),
+            expires_at: i64::MAX,
+            next_review: Some(Pubkey::new_unique()),
+            global_seq: u64::MAX,
+            upvotes: u32::MAX,
+            rewarded_upvotes: u32::MAX,
+        };
+        assert_eq!(
+            review.try_to_vec().unwrap().len(),
+            MovieAccountState::get_account_size(&review.title, &review.display_title, &review.description, &review.genre)
+        );
+
+        let comment = MovieComment {
+            discriminator: MovieComment::DISCRIMINATOR.to_string(),
+            version: MovieComment::VERSION,
+            is_initialized: true,
+            review: Pubkey::new_unique(),
+            commenter: Pubkey::new_unique(),
+            comment: "The dog steals the film".to_string(),
+            count: u64::MAX,
+            bump: 255,
+            rewarded: true,
+            reactions: [u32::MAX; MovieComment::REACTION_KINDS],
+        };
+        assert_eq!(comment.try_to_vec().unwrap().len(), MovieComment::get_account_size(&comment.comment));
+
+        let counter = MovieCommentCounter {
+            discriminator: MovieCommentCounter::DISCRIMINATOR.to_string(),
+            version: MovieCommentCounter::VERSION,
+            is_initialized: true,
+            counter: u64::MAX,
+            bump: 255,
+            unique_commenters: u64::MAX,
+        };
+        assert_eq!(counter.try_to_vec().unwrap().len(), MovieCommentCounter::SIZE);
+
+        let sequence = GlobalSequence {
+            discriminator: GlobalSequence::DISCRIMINATOR.to_string(),
+            version: GlobalSequence::VERSION,
+            is_initialized: true,
+            last_seq: u64::MAX,
+            bump: 255,
+        };
+        assert_eq!(sequence.try_to_vec().unwrap().len(), GlobalSequence::SIZE);
+    }
+
+    #[test]
+    fn index_bucket_key_uses_normalized_first_letter() {
+        assert_eq!(IndexBucket::key("Inception"), b'i');
+        assert_eq!(IndexBucket::key("  it follows"), b'i');
+        assert_eq!(IndexBucket::key("2001: A Space Odyssey"), IndexBucket::OTHER);
+        assert_eq!(IndexBucket::key("\u{c9}lite"), IndexBucket::OTHER);
+
+        let bucket = IndexBucket {
+            discriminator: IndexBucket::DISCRIMINATOR.to_string(),
+            version: IndexBucket::VERSION,
+            is_initialized: true,
+            key: b'i',
+            review_count: 3,
+            bump: 255,
+        };
+        assert_eq!(bucket.try_to_vec().unwrap().len(), IndexBucket::SIZE);
+    }
 }